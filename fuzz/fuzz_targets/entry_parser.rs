@@ -0,0 +1,37 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use teamy_robocopy::robocopy::robocopy_log_parser::RobocopyLogParser;
+use teamy_robocopy::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+
+/// A complete header, so the fuzzed bytes are read as entries and summaries.
+const HEADER: &str = "\
+-------------------------------------------------------------------------------
+   ROBOCOPY     ::     Robust File Copy for Windows
+-------------------------------------------------------------------------------
+
+  Started : August 27, 2025 10:19:37 PM
+   Source : J:\\
+     Dest : K:\\
+
+    Files : *.*
+
+  Options : *.* /S /E
+
+------------------------------------------------------------------------------
+";
+
+fuzz_target!(|input: (u8, &[u8])| {
+    let (chunk_size, bytes) = input;
+    let mut parser = RobocopyLogParser::new();
+    parser.accept(HEADER);
+    for chunk in bytes.chunks(usize::from(chunk_size.max(1))) {
+        parser.accept_bytes(chunk);
+        // Errors are fine, panics and getting stuck are not
+        for _ in 0..=bytes.len() {
+            if let Ok(RobocopyParseAdvance::NeedMoreData) = parser.advance() {
+                break;
+            }
+        }
+    }
+});
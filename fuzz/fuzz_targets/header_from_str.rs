@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use teamy_robocopy::robocopy::robocopy_header::RobocopyHeader;
+
+fuzz_target!(|text: &str| {
+    let Ok(header) = text.parse::<RobocopyHeader>() else {
+        return;
+    };
+    // Whatever was accepted must survive being written out and read again
+    let reparsed: RobocopyHeader = header
+        .to_string()
+        .parse()
+        .expect("a displayed header parses");
+    assert_eq!(reparsed, header);
+});
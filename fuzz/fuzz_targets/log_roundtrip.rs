@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use teamy_robocopy::robocopy::robocopy_log::RobocopyLog;
+
+fuzz_target!(|logs: Vec<RobocopyLog>| {
+    let text: String = logs.iter().map(ToString::to_string).collect();
+    let parsed = RobocopyLog::parse_all(&text).expect("rendered logs parse");
+    assert_eq!(parsed, logs);
+});
@@ -0,0 +1,171 @@
+use crate::alert::alert_engine::RobocopyAlert;
+use crate::alert::alert_rule::AlertAction;
+use eyre::WrapErr;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// How long a webhook may take to answer.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl AlertAction {
+    /// Deliver an alert.
+    ///
+    /// Commands run through `sh -c`, or `cmd /C` on Windows, and are waited for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command cannot be started or exits unsuccessfully,
+    /// the file cannot be appended to, or the webhook does not answer with a
+    /// success status.
+    pub fn run(&self, alert: &RobocopyAlert) -> eyre::Result<()> {
+        let json = serde_json::to_string(alert)?;
+        match self {
+            AlertAction::Command(command) => {
+                let mut child = shell(command)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .wrap_err_with(|| format!("Failed to run `{command}`"))?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    // A command that ignores its input may exit before reading it
+                    let _ = stdin.write_all(json.as_bytes());
+                }
+                let status = child.wait()?;
+                if !status.success() {
+                    eyre::bail!("`{command}` failed with {status}");
+                }
+            }
+            AlertAction::Jsonl(path) => {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+                // One write per line so concurrent writers do not interleave
+                file.write_all(format!("{json}\n").as_bytes())?;
+            }
+            AlertAction::Webhook(url) => {
+                let agent: ureq::Agent = ureq::Agent::config_builder()
+                    .timeout_global(Some(WEBHOOK_TIMEOUT))
+                    .build()
+                    .into();
+                agent
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .send(json.as_bytes())
+                    .wrap_err_with(|| format!("Failed to POST the alert to {url}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn alert() -> RobocopyAlert {
+        RobocopyAlert {
+            rule: "disk-full".to_string(),
+            reason: "error 112 (ERROR_DISK_FULL)".to_string(),
+            log: "robocopy.log".to_string(),
+            source: Some(r"C:\source\".to_string()),
+            dest: Some(r"D:\dest\".to_string()),
+            time: "2025-08-27T22:19:37+00:00".to_string(),
+            error: None,
+        }
+    }
+
+    /// The request line and body a stub received.
+    type StubRequest = std::thread::JoinHandle<eyre::Result<(String, String)>>;
+
+    /// Accept one request and hand back its request line and body.
+    fn http_stub() -> eyre::Result<(String, StubRequest)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/hooks/robocopy", listener.local_addr()?);
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept()?;
+            let mut reader = BufReader::new(stream.try_clone()?);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line)?;
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header)?;
+                if header.trim_end().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    length = value.trim().parse()?;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")?;
+            Ok((request_line, String::from_utf8(body)?))
+        });
+        Ok((url, handle))
+    }
+
+    #[test]
+    fn delivers_to_every_kind_of_action() -> eyre::Result<()> {
+        let alert = alert();
+        let dir = tempfile::tempdir()?;
+
+        let jsonl = dir.path().join("alerts.jsonl");
+        AlertAction::Jsonl(jsonl.clone()).run(&alert)?;
+        AlertAction::Jsonl(jsonl.clone()).run(&alert)?;
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&jsonl)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["rule"], "disk-full");
+
+        let (url, stub) = http_stub()?;
+        AlertAction::Webhook(url).run(&alert)?;
+        let (request_line, body) = stub.join().expect("stub thread panicked")?;
+        assert!(
+            request_line.starts_with("POST /hooks/robocopy "),
+            "{request_line}"
+        );
+        assert_eq!(body, serde_json::to_string(&alert)?);
+
+        #[cfg(unix)]
+        {
+            let copy = dir.path().join("stdin.json");
+            AlertAction::Command(format!("cat > '{}'", copy.display())).run(&alert)?;
+            assert_eq!(
+                std::fs::read_to_string(copy)?,
+                serde_json::to_string(&alert)?
+            );
+            assert!(
+                AlertAction::Command("exit 3".to_string())
+                    .run(&alert)
+                    .is_err()
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use crate::alert::alert_rule::AlertCondition;
+use crate::alert::alert_rule::AlertRule;
+use crate::robocopy::robocopy_job_status::RobocopyJobState;
+use crate::robocopy::robocopy_job_status::RobocopyJobStatus;
+use crate::robocopy::robocopy_job_status::RobocopyJobThresholds;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_win32_error::win32_error_info;
+use chrono::DateTime;
+use chrono::Local;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A rule that fired, as handed to its actions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RobocopyAlert {
+    pub rule: String,
+    /// What happened, e.g. `error 112 (ERROR_DISK_FULL)`.
+    pub reason: String,
+    pub log: String,
+    pub source: Option<String>,
+    pub dest: Option<String>,
+    /// When the rule fired, RFC 3339.
+    pub time: String,
+    /// The error that fired an error rule.
+    pub error: Option<RobocopyAlertError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RobocopyAlertError {
+    pub code: u32,
+    pub path: Option<String>,
+    pub message: String,
+    /// When robocopy logged it, RFC 3339.
+    pub logged: String,
+}
+
+/// Evaluates rules against one log as it is parsed.
+///
+/// Conditions are checked against the job the log is on; a header starts a
+/// new job and re-arms every rule.
+#[derive(Debug)]
+pub struct RobocopyAlertEngine {
+    rules: Vec<AlertRule>,
+    log: String,
+    source: Option<String>,
+    dest: Option<String>,
+    status: RobocopyJobStatus,
+    resolver: RobocopyPathResolver,
+    // Times of errors still inside the widest error-rate window
+    recent_errors: VecDeque<DateTime<Local>>,
+    // Per rule, whether it fired and has not re-armed yet
+    fired: Vec<bool>,
+}
+
+impl RobocopyAlertEngine {
+    #[must_use]
+    pub fn new(rules: Vec<AlertRule>, log: impl Into<String>) -> Self {
+        Self {
+            fired: vec![false; rules.len()],
+            rules,
+            log: log.into(),
+            source: None,
+            dest: None,
+            status: RobocopyJobStatus::new(),
+            resolver: RobocopyPathResolver::new(),
+            recent_errors: VecDeque::new(),
+        }
+    }
+
+    /// Start over, e.g. after the log was truncated or rotated; every rule re-arms.
+    pub fn reset(&mut self) {
+        *self = Self::new(
+            std::mem::take(&mut self.rules),
+            std::mem::take(&mut self.log),
+        );
+    }
+
+    /// Handle anything the parser produced, returning the alerts it fired.
+    pub fn push_advance(&mut self, advance: &RobocopyParseAdvance) -> Vec<RobocopyAlert> {
+        self.status.push_advance(advance);
+        let mut alerts = Vec::new();
+        match advance {
+            RobocopyParseAdvance::NeedMoreData => return alerts,
+            RobocopyParseAdvance::Header(header) => {
+                self.source = Some(header.source.display().to_string());
+                self.dest = Some(header.dest.display().to_string());
+                self.resolver = RobocopyPathResolver::new();
+                self.recent_errors.clear();
+                self.fired.fill(false);
+            }
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry, &mut alerts),
+            RobocopyParseAdvance::Summary(_) => {}
+        }
+        // Output means the job is no longer stalled
+        for (rule, fired) in self.rules.iter().zip(&mut self.fired) {
+            if matches!(rule.when, AlertCondition::StalledForSeconds(_)) {
+                *fired = false;
+            }
+        }
+        alerts
+    }
+
+    /// Check time-based rules, given how long the log has had no new output.
+    pub fn tick(&mut self, idle: Duration) -> Vec<RobocopyAlert> {
+        let mut alerts = Vec::new();
+        for index in 0..self.rules.len() {
+            let AlertCondition::StalledForSeconds(seconds) = self.rules[index].when else {
+                continue;
+            };
+            let thresholds = RobocopyJobThresholds {
+                stall_after: Duration::from_secs(seconds),
+                abandon_after: Duration::MAX,
+            };
+            if self.status.started()
+                && self.status.state(idle, &thresholds) == RobocopyJobState::Stalled
+                && !self.fired[index]
+            {
+                self.fired[index] = true;
+                let reason = format!(
+                    "no output for {}",
+                    humantime::format_duration(Duration::from_secs(idle.as_secs()))
+                );
+                alerts.push(self.alert(index, reason, None));
+            }
+        }
+        alerts
+    }
+
+    fn push(&mut self, entry: &RobocopyLogEntry, alerts: &mut Vec<RobocopyAlert>) {
+        let path = self.resolver.resolve(entry);
+        if let RobocopyLogEntry::Retry { .. } = entry {
+            for index in 0..self.rules.len() {
+                if let AlertCondition::Retries(retries) = self.rules[index].when
+                    && self.status.retries() >= retries
+                    && !self.fired[index]
+                {
+                    self.fired[index] = true;
+                    let reason = format!("{} retries", self.status.retries());
+                    alerts.push(self.alert(index, reason, None));
+                }
+            }
+            return;
+        }
+        let (code, when, message) = match entry {
+            RobocopyLogEntry::Error {
+                code,
+                when,
+                message,
+                ..
+            } => (*code, *when, message.as_str()),
+            RobocopyLogEntry::AccessDeniedError { when, .. } => (
+                entry.error_code().unwrap_or_default(),
+                *when,
+                ACCESS_DENIED_MESSAGE,
+            ),
+            _ => return,
+        };
+        let error = RobocopyAlertError {
+            code,
+            path: path.map(|path| path.to_string_lossy().into_owned()),
+            message: message.to_string(),
+            logged: when.to_rfc3339(),
+        };
+        let widest = self
+            .rules
+            .iter()
+            .filter_map(|rule| match rule.when {
+                AlertCondition::ErrorRate { within_seconds, .. } => Some(within_seconds),
+                _ => None,
+            })
+            .max();
+        match widest {
+            Some(widest) => {
+                self.recent_errors.push_back(when);
+                while self.recent_errors.front().is_some_and(|first| {
+                    (when - *first).num_seconds() > i64::try_from(widest).unwrap_or(i64::MAX)
+                }) {
+                    self.recent_errors.pop_front();
+                }
+            }
+            None => self.recent_errors.clear(),
+        }
+        for index in 0..self.rules.len() {
+            match self.rules[index].when {
+                AlertCondition::ErrorCode(wanted) if wanted == code => {
+                    let name = win32_error_info(code)
+                        .map_or(String::new(), |info| format!(" ({})", info.name));
+                    alerts.push(self.alert(
+                        index,
+                        format!("error {code}{name}"),
+                        Some(error.clone()),
+                    ));
+                }
+                AlertCondition::ErrorRate {
+                    errors,
+                    within_seconds,
+                } => {
+                    let within = i64::try_from(within_seconds).unwrap_or(i64::MAX);
+                    let count = self
+                        .recent_errors
+                        .iter()
+                        .filter(|logged| (when - **logged).num_seconds() <= within)
+                        .count();
+                    if count < errors {
+                        self.fired[index] = false;
+                    } else if !self.fired[index] {
+                        self.fired[index] = true;
+                        let reason = format!(
+                            "{count} errors within {}",
+                            humantime::format_duration(Duration::from_secs(within_seconds))
+                        );
+                        alerts.push(self.alert(index, reason, Some(error.clone())));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn alert(
+        &self,
+        index: usize,
+        reason: String,
+        error: Option<RobocopyAlertError>,
+    ) -> RobocopyAlert {
+        RobocopyAlert {
+            rule: self.rules[index].name.clone(),
+            reason,
+            log: self.log.clone(),
+            source: self.source.clone(),
+            dest: self.dest.clone(),
+            time: Local::now().to_rfc3339(),
+            error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::alert_rule::AlertRules;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use std::path::PathBuf;
+
+    #[test]
+    fn fires_each_condition() -> eyre::Result<()> {
+        let rules: AlertRules = serde_json::from_str(
+            r#"{ "rules": [
+                { "name": "access", "when": { "error_code": 5 }, "actions": [] },
+                { "name": "burst", "when": { "error_rate": { "errors": 3, "within_seconds": 60 } }, "actions": [] },
+                { "name": "stalled", "when": { "stalled_for_seconds": 300 }, "actions": [] },
+                { "name": "retrying", "when": { "retries": 2 }, "actions": [] }
+            ] }"#,
+        )?;
+        let log = RobocopyLog::parse_all(include_str!("../robocopy/sample.txt"))?.remove(0);
+        let mut engine = RobocopyAlertEngine::new(rules.rules, "sample.txt");
+        let mut fired = engine.push_advance(&RobocopyParseAdvance::Header(log.header.clone()));
+        for entry in &log.parts {
+            fired.extend(engine.push_advance(&RobocopyParseAdvance::LogEntry(entry.clone())));
+        }
+        // The sample opens with two unreadable directories
+        let names: Vec<_> = fired.iter().map(|alert| alert.rule.as_str()).collect();
+        assert_eq!(names, ["access", "access"]);
+        assert_eq!(fired[0].source.as_deref(), Some(r"J:\"));
+        assert_eq!(
+            fired[0]
+                .error
+                .as_ref()
+                .and_then(|error| error.path.as_deref()),
+            Some(r"J:\$RECYCLE.BIN\")
+        );
+
+        let error = RobocopyLogEntry::Error {
+            when: *log.header.started.as_datetime(),
+            code: 32,
+            operation: "Copying File".to_string(),
+            path: PathBuf::from("busy.txt"),
+            message: "The process cannot access the file.".to_string(),
+        };
+        let mut fired = engine.push_advance(&RobocopyParseAdvance::LogEntry(error.clone()));
+        fired.extend(engine.push_advance(&RobocopyParseAdvance::LogEntry(
+            RobocopyLogEntry::Retry {
+                wait: Duration::from_secs(5),
+            },
+        )));
+        fired.extend(engine.push_advance(&RobocopyParseAdvance::LogEntry(error.clone())));
+        fired.extend(engine.push_advance(&RobocopyParseAdvance::LogEntry(
+            RobocopyLogEntry::Retry {
+                wait: Duration::from_secs(5),
+            },
+        )));
+        let names: Vec<_> = fired.iter().map(|alert| alert.rule.as_str()).collect();
+        assert_eq!(names, ["burst", "retrying"]);
+        assert!(
+            fired[0].reason.starts_with("3 errors"),
+            "{}",
+            fired[0].reason
+        );
+
+        // Quiet for a while, but robocopy said it would wait 5 seconds
+        assert!(engine.tick(Duration::from_secs(200)).is_empty());
+        let fired = engine.tick(Duration::from_secs(400));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule, "stalled");
+        assert!(engine.tick(Duration::from_secs(500)).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use eyre::WrapErr;
+use serde::Deserialize;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// What makes a rule fire.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum AlertCondition {
+    /// Every error with this Win32 code, e.g. `112` for a full disk.
+    ErrorCode(u32),
+    /// At least `errors` errors logged within `within_seconds` of each other;
+    /// fires again once the rate has dropped below and climbed back.
+    ErrorRate { errors: usize, within_seconds: u64 },
+    /// No output for this long, not counting a retry wait robocopy announced;
+    /// fires again after output resumes and stops again.
+    StalledForSeconds(u64),
+    /// The job has announced this many retries; fires once per job.
+    Retries(u64),
+}
+
+/// What to do when a rule fires. Each gets the alert as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum AlertAction {
+    /// Run a shell command with the alert on its standard input.
+    Command(String),
+    /// Append the alert as one line to a JSON Lines file.
+    Jsonl(PathBuf),
+    /// POST the alert to a URL.
+    Webhook(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    pub name: String,
+    pub when: AlertCondition,
+    pub actions: Vec<AlertAction>,
+}
+
+/// A rules file, for example:
+///
+/// ```json
+/// { "rules": [
+///     { "name": "disk-full", "when": { "error_code": 112 },
+///       "actions": [{ "webhook": "https://hooks.example.com/robocopy" }] },
+///     { "name": "stalled", "when": { "stalled_for_seconds": 900 },
+///       "actions": [{ "jsonl": "alerts.jsonl" }, { "command": "logger -t robocopy" }] }
+/// ] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRules {
+    pub rules: Vec<AlertRule>,
+}
+
+impl AlertRules {
+    /// Read a rules file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid rules file.
+    pub fn read(path: &Path) -> eyre::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&text)
+            .wrap_err_with(|| format!("{} is not a valid rules file", path.display()))
+    }
+}
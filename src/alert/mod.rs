@@ -0,0 +1,3 @@
+pub mod alert_action;
+pub mod alert_engine;
+pub mod alert_rule;
@@ -0,0 +1,32 @@
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use std::path::PathBuf;
+
+/// An arbitrary path that survives a round trip through the command line.
+///
+/// Clap rejects empty paths and reads a leading `-` as a flag.
+pub(crate) fn arbitrary_path(u: &mut Unstructured<'_>) -> arbitrary::Result<PathBuf> {
+    let path = String::arbitrary(u)?;
+    let path = path.trim_start_matches('-');
+    Ok(PathBuf::from(if path.is_empty() {
+        "robocopy.log"
+    } else {
+        path
+    }))
+}
+
+pub(crate) fn arbitrary_optional_path(
+    u: &mut Unstructured<'_>,
+) -> arbitrary::Result<Option<PathBuf>> {
+    Ok(if bool::arbitrary(u)? {
+        Some(arbitrary_path(u)?)
+    } else {
+        None
+    })
+}
+
+/// One to three arbitrary paths, for commands that require at least one.
+pub(crate) fn arbitrary_paths(u: &mut Unstructured<'_>) -> arbitrary::Result<Vec<PathBuf>> {
+    let count = u.int_in_range(1..=3)?;
+    (0..count).map(|_| arbitrary_path(u)).collect()
+}
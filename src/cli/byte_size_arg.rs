@@ -0,0 +1,10 @@
+use bytesize::ByteSize;
+
+/// Parse a size such as `1.5 TiB`, `200GB` or a plain number of bytes.
+///
+/// # Errors
+///
+/// Returns a message clap shows when the value is not a size.
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    value.trim().parse::<ByteSize>().map(|size| size.as_u64())
+}
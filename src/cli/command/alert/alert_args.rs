@@ -0,0 +1,298 @@
+use crate::alert::alert_engine::RobocopyAlert;
+use crate::alert::alert_engine::RobocopyAlertEngine;
+use crate::alert::alert_rule::AlertRule;
+use crate::alert::alert_rule::AlertRules;
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::to_args::ToArgs;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::tail::log_directory_tailer::LogDirectoryEvent;
+use crate::tail::log_directory_tailer::LogDirectoryTailer;
+use crate::tail::log_directory_tailer::LogWatchTarget;
+use arbitrary::Arbitrary;
+use clap::Args;
+use crossbeam_channel::RecvTimeoutError;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use tracing::info;
+use tracing::warn;
+
+/// How often quiet logs are checked for stalls.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct AlertArgs {
+    /// Path to the robocopy log file, or a directory or glob such as
+    /// `logs\*.log` to watch every log in it
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// JSON file of rules: conditions such as an error code, error rate,
+    /// stall or retry count, and the commands, files or webhooks to alert
+    #[arg(long)]
+    #[arbitrary(with = arbitrary_path)]
+    pub rules: PathBuf,
+    /// Also alert on what the logs already held when watching started
+    #[arg(long)]
+    pub include_existing: bool,
+}
+
+struct WatchedLog {
+    path: PathBuf,
+    parser: RobocopyLogParser,
+    engine: RobocopyAlertEngine,
+    caught_up: bool,
+    // Set when the log fails to parse, until it is truncated or replaced
+    failed: bool,
+    last_output: SystemTime,
+}
+
+impl AlertArgs {
+    /// Follow logs and run the actions of every rule that fires, until interrupted.
+    ///
+    /// A log that cannot be parsed is skipped with a warning until it is
+    /// truncated or replaced; the other logs are still watched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rules cannot be read or the logs cannot be
+    /// watched. Failed actions are logged and do not stop watching.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let rules = AlertRules::read(&self.rules)?.rules;
+        let target = LogWatchTarget::from_path(&self.robocopy_log_file_path)
+            .unwrap_or_else(|| LogWatchTarget::file(&self.robocopy_log_file_path));
+        let rx = LogDirectoryTailer::default().watch(target)?;
+        info!(
+            "Watching {} with {} rule(s)",
+            self.robocopy_log_file_path.display(),
+            rules.len()
+        );
+        let mut watch = AlertWatch::new(rules.clone(), self.include_existing);
+        loop {
+            match rx.recv_timeout(TICK_INTERVAL) {
+                Ok(event) => fire(&rules, &watch.push_event(event)),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            fire(&rules, &watch.tick());
+        }
+    }
+}
+
+/// The rules and every log they are checked against.
+struct AlertWatch {
+    rules: Vec<AlertRule>,
+    include_existing: bool,
+    logs: Vec<WatchedLog>,
+}
+
+impl AlertWatch {
+    fn new(rules: Vec<AlertRule>, include_existing: bool) -> Self {
+        Self {
+            rules,
+            include_existing,
+            logs: Vec::new(),
+        }
+    }
+
+    /// Take in whatever the directory tailer observed, returning the alerts to deliver.
+    fn push_event(&mut self, event: LogDirectoryEvent) -> Vec<RobocopyAlert> {
+        match event {
+            LogDirectoryEvent::Added { path, .. } => self.logs.push(WatchedLog {
+                parser: RobocopyLogParser::new(),
+                engine: RobocopyAlertEngine::new(self.rules.clone(), path.display().to_string()),
+                path,
+                caught_up: self.include_existing,
+                failed: false,
+                last_output: SystemTime::now(),
+            }),
+            LogDirectoryEvent::CaughtUp { log } => {
+                if let Some(log) = self.logs.get_mut(log) {
+                    log.caught_up = true;
+                    // What was read at startup may have been written long ago
+                    if let Ok(modified) =
+                        std::fs::metadata(&log.path).and_then(|metadata| metadata.modified())
+                    {
+                        log.last_output = modified;
+                    }
+                }
+            }
+            LogDirectoryEvent::Tail { log, event } => {
+                let Some(log) = self.logs.get_mut(log) else {
+                    return Vec::new();
+                };
+                log.last_output = SystemTime::now();
+                if event.is_reset() {
+                    log.engine.reset();
+                    log.failed = false;
+                } else if log.failed {
+                    return Vec::new();
+                }
+                event.feed(&mut log.parser);
+                return log.parse();
+            }
+        }
+        Vec::new()
+    }
+
+    /// Check every log for stalls.
+    fn tick(&mut self) -> Vec<RobocopyAlert> {
+        let mut alerts = Vec::new();
+        for log in self.logs.iter_mut().filter(|log| !log.failed) {
+            let idle = SystemTime::now()
+                .duration_since(log.last_output)
+                .unwrap_or_default();
+            let fired = log.engine.tick(idle);
+            if log.caught_up {
+                alerts.extend(fired);
+            }
+        }
+        alerts
+    }
+}
+
+impl WatchedLog {
+    /// Run everything the parser can produce past the rules.
+    fn parse(&mut self) -> Vec<RobocopyAlert> {
+        let mut alerts = Vec::new();
+        loop {
+            let advance = match self.parser.advance() {
+                Ok(advance) => advance,
+                Err(error) => {
+                    warn!(
+                        "Not alerting on {} until it is replaced: {error:?}",
+                        self.path.display()
+                    );
+                    self.failed = true;
+                    return alerts;
+                }
+            };
+            let fired = self.engine.push_advance(&advance);
+            if self.caught_up {
+                alerts.extend(fired);
+            }
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                return alerts;
+            }
+        }
+    }
+}
+
+fn fire(rules: &[AlertRule], alerts: &[RobocopyAlert]) {
+    for alert in alerts {
+        info!("Alert {}: {} in {}", alert.rule, alert.reason, alert.log);
+        for rule in rules.iter().filter(|rule| rule.name == alert.rule) {
+            for action in &rule.actions {
+                if let Err(error) = action.run(alert) {
+                    warn!("Alert {} could not be delivered: {error:?}", alert.rule);
+                }
+            }
+        }
+    }
+}
+
+impl ToArgs for AlertArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            self.robocopy_log_file_path.clone().into(),
+            "--rules".into(),
+            self.rules.clone().into(),
+        ];
+        if self.include_existing {
+            args.push("--include-existing".into());
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tail::polling_log_tailer::PollingLogTailer;
+    use crate::tail::tail_event::TailEvent;
+    use std::io::Write;
+
+    #[test]
+    fn alerts_on_the_first_write_to_a_new_or_empty_log() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sample = include_bytes!("../../../robocopy/sample.txt");
+        std::fs::write(dir.path().join("old.log"), sample)?;
+        std::fs::write(dir.path().join("empty.log"), b"")?;
+        let rules: AlertRules = serde_json::from_str(
+            r#"{ "rules": [{ "name": "access", "when": { "error_code": 5 }, "actions": [] }] }"#,
+        )?;
+        let watcher = LogDirectoryTailer {
+            scan_interval: Duration::from_millis(10),
+            tailer: PollingLogTailer {
+                poll_interval: Duration::from_millis(10),
+                ..PollingLogTailer::default()
+            },
+        };
+        let rx = watcher.watch(LogWatchTarget::Directory(dir.path().to_path_buf()))?;
+        let mut watch = AlertWatch::new(rules.rules, false);
+        let mut alerts = Vec::new();
+        let mut receive_until = |watch: &mut AlertWatch, count: usize| {
+            while alerts.len() < count {
+                let event = rx
+                    .recv_timeout(Duration::from_secs(5))
+                    .expect("watcher produced no event");
+                alerts.extend(watch.push_event(event));
+            }
+            alerts
+                .iter()
+                .map(|alert| alert.log.clone())
+                .collect::<Vec<_>>()
+        };
+        // Wait for both existing logs to be picked up before writing
+        while watch.logs.len() < 2 {
+            let event = rx.recv_timeout(Duration::from_secs(5))?;
+            assert!(watch.push_event(event).is_empty());
+        }
+
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("empty.log"))?
+            .write_all(sample)?;
+        let empty = dir.path().join("empty.log").display().to_string();
+        assert_eq!(receive_until(&mut watch, 2), [empty.clone(), empty.clone()]);
+
+        std::fs::write(dir.path().join("new.log"), sample)?;
+        let new = dir.path().join("new.log").display().to_string();
+        assert_eq!(
+            receive_until(&mut watch, 4),
+            [empty.clone(), empty, new.clone(), new]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn skips_a_log_it_cannot_parse_until_it_is_replaced() -> eyre::Result<()> {
+        let rules: AlertRules = serde_json::from_str(
+            r#"{ "rules": [{ "name": "access", "when": { "error_code": 5 }, "actions": [] }] }"#,
+        )?;
+        let sample = include_bytes!("../../../robocopy/sample.txt").to_vec();
+        let mut watch = AlertWatch::new(rules.rules, true);
+        watch.push_event(LogDirectoryEvent::Added {
+            log: 0,
+            path: "broken.log".into(),
+        });
+        let mut fired = |event| {
+            watch
+                .push_event(LogDirectoryEvent::Tail { log: 0, event })
+                .len()
+        };
+        assert_eq!(fired(TailEvent::Data(sample.clone())), 2);
+        assert_eq!(
+            fired(TailEvent::Data(
+                b"\t    New File  \t\t  lots\tJ:\\x\r\n".to_vec()
+            )),
+            0
+        );
+        // The same errors again, but nothing is read past the bad line
+        assert_eq!(fired(TailEvent::Data(sample.clone())), 0);
+        assert_eq!(fired(TailEvent::Rotated), 0);
+        assert_eq!(fired(TailEvent::Data(sample)), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+mod alert_args;
+
+pub use alert_args::AlertArgs;
@@ -1,4 +1,20 @@
+use crate::cli::command::alert::AlertArgs;
+use crate::cli::command::copy::CopyArgs;
+use crate::cli::command::diff::DiffArgs;
+use crate::cli::command::errors::ErrorsArgs;
+use crate::cli::command::exit_code::ExitCodeArgs;
+use crate::cli::command::export::ExportArgs;
+use crate::cli::command::generate::GenerateArgs;
+use crate::cli::command::plan::PlanArgs;
+use crate::cli::command::progress::ProgressArgs;
+use crate::cli::command::query::QueryArgs;
 use crate::cli::command::robocopy_logs_tui::RobocopyLogsTuiArgs;
+use crate::cli::command::run::RunArgs;
+use crate::cli::command::serve_metrics::ServeMetricsArgs;
+use crate::cli::command::status::StatusArgs;
+use crate::cli::command::summary::SummaryArgs;
+use crate::cli::command::tree::TreeArgs;
+use crate::cli::command::verify::VerifyArgs;
 use crate::cli::global_args::GlobalArgs;
 use crate::cli::to_args::ToArgs;
 use arbitrary::Arbitrary;
@@ -8,8 +24,40 @@ use std::ffi::OsString;
 /// Teamy MFT commands
 #[derive(Subcommand, Arbitrary, PartialEq, Debug)]
 pub enum Command {
-    /// Explore robocopy logs in a TUI (validate file exists for now)
+    /// Watch a robocopy log, or a directory of them, live in an interactive terminal UI
     RobocopyLogsTui(RobocopyLogsTuiArgs),
+    /// Write a synthetic robocopy log for testing and demos
+    Generate(GenerateArgs),
+    /// Rebuild the job summary table from a log's entries
+    Summary(SummaryArgs),
+    /// Follow a log and report bytes copied, throughput and time left
+    Progress(ProgressArgs),
+    /// Show bytes copied, file counts and errors per directory
+    Tree(TreeArgs),
+    /// Group errors by code, operation and directory, with suggested fixes
+    Errors(ErrorsArgs),
+    /// Compare two runs: newly copied and failed files, reclassifications, options and totals
+    Diff(DiffArgs),
+    /// Print the entries matching a filter such as `size > 1GiB and path ~ "*.bucket"`
+    Query(QueryArgs),
+    /// Write a log to a `SQLite` database, CSV or an Arrow IPC file for analysis
+    Export(ExportArgs),
+    /// Follow logs and serve Prometheus metrics per job on `/metrics`
+    ServeMetrics(ServeMetricsArgs),
+    /// Report whether a job is running, retrying, stalled, finished or abandoned, via the exit code
+    Status(StatusArgs),
+    /// Follow logs and run commands, append to a file or call webhooks when rules fire
+    Alert(AlertArgs),
+    /// Explain a robocopy exit code and check it against the job's log
+    ExitCode(ExitCodeArgs),
+    /// Run robocopy, or a compatible executable, showing live progress and a decoded result
+    Run(RunArgs),
+    /// Copy like robocopy without robocopy, writing a robocopy log
+    Copy(CopyArgs),
+    /// Preview what robocopy would do: a `/L` log or JSON, computed from the two trees
+    Plan(PlanArgs),
+    /// Check that the files a log says were copied arrived with the right size, time and contents
+    Verify(VerifyArgs),
 }
 
 impl Command {
@@ -24,6 +72,22 @@ impl Command {
         crate::logging::init_tracing(global_args.log_level(), &json_behaviour)?;
         match self {
             Command::RobocopyLogsTui(args) => args.invoke(),
+            Command::Generate(args) => args.invoke(),
+            Command::Summary(args) => args.invoke(),
+            Command::Progress(args) => args.invoke(),
+            Command::Tree(args) => args.invoke(),
+            Command::Errors(args) => args.invoke(),
+            Command::Diff(args) => args.invoke(),
+            Command::Query(args) => args.invoke(),
+            Command::Export(args) => args.invoke(),
+            Command::ServeMetrics(args) => args.invoke(),
+            Command::Status(args) => args.invoke(),
+            Command::Alert(args) => args.invoke(),
+            Command::ExitCode(args) => args.invoke(),
+            Command::Run(args) => args.invoke(),
+            Command::Copy(args) => args.invoke(),
+            Command::Plan(args) => args.invoke(),
+            Command::Verify(args) => args.invoke(),
         }
     }
 }
@@ -36,6 +100,70 @@ impl ToArgs for Command {
                 args.push("robocopy-logs-tui".into());
                 args.extend(logs_args.to_args());
             }
+            Command::Generate(generate_args) => {
+                args.push("generate".into());
+                args.extend(generate_args.to_args());
+            }
+            Command::Summary(summary_args) => {
+                args.push("summary".into());
+                args.extend(summary_args.to_args());
+            }
+            Command::Progress(progress_args) => {
+                args.push("progress".into());
+                args.extend(progress_args.to_args());
+            }
+            Command::Tree(tree_args) => {
+                args.push("tree".into());
+                args.extend(tree_args.to_args());
+            }
+            Command::Errors(errors_args) => {
+                args.push("errors".into());
+                args.extend(errors_args.to_args());
+            }
+            Command::Diff(diff_args) => {
+                args.push("diff".into());
+                args.extend(diff_args.to_args());
+            }
+            Command::Query(query_args) => {
+                args.push("query".into());
+                args.extend(query_args.to_args());
+            }
+            Command::Export(export_args) => {
+                args.push("export".into());
+                args.extend(export_args.to_args());
+            }
+            Command::ServeMetrics(serve_metrics_args) => {
+                args.push("serve-metrics".into());
+                args.extend(serve_metrics_args.to_args());
+            }
+            Command::Status(status_args) => {
+                args.push("status".into());
+                args.extend(status_args.to_args());
+            }
+            Command::Alert(alert_args) => {
+                args.push("alert".into());
+                args.extend(alert_args.to_args());
+            }
+            Command::ExitCode(exit_code_args) => {
+                args.push("exit-code".into());
+                args.extend(exit_code_args.to_args());
+            }
+            Command::Run(run_args) => {
+                args.push("run".into());
+                args.extend(run_args.to_args());
+            }
+            Command::Copy(copy_args) => {
+                args.push("copy".into());
+                args.extend(copy_args.to_args());
+            }
+            Command::Plan(plan_args) => {
+                args.push("plan".into());
+                args.extend(plan_args.to_args());
+            }
+            Command::Verify(verify_args) => {
+                args.push("verify".into());
+                args.extend(verify_args.to_args());
+            }
         }
         args
     }
@@ -0,0 +1,104 @@
+use crate::cli::to_args::ToArgs;
+use crate::copy::copy_engine::CopyEngine;
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_log_writer::LineEnding;
+use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use eyre::WrapErr;
+use std::ffi::OsString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct CopyArgs {
+    /// Robocopy arguments, after `--`: source, destination, file patterns and switches
+    #[arg(last = true, required = true)]
+    #[arbitrary(with = arbitrary_robocopy_args)]
+    pub robocopy_args: Vec<String>,
+}
+
+fn arbitrary_robocopy_args(u: &mut Unstructured<'_>) -> arbitrary::Result<Vec<String>> {
+    let count = u.int_in_range(2..=5)?;
+    (0..count).map(|_| String::arbitrary(u)).collect()
+}
+
+impl CopyArgs {
+    /// Copy with the built-in engine and exit with the code robocopy would.
+    ///
+    /// The log goes to the `/LOG` or `/LOG+` file when one is given, and to
+    /// standard output when none is or `/TEE` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments conflict or use a switch the engine
+    /// does not support, or the log cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let command = RobocopyCommand::from_args(&self.robocopy_args)?;
+        let engine = CopyEngine::new(command.clone())?;
+        let log = command
+            .switches()
+            .iter()
+            .find_map(|switch| {
+                let path = switch.log_file()?;
+                let append = matches!(switch, RobocopySwitch::AppendLog(_));
+                Some(
+                    OpenOptions::new()
+                        .create(true)
+                        .write(true)
+                        .append(append)
+                        .truncate(!append)
+                        .open(path)
+                        .wrap_err_with(|| format!("Failed to open {}", path.display())),
+                )
+            })
+            .transpose()?;
+        let stdout = log.is_none() || command.has_switch(&RobocopySwitch::Tee);
+        let mut writer =
+            RobocopyLogWriter::new(LogOutput { log, stdout }).with_line_ending(LineEnding::CrLf);
+        let outcome = engine.run(&mut writer)?;
+        if outcome.exit_code.0 != 0 {
+            std::process::exit(i32::try_from(outcome.exit_code.0).unwrap_or(i32::MAX));
+        }
+        Ok(())
+    }
+}
+
+/// Sends the log to the log file, standard output, or both.
+struct LogOutput {
+    log: Option<File>,
+    stdout: bool,
+}
+
+impl Write for LogOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(log) = &mut self.log {
+            log.write_all(buf)?;
+        }
+        if self.stdout {
+            std::io::stdout().write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(log) = &mut self.log {
+            log.flush()?;
+        }
+        if self.stdout {
+            std::io::stdout().flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for CopyArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--".into()];
+        args.extend(self.robocopy_args.iter().map(OsString::from));
+        args
+    }
+}
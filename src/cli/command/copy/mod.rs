@@ -0,0 +1,3 @@
+mod copy_args;
+
+pub use copy_args::CopyArgs;
@@ -0,0 +1,347 @@
+use crate::cli::arbitrary_path::arbitrary_optional_path;
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_run_diff::RobocopyRunDiff;
+use crate::robocopy::robocopy_run_diff::RobocopyTotalsChange;
+use arbitrary::Arbitrary;
+use clap::Args;
+use humansize::BINARY;
+use humansize::format_size;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+use thousands::Separable;
+use uom::si::information::byte;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct DiffArgs {
+    /// Log of the earlier run
+    #[arbitrary(with = arbitrary_path)]
+    pub before: PathBuf,
+    /// Log of the later run; if omitted, compares two jobs in the first log
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub after: Option<PathBuf>,
+    /// Job to use from the earlier log, counting from 1. Defaults to the last
+    /// job, or the one before it when comparing jobs within one log
+    #[arg(long)]
+    pub before_job: Option<usize>,
+    /// Job to use from the later log, counting from 1. Defaults to the last job
+    #[arg(long)]
+    pub after_job: Option<usize>,
+    /// Paths listed per section in human output; 0 lists them all
+    #[arg(long, default_value_t = 50)]
+    pub max_paths: usize,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl DiffArgs {
+    /// Compare two runs and report what changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a log cannot be read or parsed, or a requested job
+    /// does not exist.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let before_logs = RobocopyLog::read_all(&self.before)?;
+        let after_path = self.after.as_deref().unwrap_or(&self.before);
+        let after_logs = match &self.after {
+            Some(after) => RobocopyLog::read_all(after)?,
+            None => before_logs.clone(),
+        };
+        if self.after.is_none() && before_logs.len() < 2 && self.before_job.is_none() {
+            eyre::bail!(
+                "{} holds a single job; pass a second log to compare against",
+                self.before.display()
+            );
+        }
+        let after_job = self.after_job.unwrap_or(after_logs.len());
+        let before_job = match (self.before_job, &self.after) {
+            (Some(job), _) => job,
+            (None, Some(_)) => before_logs.len(),
+            (None, None) => after_job.saturating_sub(1),
+        };
+        let before = select_job(&before_logs, before_job, &self.before)?;
+        let after = select_job(&after_logs, after_job, after_path)?;
+        let diff = RobocopyRunDiff::new(before, after);
+        let report = DiffReport::new(
+            JsonRun::new(&self.before, before_job, before),
+            JsonRun::new(after_path, after_job, after),
+            &diff,
+        );
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            OutputFormat::Human => report.print_human(self.max_paths),
+        }
+        Ok(())
+    }
+}
+
+fn select_job<'a>(
+    logs: &'a [RobocopyLog],
+    job: usize,
+    path: &Path,
+) -> eyre::Result<&'a RobocopyLog> {
+    match job.checked_sub(1).and_then(|index| logs.get(index)) {
+        Some(log) => Ok(log),
+        None => eyre::bail!(
+            "{} has {} job(s), there is no job {job}",
+            path.display(),
+            logs.len()
+        ),
+    }
+}
+
+#[derive(Serialize)]
+struct DiffReport {
+    before: JsonRun,
+    after: JsonRun,
+    header: Vec<JsonHeaderChange>,
+    options_added: Vec<String>,
+    options_removed: Vec<String>,
+    copied: Vec<JsonCopiedFile>,
+    newly_failed: Vec<String>,
+    recovered: Vec<String>,
+    reclassified: Vec<JsonReclassifiedFile>,
+    totals: Vec<JsonTotalsChange>,
+}
+
+#[derive(Serialize)]
+struct JsonRun {
+    log: String,
+    job: usize,
+    source: String,
+    dest: String,
+    started: String,
+    #[serde(skip)]
+    started_display: String,
+}
+
+#[derive(Serialize)]
+struct JsonHeaderChange {
+    field: &'static str,
+    before: String,
+    after: String,
+}
+
+#[derive(Serialize)]
+struct JsonCopiedFile {
+    path: String,
+    class: &'static str,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct JsonReclassifiedFile {
+    path: String,
+    before: &'static str,
+    after: &'static str,
+}
+
+#[derive(Serialize)]
+struct JsonTotalsChange {
+    row: &'static str,
+    column: &'static str,
+    before: u64,
+    after: u64,
+    delta: i128,
+}
+
+impl JsonRun {
+    fn new(path: &Path, job: usize, log: &RobocopyLog) -> Self {
+        Self {
+            log: path.display().to_string(),
+            job,
+            source: log.header.source.display().to_string(),
+            dest: log.header.dest.display().to_string(),
+            started: log.header.started.to_rfc3339(),
+            started_display: log.header.started.to_string(),
+        }
+    }
+}
+
+impl From<&RobocopyTotalsChange> for JsonTotalsChange {
+    fn from(change: &RobocopyTotalsChange) -> Self {
+        Self {
+            row: change.row,
+            column: change.column,
+            before: change.before,
+            after: change.after,
+            delta: change.delta(),
+        }
+    }
+}
+
+impl DiffReport {
+    fn new(before: JsonRun, after: JsonRun, diff: &RobocopyRunDiff) -> Self {
+        Self {
+            before,
+            after,
+            header: diff
+                .header
+                .iter()
+                .map(|change| JsonHeaderChange {
+                    field: change.field,
+                    before: change.before.clone(),
+                    after: change.after.clone(),
+                })
+                .collect(),
+            options_added: diff.options_added.clone(),
+            options_removed: diff.options_removed.clone(),
+            copied: diff
+                .copied
+                .iter()
+                .map(|file| JsonCopiedFile {
+                    path: file.path.clone(),
+                    class: file.class.label(),
+                    size: file.size.get::<byte>() as u64,
+                })
+                .collect(),
+            newly_failed: diff.newly_failed.clone(),
+            recovered: diff.recovered.clone(),
+            reclassified: diff
+                .reclassified
+                .iter()
+                .map(|file| JsonReclassifiedFile {
+                    path: file.path.clone(),
+                    before: file.before.label(),
+                    after: file.after.label(),
+                })
+                .collect(),
+            totals: diff.totals.iter().map(JsonTotalsChange::from).collect(),
+        }
+    }
+
+    fn print_human(&self, max_paths: usize) {
+        for (label, run) in [("Before", &self.before), ("After ", &self.after)] {
+            println!(
+                "{label} : job {} of {}, started {}, {} -> {}",
+                run.job, run.log, run.started_display, run.source, run.dest
+            );
+        }
+        println!();
+        let unchanged = self.header.is_empty()
+            && self.copied.is_empty()
+            && self.newly_failed.is_empty()
+            && self.recovered.is_empty()
+            && self.reclassified.is_empty()
+            && self.totals.is_empty();
+        if unchanged {
+            println!("No differences.");
+            return;
+        }
+        for change in &self.header {
+            println!(
+                "{:>8} : {} -> {}",
+                change.field,
+                change.before.trim(),
+                change.after.trim()
+            );
+        }
+        if !self.options_added.is_empty() || !self.options_removed.is_empty() {
+            let added = self
+                .options_added
+                .iter()
+                .map(|option| format!(" +{option}"));
+            let removed = self
+                .options_removed
+                .iter()
+                .map(|option| format!(" -{option}"));
+            println!(
+                "         {}",
+                added.chain(removed).collect::<String>().trim_start()
+            );
+        }
+        if !self.totals.is_empty() {
+            println!("Totals:");
+            for change in &self.totals {
+                let format = |value: u64| {
+                    if change.row == "Bytes" {
+                        format_size(value, BINARY)
+                    } else {
+                        value.separate_with_commas()
+                    }
+                };
+                let sign = if change.delta < 0 { "-" } else { "+" };
+                println!(
+                    "  {:>5} {:<8} : {} -> {} ({sign}{})",
+                    change.row,
+                    change.column,
+                    format(change.before),
+                    format(change.after),
+                    format(change.before.abs_diff(change.after)),
+                );
+            }
+        }
+        print_section(
+            "Newly copied",
+            self.copied.iter().map(|file| {
+                format!(
+                    "{:<12}{:>12}  {}",
+                    file.class,
+                    format_size(file.size, BINARY),
+                    file.path
+                )
+            }),
+            max_paths,
+        );
+        print_section("Newly failed", self.newly_failed.iter().cloned(), max_paths);
+        print_section(
+            "No longer failing",
+            self.recovered.iter().cloned(),
+            max_paths,
+        );
+        print_section(
+            "Reclassified",
+            self.reclassified
+                .iter()
+                .map(|file| format!("{} -> {}  {}", file.before, file.after, file.path)),
+            max_paths,
+        );
+    }
+}
+
+fn print_section(title: &str, lines: impl ExactSizeIterator<Item = String>, max_paths: usize) {
+    let count = lines.len();
+    if count == 0 {
+        return;
+    }
+    println!("{title} ({}):", count.separate_with_commas());
+    let shown = if max_paths == 0 {
+        count
+    } else {
+        max_paths.min(count)
+    };
+    for line in lines.take(shown) {
+        println!("  {line}");
+    }
+    if shown < count {
+        println!("  ... and {} more", (count - shown).separate_with_commas());
+    }
+}
+
+impl ToArgs for DiffArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![self.before.clone().into()];
+        if let Some(after) = &self.after {
+            args.push(after.clone().into());
+        }
+        if let Some(job) = self.before_job {
+            args.push("--before-job".into());
+            args.push(job.to_string().into());
+        }
+        if let Some(job) = self.after_job {
+            args.push("--after-job".into());
+            args.push(job.to_string().into());
+        }
+        args.push("--max-paths".into());
+        args.push(self.max_paths.to_string().into());
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args
+    }
+}
@@ -0,0 +1,3 @@
+mod diff_args;
+
+pub use diff_args::DiffArgs;
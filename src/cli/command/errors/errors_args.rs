@@ -0,0 +1,136 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_error_report::RobocopyErrorGroup;
+use crate::robocopy::robocopy_error_report::group_errors;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use arbitrary::Arbitrary;
+use clap::Args;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use thousands::Separable;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct ErrorsArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl ErrorsArgs {
+    /// Group the errors in a log and suggest fixes for the common ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be read or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let logs = RobocopyLog::read_all(&self.robocopy_log_file_path)?;
+        let groups = group_errors(logs.iter().flat_map(|log| &log.parts));
+        match self.format {
+            OutputFormat::Json => {
+                let groups: Vec<JsonErrorGroup> = groups.iter().map(JsonErrorGroup::new).collect();
+                println!("{}", serde_json::to_string_pretty(&groups)?);
+            }
+            OutputFormat::Human => {
+                if groups.is_empty() {
+                    println!("No errors.");
+                }
+                for (index, group) in groups.iter().enumerate() {
+                    if index > 0 {
+                        println!();
+                    }
+                    print_human(group);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_human(group: &RobocopyErrorGroup) {
+    let info = group.info();
+    let name = info.map_or(String::new(), |info| format!(" {}", info.name));
+    let parent = if group.parent.is_empty() {
+        "(no directory)"
+    } else {
+        &group.parent
+    };
+    println!(
+        "ERROR {}{name}: {} in {parent}",
+        group.code, group.operation
+    );
+    println!(
+        "  {} occurrences on {} paths, first {}, last {}",
+        group.count.separate_with_commas(),
+        group.paths.separate_with_commas(),
+        group.first.format("%Y-%m-%d %H:%M:%S"),
+        group.last.format("%Y-%m-%d %H:%M:%S"),
+    );
+    if group.retries > 0 || group.gave_up > 0 {
+        println!(
+            "  {} retries waiting {}, gave up {} times",
+            group.retries.separate_with_commas(),
+            humantime::format_duration(group.retry_wait),
+            group.gave_up.separate_with_commas(),
+        );
+    }
+    println!("  {}", group.message);
+    if let Some(info) = info {
+        println!("  Why: {}", info.explanation);
+        println!("  Fix: {}", info.fix);
+    }
+}
+
+#[derive(Serialize)]
+struct JsonErrorGroup {
+    code: u32,
+    name: Option<&'static str>,
+    operation: String,
+    parent: String,
+    message: String,
+    count: u64,
+    paths: u64,
+    first: String,
+    last: String,
+    retries: u64,
+    retry_wait_seconds: u64,
+    gave_up: u64,
+    explanation: Option<&'static str>,
+    fix: Option<&'static str>,
+}
+
+impl JsonErrorGroup {
+    fn new(group: &RobocopyErrorGroup) -> Self {
+        let info = group.info();
+        Self {
+            code: group.code,
+            name: info.map(|info| info.name),
+            operation: group.operation.clone(),
+            parent: group.parent.clone(),
+            message: group.message.clone(),
+            count: group.count,
+            paths: group.paths,
+            first: group.first.to_rfc3339(),
+            last: group.last.to_rfc3339(),
+            retries: group.retries,
+            retry_wait_seconds: group.retry_wait.as_secs(),
+            gave_up: group.gave_up,
+            explanation: info.map(|info| info.explanation),
+            fix: info.map(|info| info.fix),
+        }
+    }
+}
+
+impl ToArgs for ErrorsArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![
+            self.robocopy_log_file_path.clone().into(),
+            "--format".into(),
+            value_enum_arg(&self.format),
+        ]
+    }
+}
@@ -0,0 +1,3 @@
+mod errors_args;
+
+pub use errors_args::ErrorsArgs;
@@ -0,0 +1,152 @@
+use crate::cli::arbitrary_path::arbitrary_optional_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_exit_code::RobocopyExitCode;
+use crate::robocopy::robocopy_exit_code::RobocopyExitPolicy;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use arbitrary::Arbitrary;
+use clap::Args;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct ExitCodeArgs {
+    /// Exit code robocopy returned, as recorded by the wrapper that ran it
+    pub code: Option<u32>,
+    /// Log of the job; derives the code robocopy should have returned and
+    /// checks it against the recorded one
+    #[arg(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub log: Option<PathBuf>,
+    /// Job to use from the log, counting from 1. Defaults to the last job
+    #[arg(long)]
+    pub job: Option<usize>,
+    /// Which codes count as success; a failing code exits with 1
+    #[arg(long, value_enum, default_value_t)]
+    pub policy: RobocopyExitPolicy,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl ExitCodeArgs {
+    /// Explain a robocopy exit code, derive the expected one from a log, or
+    /// both, and exit with 1 when the code fails the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither a code nor a log is given, the log cannot
+    /// be read or lacks the job, or the recorded code disagrees with the log.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let expected = match &self.log {
+            Some(path) => {
+                let logs = RobocopyLog::read_all(path)?;
+                let job = self.job.unwrap_or(logs.len());
+                let Some(log) = job.checked_sub(1).and_then(|index| logs.get(index)) else {
+                    eyre::bail!(
+                        "{} has {} job(s), there is no job {job}",
+                        path.display(),
+                        logs.len()
+                    );
+                };
+                Some(match &log.summary {
+                    Some(summary) => (RobocopyExitCode::from_summary(summary), "summary"),
+                    None => (
+                        RobocopyExitCode::from_counts(&RobocopySummaryCounts::from_entries(
+                            &log.parts,
+                        )),
+                        "entries",
+                    ),
+                })
+            }
+            None => None,
+        };
+        let code = match (self.code, expected) {
+            (Some(code), _) => RobocopyExitCode(code),
+            (None, Some((expected, _))) => expected,
+            (None, None) => eyre::bail!("Pass an exit code, a log with --log, or both"),
+        };
+        // A job cut short never writes its summary, so only a fatal code or
+        // one from a killed process fits a log without one
+        let consistent = match expected {
+            None => true,
+            Some((_, "entries")) if code.fatal() || !code.is_robocopy_code() => true,
+            Some((expected, _)) => expected == code,
+        };
+        let success = code.is_success(self.policy);
+        match self.format {
+            OutputFormat::Human => {
+                println!("Exit code {code}");
+                for description in code.descriptions() {
+                    println!("  {description}");
+                }
+                if let Some((expected, from)) = expected {
+                    println!("Expected from the log's {from}: {expected}");
+                }
+                println!(
+                    "{} under the {} policy",
+                    if success { "Success" } else { "Failure" },
+                    value_enum_arg(&self.policy).to_string_lossy()
+                );
+            }
+            OutputFormat::Json => {
+                let report = JsonExitCode {
+                    code: code.0,
+                    flags: code.flag_names(),
+                    descriptions: code.descriptions(),
+                    expected: expected.map(|(expected, _)| expected.0),
+                    expected_from: expected.map(|(_, from)| from),
+                    consistent,
+                    success,
+                };
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        if let (false, Some((expected, from))) = (consistent, expected) {
+            eyre::bail!(
+                "Recorded exit code {code} disagrees with the log's {from}, which implies {expected}"
+            );
+        }
+        if !success {
+            std::io::stdout().flush()?;
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonExitCode {
+    code: u32,
+    flags: Vec<&'static str>,
+    descriptions: Vec<&'static str>,
+    expected: Option<u32>,
+    expected_from: Option<&'static str>,
+    consistent: bool,
+    success: bool,
+}
+
+impl ToArgs for ExitCodeArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(code) = self.code {
+            args.push(code.to_string().into());
+        }
+        if let Some(log) = &self.log {
+            args.push("--log".into());
+            args.push(log.clone().into());
+        }
+        if let Some(job) = self.job {
+            args.push("--job".into());
+            args.push(job.to_string().into());
+        }
+        args.push("--policy".into());
+        args.push(value_enum_arg(&self.policy));
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args
+    }
+}
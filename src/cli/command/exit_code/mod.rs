@@ -0,0 +1,3 @@
+mod exit_code_args;
+
+pub use exit_code_args::ExitCodeArgs;
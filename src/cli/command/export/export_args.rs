@@ -0,0 +1,112 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::export::arrow_exporter::RobocopyArrowExporter;
+use crate::export::csv_exporter::RobocopyCsvExporter;
+use crate::export::export_row::RobocopyExportRow;
+use crate::export::export_row::RobocopyExportRows;
+use crate::export::for_each_advance;
+use crate::export::sqlite_exporter::RobocopySqliteExporter;
+use arbitrary::Arbitrary;
+use clap::Args;
+use clap::ValueEnum;
+use eyre::WrapErr;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::info;
+
+/// File formats `export` can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Arbitrary)]
+pub enum ExportFormat {
+    /// Normalized tables in a `SQLite` database; exporting into an existing
+    /// database adds new jobs
+    Sqlite,
+    /// One row per finished file or error, with stable columns
+    Csv,
+    /// The same rows as CSV in an Arrow IPC file, with typed sizes and timestamps
+    Arrow,
+}
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct ExportArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    #[arg(long, value_enum)]
+    pub format: ExportFormat,
+    /// Where to write the export
+    #[arg(long, short)]
+    #[arbitrary(with = arbitrary_path)]
+    pub output: PathBuf,
+}
+
+impl ExportArgs {
+    /// Convert a log into a format other tools can load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be read or parsed, or the output
+    /// cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        match self.format {
+            ExportFormat::Sqlite => {
+                let mut exporter =
+                    RobocopySqliteExporter::open(&self.output, &self.robocopy_log_file_path)?;
+                for_each_advance(&self.robocopy_log_file_path, |advance| {
+                    exporter.push_advance(&advance)
+                })?;
+                exporter.finish()?;
+            }
+            ExportFormat::Csv => {
+                let mut exporter = RobocopyCsvExporter::new(self.create_output()?)?;
+                self.export_rows(|row| exporter.write(&row))?;
+                exporter.finish()?.flush()?;
+            }
+            ExportFormat::Arrow => {
+                let mut exporter = RobocopyArrowExporter::new(self.create_output()?)?;
+                self.export_rows(|row| exporter.write(&row))?;
+                exporter.finish()?.flush()?;
+            }
+        }
+        info!(
+            "Exported {} to {}",
+            self.robocopy_log_file_path.display(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+impl ExportArgs {
+    fn create_output(&self) -> eyre::Result<BufWriter<File>> {
+        let file = File::create(&self.output)
+            .wrap_err_with(|| format!("Failed to create {}", self.output.display()))?;
+        Ok(BufWriter::new(file))
+    }
+
+    fn export_rows(
+        &self,
+        mut emit: impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        let mut rows = RobocopyExportRows::new();
+        for_each_advance(&self.robocopy_log_file_path, |advance| {
+            rows.push_advance(&advance, &mut emit)
+        })?;
+        rows.finish(&mut emit)
+    }
+}
+
+impl ToArgs for ExportArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![
+            self.robocopy_log_file_path.clone().into(),
+            "--format".into(),
+            value_enum_arg(&self.format),
+            "--output".into(),
+            self.output.clone().into(),
+        ]
+    }
+}
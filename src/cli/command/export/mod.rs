@@ -0,0 +1,3 @@
+mod export_args;
+
+pub use export_args::ExportArgs;
@@ -0,0 +1,152 @@
+use crate::cli::arbitrary_path::arbitrary_optional_path;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log_encoding::RobocopyLogEncoding;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_generator::RobocopyGeneratorOptionSet;
+use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+use crate::robocopy::robocopy_log_writer::LineEnding;
+use arbitrary::Arbitrary;
+use clap::Args;
+use eyre::WrapErr;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct GenerateArgs {
+    /// Where to write the log; standard output if omitted
+    #[arg(long, short)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub output: Option<PathBuf>,
+    /// The same seed and options always produce the same log
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Number of files in the simulated source tree
+    #[arg(long, default_value_t = 100)]
+    pub files: usize,
+    /// Maximum directory nesting below the source root
+    #[arg(long, default_value_t = 3)]
+    pub max_depth: usize,
+    /// Largest simulated file, in bytes
+    #[arg(long, default_value_t = 256 * 1024 * 1024)]
+    pub max_file_size: usize,
+    /// File classifications to generate; repeat for several. Defaults to a realistic mix
+    #[arg(long = "class", value_enum)]
+    pub classes: Vec<RobocopyFileClass>,
+    /// Chance, in percent, that copying a file fails at least once
+    #[arg(long, default_value_t = 2)]
+    pub error_percent: u8,
+    /// Retries after a failure (`/R:n`)
+    #[arg(long, default_value_t = 2)]
+    pub retries: u32,
+    /// Seconds to wait between retries (`/W:n`)
+    #[arg(long, default_value_t = 5)]
+    pub retry_wait: u64,
+    /// Simulate `/MT:n`, interleaving the output of several directories
+    #[arg(long)]
+    pub threads: Option<u8>,
+    #[arg(long, value_enum, default_value_t)]
+    pub option_set: RobocopyGeneratorOptionSet,
+    /// Omit progress percentages, as with `/NP`
+    #[arg(long)]
+    pub no_progress: bool,
+    /// Omit the job summary, as with `/NJS`
+    #[arg(long)]
+    pub no_summary: bool,
+    #[arg(long, value_enum, default_value_t)]
+    pub encoding: RobocopyLogEncoding,
+    #[arg(long, value_enum, default_value_t)]
+    pub line_ending: LineEnding,
+}
+
+impl GenerateArgs {
+    #[must_use]
+    pub fn config(&self) -> RobocopyLogGeneratorConfig {
+        RobocopyLogGeneratorConfig {
+            seed: self.seed,
+            files: self.files,
+            max_depth: self.max_depth,
+            max_file_size: Information::new::<byte>(self.max_file_size),
+            classes: self.classes.clone(),
+            error_percent: self.error_percent,
+            retries: self.retries,
+            retry_wait: Duration::from_secs(self.retry_wait),
+            threads: self.threads,
+            option_set: self.option_set,
+            progress: !self.no_progress,
+            summary: !self.no_summary,
+            encoding: self.encoding,
+            line_ending: self.line_ending,
+            ..Default::default()
+        }
+    }
+
+    /// Write a synthetic robocopy log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let bytes = RobocopyLogGenerator::new(self.config()).generate_bytes()?;
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &bytes)
+                    .wrap_err_with(|| format!("Failed to write {}", path.display()))?;
+                info!("Wrote {} bytes to {}", bytes.len(), path.display());
+            }
+            None => std::io::stdout().lock().write_all(&bytes)?,
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for GenerateArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = Vec::new();
+        if let Some(output) = &self.output {
+            args.push("--output".into());
+            args.push(output.clone().into());
+        }
+        args.push("--seed".into());
+        args.push(self.seed.to_string().into());
+        args.push("--files".into());
+        args.push(self.files.to_string().into());
+        args.push("--max-depth".into());
+        args.push(self.max_depth.to_string().into());
+        args.push("--max-file-size".into());
+        args.push(self.max_file_size.to_string().into());
+        for class in &self.classes {
+            args.push("--class".into());
+            args.push(value_enum_arg(class));
+        }
+        args.push("--error-percent".into());
+        args.push(self.error_percent.to_string().into());
+        args.push("--retries".into());
+        args.push(self.retries.to_string().into());
+        args.push("--retry-wait".into());
+        args.push(self.retry_wait.to_string().into());
+        if let Some(threads) = self.threads {
+            args.push("--threads".into());
+            args.push(threads.to_string().into());
+        }
+        args.push("--option-set".into());
+        args.push(value_enum_arg(&self.option_set));
+        if self.no_progress {
+            args.push("--no-progress".into());
+        }
+        if self.no_summary {
+            args.push("--no-summary".into());
+        }
+        args.push("--encoding".into());
+        args.push(value_enum_arg(&self.encoding));
+        args.push("--line-ending".into());
+        args.push(value_enum_arg(&self.line_ending));
+        args
+    }
+}
@@ -0,0 +1,3 @@
+mod generate_args;
+
+pub use generate_args::GenerateArgs;
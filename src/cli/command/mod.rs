@@ -1,4 +1,20 @@
+pub mod alert;
+pub mod copy;
+pub mod diff;
+pub mod errors;
+pub mod exit_code;
+pub mod export;
+pub mod generate;
+pub mod plan;
+pub mod progress;
+pub mod query;
 pub mod robocopy_logs_tui;
+pub mod run;
+pub mod serve_metrics;
+pub mod status;
+pub mod summary;
+pub mod tree;
+pub mod verify;
 
 #[allow(
     clippy::module_inception,
@@ -0,0 +1,3 @@
+mod plan_args;
+
+pub use plan_args::PlanArgs;
@@ -0,0 +1,200 @@
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::copy::copy_engine::CopyEngine;
+use crate::copy::copy_plan::CopyAction;
+use crate::copy::copy_plan::CopyDirectoryPlan;
+use crate::copy::copy_plan::plan_counts;
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_exit_code::RobocopyExitCode;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_writer::LineEnding;
+use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::Write;
+use uom::si::information::byte;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct PlanArgs {
+    /// `human` prints the log robocopy would write with `/L`; `json` the classified tree
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+    /// Robocopy arguments, after `--`: source, destination, file patterns and switches
+    #[arg(last = true, required = true)]
+    #[arbitrary(with = arbitrary_robocopy_args)]
+    pub robocopy_args: Vec<String>,
+}
+
+fn arbitrary_robocopy_args(u: &mut Unstructured<'_>) -> arbitrary::Result<Vec<String>> {
+    let count = u.int_in_range(2..=5)?;
+    (0..count).map(|_| String::arbitrary(u)).collect()
+}
+
+impl PlanArgs {
+    /// Show what robocopy would do with these arguments, without changing
+    /// the destination.
+    ///
+    /// The log format exits with the code robocopy `/L` would, like the
+    /// `copy` subcommand; JSON reports it instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments conflict or use a switch the engine
+    /// does not support, or the output cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let mut command = RobocopyCommand::from_args(&self.robocopy_args)?;
+        if !command.has_switch(&RobocopySwitch::List) {
+            command = command.with_switch(RobocopySwitch::List);
+        }
+        let engine = CopyEngine::new(command)?;
+        match self.format {
+            OutputFormat::Human => {
+                let mut writer = RobocopyLogWriter::new(std::io::stdout().lock())
+                    .with_line_ending(LineEnding::CrLf);
+                let outcome = engine.run(&mut writer)?;
+                writer.flush()?;
+                if outcome.exit_code.0 != 0 {
+                    std::process::exit(i32::try_from(outcome.exit_code.0).unwrap_or(i32::MAX));
+                }
+            }
+            OutputFormat::Json => {
+                let header = engine.header();
+                let plans = engine.plan();
+                let counts = plan_counts(&plans);
+                let exit_code = RobocopyExitCode::from_counts(&counts);
+                let bytes = RobocopySummaryRow::from_values(
+                    counts
+                        .bytes
+                        .values()
+                        .map(|value| value.get::<byte>() as u64),
+                );
+                let report = JsonPlan {
+                    source: header.source.display().to_string(),
+                    dest: header.dest.display().to_string(),
+                    options: header.options.to_string(),
+                    exit_code: exit_code.0,
+                    flags: exit_code.flag_names(),
+                    dirs: counts.dirs.values().into(),
+                    files: counts.files.values().into(),
+                    bytes: bytes.values().into(),
+                    directories: plans.iter().map(JsonDirectory::new).collect(),
+                };
+                let mut stdout = std::io::stdout().lock();
+                serde_json::to_writer_pretty(&mut stdout, &report)?;
+                writeln!(stdout)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonPlan {
+    source: String,
+    dest: String,
+    options: String,
+    /// The exit code robocopy would return if every copy succeeded.
+    exit_code: u32,
+    flags: Vec<&'static str>,
+    dirs: JsonRow,
+    files: JsonRow,
+    bytes: JsonRow,
+    directories: Vec<JsonDirectory>,
+}
+
+#[derive(Serialize)]
+struct JsonRow {
+    total: u64,
+    copied: u64,
+    skipped: u64,
+    mismatch: u64,
+    failed: u64,
+    extras: u64,
+}
+
+impl From<[u64; 6]> for JsonRow {
+    fn from([total, copied, skipped, mismatch, failed, extras]: [u64; 6]) -> Self {
+        Self {
+            total,
+            copied,
+            skipped,
+            mismatch,
+            failed,
+            extras,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDirectory {
+    class: &'static str,
+    source: String,
+    dest: String,
+    /// Files selected in the source directory, or -1 for extra directories.
+    file_count: i64,
+    error: Option<JsonError>,
+    files: Vec<JsonFile>,
+}
+
+#[derive(Serialize)]
+struct JsonError {
+    code: u32,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonFile {
+    class: &'static str,
+    action: &'static str,
+    size: u64,
+    source: String,
+    dest: String,
+}
+
+impl JsonDirectory {
+    fn new(plan: &CopyDirectoryPlan) -> Self {
+        Self {
+            class: match plan.class {
+                RobocopyDirectoryClass::Existing => "Existing Dir",
+                class => class.label(),
+            },
+            source: plan.source.display().to_string(),
+            dest: plan.dest.display().to_string(),
+            file_count: plan.file_count,
+            error: plan.error.as_ref().map(|error| JsonError {
+                code: error.code,
+                message: error.message.clone(),
+            }),
+            files: plan
+                .files
+                .iter()
+                .map(|file| JsonFile {
+                    class: file.class.label(),
+                    action: match file.action {
+                        CopyAction::Copy => "copy",
+                        CopyAction::Skip => "skip",
+                        CopyAction::Purge => "purge",
+                    },
+                    size: file.size,
+                    source: file.source.display().to_string(),
+                    dest: file.dest.display().to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ToArgs for PlanArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--format".into(), value_enum_arg(&self.format)];
+        args.push("--".into());
+        args.extend(self.robocopy_args.iter().map(OsString::from));
+        args
+    }
+}
@@ -0,0 +1,3 @@
+mod progress_args;
+
+pub use progress_args::ProgressArgs;
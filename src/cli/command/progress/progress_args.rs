@@ -0,0 +1,154 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::byte_size_arg::parse_byte_size;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::robocopy::robocopy_progress::RobocopyProgressSnapshot;
+use crate::tail::log_tailer::default_log_tailer;
+use arbitrary::Arbitrary;
+use clap::Args;
+use crossbeam_channel::RecvTimeoutError;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::info;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct ProgressArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// Total size of the source, e.g. `1.5 TiB`, used to estimate the time left
+    #[arg(long, value_parser = parse_byte_size)]
+    pub total_size: Option<u64>,
+    /// Milliseconds between progress reports
+    #[arg(long, default_value_t = 1000)]
+    pub interval_ms: u64,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl ProgressArgs {
+    /// Follow a log and report bytes copied, throughput and time left.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be tailed or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let interval = Duration::from_millis(self.interval_ms.max(100));
+        let total_bytes = self
+            .total_size
+            .map(|size| Information::new::<byte>(usize::try_from(size).unwrap_or(usize::MAX)));
+        let mut progress = RobocopyProgress::new().with_total_bytes(total_bytes);
+        let mut parser = RobocopyLogParser::new();
+        let rx = default_log_tailer().tail(&self.robocopy_log_file_path)?;
+
+        // Everything already in the file happened before we started watching,
+        // so it counts towards bytes done but not towards the rate
+        info!("Catching up on {}", self.robocopy_log_file_path.display());
+        while let Ok(event) = rx.recv_timeout(interval) {
+            event.feed(&mut parser);
+            advance_all(&mut parser, &mut progress)?;
+            if rx.is_empty() {
+                break;
+            }
+        }
+        progress.restart_clock(Instant::now());
+        self.report(&progress.snapshot())?;
+
+        let mut last_report = Instant::now();
+        loop {
+            match rx.recv_timeout(interval.saturating_sub(last_report.elapsed())) {
+                Ok(event) => {
+                    event.feed(&mut parser);
+                    advance_all(&mut parser, &mut progress)?;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if last_report.elapsed() >= interval {
+                progress.tick(Instant::now());
+                self.report(&progress.snapshot())?;
+                last_report = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn report(&self, snapshot: &RobocopyProgressSnapshot) -> eyre::Result<()> {
+        match self.format {
+            OutputFormat::Human => println!("{snapshot}"),
+            OutputFormat::Json => {
+                println!("{}", serde_json::to_string(&JsonProgress::new(snapshot))?);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn advance_all(
+    parser: &mut RobocopyLogParser,
+    progress: &mut RobocopyProgress,
+) -> eyre::Result<()> {
+    loop {
+        let advance = parser.advance()?;
+        progress.push_advance(&advance, Instant::now());
+        if matches!(advance, RobocopyParseAdvance::NeedMoreData) {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonProgress {
+    bytes_done: usize,
+    total_bytes: Option<usize>,
+    fraction: Option<f64>,
+    files_done: u64,
+    current_file: Option<String>,
+    current_file_percent: Option<u8>,
+    bytes_per_second: Option<f64>,
+    eta_seconds: Option<u64>,
+    finished: bool,
+}
+
+impl JsonProgress {
+    fn new(snapshot: &RobocopyProgressSnapshot) -> Self {
+        Self {
+            bytes_done: snapshot.bytes_done.get::<byte>(),
+            total_bytes: snapshot.total_bytes.map(|total| total.get::<byte>()),
+            fraction: snapshot.fraction(),
+            files_done: snapshot.files_done,
+            current_file: snapshot
+                .current_file
+                .as_ref()
+                .map(|file| file.path.display().to_string()),
+            current_file_percent: snapshot.current_file.as_ref().and_then(|file| file.percent),
+            bytes_per_second: snapshot.bytes_per_second,
+            eta_seconds: snapshot.eta.map(|eta| eta.as_secs()),
+            finished: snapshot.finished,
+        }
+    }
+}
+
+impl ToArgs for ProgressArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![self.robocopy_log_file_path.clone().into()];
+        if let Some(total_size) = self.total_size {
+            args.push("--total-size".into());
+            args.push(total_size.to_string().into());
+        }
+        args.push("--interval-ms".into());
+        args.push(self.interval_ms.to_string().into());
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args
+    }
+}
@@ -0,0 +1,3 @@
+mod query_args;
+
+pub use query_args::QueryArgs;
@@ -0,0 +1,227 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_query::RobocopyQuery;
+use crate::robocopy::robocopy_query::RobocopyQueryMatch;
+use crate::robocopy::robocopy_query::RobocopyQueryRunner;
+use crate::robocopy::robocopy_query::entry_kind;
+use crate::tail::log_tailer::default_log_tailer;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use clap::ValueEnum;
+use eyre::WrapErr;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+use uom::si::information::byte;
+
+/// What `query` prints for each matching entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Arbitrary)]
+pub enum QueryOutput {
+    /// The full path of each match, one per line
+    #[default]
+    Paths,
+    /// Each match as a JSON object, one per line
+    Json,
+    /// Only the number of matches
+    Count,
+}
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct QueryArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// Filter such as `kind == "error" and code in (5, 32)` or
+    /// `size > 1GiB and path ~ "*.bucket"`. Fields are kind, class, path,
+    /// size, percent, code, operation, message, files and wait
+    #[arbitrary(with = arbitrary_query)]
+    pub query: String,
+    #[arg(long, value_enum, default_value_t)]
+    pub output: QueryOutput,
+    /// Keep watching the log and report new matches as robocopy writes them
+    #[arg(long)]
+    pub follow: bool,
+}
+
+/// A query that clap will not mistake for a flag.
+fn arbitrary_query(u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+    Ok(String::arbitrary(u)?.trim_start_matches('-').to_string())
+}
+
+impl QueryArgs {
+    /// Print the entries of a log that match a filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is malformed or the log file cannot be
+    /// read, tailed or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let query: RobocopyQuery = self.query.parse().wrap_err("Invalid query")?;
+        let mut runner = RobocopyQueryRunner::new(query);
+        let mut count: u64 = 0;
+        if !self.follow {
+            for log in RobocopyLog::read_all(&self.robocopy_log_file_path)? {
+                for entry in &log.parts {
+                    self.report(runner.push(entry), &mut count)?;
+                }
+                self.report(runner.finish(), &mut count)?;
+            }
+            if self.output == QueryOutput::Count {
+                println!("{count}");
+            }
+            return Ok(());
+        }
+
+        let mut parser = RobocopyLogParser::new();
+        let rx = default_log_tailer().tail(&self.robocopy_log_file_path)?;
+        info!("Catching up on {}", self.robocopy_log_file_path.display());
+        while let Ok(event) = rx.recv_timeout(Duration::from_secs(1)) {
+            event.feed(&mut parser);
+            self.advance_all(&mut parser, &mut runner, &mut count)?;
+            if rx.is_empty() {
+                break;
+            }
+        }
+        if self.output == QueryOutput::Count {
+            println!("{count}");
+        }
+        for event in rx {
+            event.feed(&mut parser);
+            let before = count;
+            self.advance_all(&mut parser, &mut runner, &mut count)?;
+            if self.output == QueryOutput::Count && count != before {
+                println!("{count}");
+            }
+        }
+        Ok(())
+    }
+
+    fn advance_all(
+        &self,
+        parser: &mut RobocopyLogParser,
+        runner: &mut RobocopyQueryRunner,
+        count: &mut u64,
+    ) -> eyre::Result<()> {
+        loop {
+            let advance = parser.advance()?;
+            self.report(runner.push_advance(&advance), count)?;
+            if matches!(advance, RobocopyParseAdvance::NeedMoreData) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn report(
+        &self,
+        matches: impl IntoIterator<Item = RobocopyQueryMatch>,
+        count: &mut u64,
+    ) -> eyre::Result<()> {
+        for found in matches {
+            *count += 1;
+            match self.output {
+                QueryOutput::Paths => {
+                    if let Some(path) = &found.path {
+                        println!("{path}");
+                    }
+                }
+                QueryOutput::Json => {
+                    println!("{}", serde_json::to_string(&JsonEntry::new(&found))?);
+                }
+                QueryOutput::Count => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    class: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    when: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wait_seconds: Option<u64>,
+}
+
+impl<'a> JsonEntry<'a> {
+    fn new(found: &'a RobocopyQueryMatch) -> Self {
+        let entry = &found.entry;
+        let mut json = Self {
+            kind: entry_kind(entry),
+            class: entry.file_class().map(RobocopyFileClass::label),
+            path: found.path.as_deref(),
+            size: entry.file_size().map(|size| size.get::<byte>() as u64),
+            percent: entry.percentages().and_then(|p| p.last().copied()),
+            files: None,
+            code: entry.error_code(),
+            operation: None,
+            message: None,
+            when: None,
+            wait_seconds: None,
+        };
+        match entry {
+            RobocopyLogEntry::Directory {
+                class, file_count, ..
+            } => {
+                json.class = Some(class.label());
+                json.files = Some(*file_count);
+            }
+            RobocopyLogEntry::Error {
+                when,
+                operation,
+                message,
+                ..
+            } => {
+                json.operation = Some(operation);
+                json.message = Some(message);
+                json.when = Some(when.to_rfc3339());
+            }
+            RobocopyLogEntry::AccessDeniedError { when, .. } => {
+                json.when = Some(when.to_rfc3339());
+            }
+            RobocopyLogEntry::Retry { wait } => json.wait_seconds = Some(wait.as_secs()),
+            _ => {}
+        }
+        json
+    }
+}
+
+impl ToArgs for QueryArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            self.robocopy_log_file_path.clone().into(),
+            self.query.clone().into(),
+            "--output".into(),
+            value_enum_arg(&self.output),
+        ];
+        if self.follow {
+            args.push("--follow".into());
+        }
+        args
+    }
+}
@@ -1,20 +1,28 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::byte_size_arg::parse_byte_size;
 use crate::cli::to_args::ToArgs;
-use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
-use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::tail::log_directory_tailer::LogWatchTarget;
+use crate::tui::run_robocopy_logs_dashboard;
+use crate::tui::run_robocopy_logs_tui;
 use arbitrary::Arbitrary;
 use clap::Args;
 use std::path::PathBuf;
-use teamy_windows::file::WatchConfig;
-use teamy_windows::file::watch_file_content;
-use tracing::info;
+use uom::si::information::byte;
+use uom::si::usize::Information;
 
 #[derive(Args, Arbitrary, PartialEq, Debug, Default)]
 pub struct RobocopyLogsTuiArgs {
-    /// Path to the robocopy logs text file
+    /// Path to the robocopy logs text file, or a directory or glob such as
+    /// `logs\*.log` to watch every log in it on a dashboard
+    #[arbitrary(with = arbitrary_path)]
     pub robocopy_log_file_path: PathBuf,
-    /// Avoids printing content before the current time
-    #[arg(long, default_value_t = true)]
+    /// Start at the newest entry and keep following new ones
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
     pub skip_to_present: bool,
+    /// Total size of the source, e.g. `1.5 TiB`, used to estimate the time left
+    #[arg(long, value_parser = parse_byte_size)]
+    pub total_size: Option<u64>,
 }
 
 impl RobocopyLogsTuiArgs {
@@ -22,76 +30,33 @@ impl RobocopyLogsTuiArgs {
     ///
     /// # Errors
     ///
-    /// Returns an error if the log file cannot be read or parsed.
+    /// Returns an error if the log file cannot be read or parsed, or the terminal fails.
     pub fn invoke(self) -> eyre::Result<()> {
-        info!(
-            "Tailing robocopy log (skip start): {}",
-            self.robocopy_log_file_path.display()
-        );
-
-        let mut parser = RobocopyLogParser::new();
-
-        // We must start from the beginning even if skipping to latest logs
-        // This is to ensure the parser is able to advance its state machine correctly
-        let rx = watch_file_content(WatchConfig::new_from_start(self.robocopy_log_file_path))?;
-
-        info!("Waiting for first chunk");
-        let first_chunk = rx.recv()?;
-
-        info!("Sending first chunk to parser");
-        let s = String::from_utf8_lossy(&first_chunk);
-        parser.accept(&s);
-
-        if self.skip_to_present {
-            info!("Skipping to present...");
-
-            info!("Draining the buffer");
-            while let Ok(chunk) = rx.try_recv() {
-                let s = String::from_utf8_lossy(&chunk);
-                parser.accept(&s);
-            }
-
-            info!("Advancing the parser");
-            loop {
-                match parser.advance()? {
-                    RobocopyParseAdvance::NeedMoreData => {
-                        info!("All caught up!");
-                        break;
-                    }
-                    RobocopyParseAdvance::Header(_robocopy_header) => {
-                        // info!("Skipped header: {robocopy_header}");
-                    }
-                    RobocopyParseAdvance::LogEntry(_robocopy_log_entry) => {
-                        // info!("Skipped log entry: {robocopy_log_entry:?}");
-                    }
-                }
-            }
-        }
-
-        for chunk in &rx {
-            let s = String::from_utf8_lossy(&chunk);
-            parser.accept(&s);
-            loop {
-                match parser.advance()? {
-                    RobocopyParseAdvance::NeedMoreData => {
-                        println!("Need more data...");
-                        break;
-                    }
-                    RobocopyParseAdvance::Header(h) => {
-                        println!("[HEADER]\n{h}");
-                    }
-                    RobocopyParseAdvance::LogEntry(e) => {
-                        println!("[ENTRY] {e:?}");
-                    }
-                }
+        let total_bytes = self
+            .total_size
+            .map(|size| Information::new::<byte>(usize::try_from(size).unwrap_or(usize::MAX)));
+        let progress = RobocopyProgress::new().with_total_bytes(total_bytes);
+        match LogWatchTarget::from_path(&self.robocopy_log_file_path) {
+            Some(target) => run_robocopy_logs_dashboard(target, self.skip_to_present, progress),
+            None => {
+                run_robocopy_logs_tui(&self.robocopy_log_file_path, self.skip_to_present, progress)
             }
         }
-        Ok(())
     }
 }
 
 impl ToArgs for RobocopyLogsTuiArgs {
     fn to_args(&self) -> Vec<std::ffi::OsString> {
-        vec![self.robocopy_log_file_path.clone().into()]
+        let mut args: Vec<std::ffi::OsString> = Vec::new();
+        if !self.skip_to_present {
+            args.push("--skip-to-present".into());
+            args.push("false".into());
+        }
+        if let Some(total_size) = self.total_size {
+            args.push("--total-size".into());
+            args.push(total_size.to_string().into());
+        }
+        args.push(self.robocopy_log_file_path.clone().into());
+        args
     }
 }
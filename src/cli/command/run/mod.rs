@@ -0,0 +1,3 @@
+mod run_args;
+
+pub use run_args::RunArgs;
@@ -0,0 +1,230 @@
+use crate::cli::arbitrary_path::arbitrary_optional_path;
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_progress::RobocopyProgressSnapshot;
+use crate::robocopy::robocopy_runner::RobocopyRunJob;
+use crate::robocopy::robocopy_runner::RobocopyRunResult;
+use crate::robocopy::robocopy_runner::RobocopyRunner;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use humansize::BINARY;
+use humansize::format_size;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use thousands::Separable;
+use tracing::warn;
+use uom::si::information::byte;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct RunArgs {
+    /// Robocopy, or any executable that takes its arguments and writes its log format
+    #[arg(long, default_value = "robocopy")]
+    #[arbitrary(with = arbitrary_path)]
+    pub executable: PathBuf,
+    /// Also save everything robocopy writes to standard output in this file
+    #[arg(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub stdout_file: Option<PathBuf>,
+    /// Milliseconds between progress reports on stderr
+    #[arg(long, default_value_t = 1000)]
+    pub interval_ms: u64,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+    /// Arguments for robocopy, after `--`: source, destination, file patterns and switches
+    #[arg(last = true, required = true)]
+    #[arbitrary(with = arbitrary_robocopy_args)]
+    pub robocopy_args: Vec<String>,
+}
+
+fn arbitrary_robocopy_args(u: &mut Unstructured<'_>) -> arbitrary::Result<Vec<String>> {
+    let count = u.int_in_range(2..=5)?;
+    (0..count).map(|_| String::arbitrary(u)).collect()
+}
+
+impl RunArgs {
+    /// Run robocopy, report progress as its output arrives, then print what
+    /// it did and exit with robocopy's own exit code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments conflict, the process cannot be
+    /// started or its output cannot be parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let command =
+            RobocopyCommand::from_args(&self.robocopy_args)?.with_executable(&self.executable);
+        let format = self.format;
+        let result = RobocopyRunner::new(command)
+            .with_report_interval(Duration::from_millis(self.interval_ms.max(100)))
+            .with_stdout_file(self.stdout_file.clone())
+            .run(|snapshot| report_progress(format, snapshot))?;
+        if let Some(expected) = result.expected_exit_code()
+            && expected != result.exit_code
+            && !result.exit_code.fatal()
+        {
+            warn!(
+                "Exit code {} disagrees with the output, which implies {expected}",
+                result.exit_code
+            );
+        }
+        let report = RunReport::new(&result);
+        match self.format {
+            OutputFormat::Human => report.print_human(),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        }
+        std::io::stdout().flush()?;
+        if result.exit_code.0 != 0 {
+            std::process::exit(i32::try_from(result.exit_code.0).unwrap_or(i32::MAX));
+        }
+        Ok(())
+    }
+}
+
+fn report_progress(format: OutputFormat, snapshot: &RobocopyProgressSnapshot) {
+    match format {
+        OutputFormat::Human => eprintln!("{snapshot}"),
+        OutputFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "bytes_done": snapshot.bytes_done.get::<byte>(),
+                "files_done": snapshot.files_done,
+                "current_file": snapshot
+                    .current_file
+                    .as_ref()
+                    .map(|file| file.path.display().to_string()),
+                "bytes_per_second": snapshot.bytes_per_second,
+                "finished": snapshot.finished,
+            })
+        ),
+    }
+}
+
+#[derive(Serialize)]
+struct RunReport {
+    exit_code: u32,
+    flags: Vec<&'static str>,
+    descriptions: Vec<&'static str>,
+    expected_exit_code: Option<u32>,
+    elapsed_seconds: f64,
+    jobs: Vec<JsonJob>,
+}
+
+#[derive(Serialize)]
+struct JsonJob {
+    source: String,
+    dest: String,
+    started: String,
+    /// Whether robocopy printed its summary; otherwise the rows are rebuilt
+    /// from the entries.
+    summary: bool,
+    dirs: JsonRow,
+    files: JsonRow,
+    bytes: JsonRow,
+}
+
+#[derive(Serialize)]
+struct JsonRow {
+    total: u64,
+    copied: u64,
+    skipped: u64,
+    mismatch: u64,
+    failed: u64,
+    extras: u64,
+}
+
+impl From<[u64; 6]> for JsonRow {
+    fn from([total, copied, skipped, mismatch, failed, extras]: [u64; 6]) -> Self {
+        Self {
+            total,
+            copied,
+            skipped,
+            mismatch,
+            failed,
+            extras,
+        }
+    }
+}
+
+impl JsonJob {
+    fn new(job: &RobocopyRunJob) -> Self {
+        let (dirs, files, bytes) = match &job.summary {
+            Some(summary) => (&summary.dirs, &summary.files, &summary.bytes),
+            None => (&job.counts.dirs, &job.counts.files, &job.counts.bytes),
+        };
+        let bytes =
+            RobocopySummaryRow::from_values(bytes.values().map(|value| value.get::<byte>() as u64));
+        Self {
+            source: job.header.source.display().to_string(),
+            dest: job.header.dest.display().to_string(),
+            started: job.header.started.to_rfc3339(),
+            summary: job.summary.is_some(),
+            dirs: dirs.values().into(),
+            files: files.values().into(),
+            bytes: bytes.values().into(),
+        }
+    }
+}
+
+impl RunReport {
+    fn new(result: &RobocopyRunResult) -> Self {
+        Self {
+            exit_code: result.exit_code.0,
+            flags: result.exit_code.flag_names(),
+            descriptions: result.exit_code.descriptions(),
+            expected_exit_code: result.expected_exit_code().map(|code| code.0),
+            elapsed_seconds: result.elapsed.as_secs_f64(),
+            jobs: result.jobs.iter().map(JsonJob::new).collect(),
+        }
+    }
+
+    fn print_human(&self) {
+        for (index, job) in self.jobs.iter().enumerate() {
+            println!("Job {}: {} -> {}", index + 1, job.source, job.dest);
+            println!(
+                "  {} of {} files copied ({}), {} failed, {} extras{}",
+                job.files.copied.separate_with_commas(),
+                job.files.total.separate_with_commas(),
+                format_size(job.bytes.copied, BINARY),
+                job.files.failed.separate_with_commas(),
+                job.files.extras.separate_with_commas(),
+                if job.summary {
+                    ""
+                } else {
+                    " (no summary; counted from the entries)"
+                }
+            );
+        }
+        println!(
+            "Exit code {} after {}",
+            self.exit_code,
+            humantime::format_duration(Duration::from_secs_f64(self.elapsed_seconds.round()))
+        );
+        for description in &self.descriptions {
+            println!("  {description}");
+        }
+    }
+}
+
+impl ToArgs for RunArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--executable".into(), self.executable.clone().into()];
+        if let Some(path) = &self.stdout_file {
+            args.push("--stdout-file".into());
+            args.push(path.clone().into());
+        }
+        args.push("--interval-ms".into());
+        args.push(self.interval_ms.to_string().into());
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args.push("--".into());
+        args.extend(self.robocopy_args.iter().map(OsString::from));
+        args
+    }
+}
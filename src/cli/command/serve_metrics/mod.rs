@@ -0,0 +1,3 @@
+mod serve_metrics_args;
+
+pub use serve_metrics_args::ServeMetricsArgs;
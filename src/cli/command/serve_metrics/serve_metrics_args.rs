@@ -0,0 +1,188 @@
+use crate::cli::arbitrary_path::arbitrary_paths;
+use crate::cli::to_args::ToArgs;
+use crate::metrics::metrics_server::serve_metrics;
+use crate::metrics::robocopy_metrics::RobocopyLogMetrics;
+use crate::metrics::robocopy_metrics::RobocopyMetricsExposition;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::tail::log_tailer::default_log_tailer;
+use crate::tail::tail_event::TailEvent;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use crossbeam_channel::Receiver;
+use eyre::WrapErr;
+use std::ffi::OsString;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Instant;
+use tracing::info;
+use tracing::warn;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct ServeMetricsArgs {
+    /// Robocopy log files to follow
+    #[arg(required = true)]
+    #[arbitrary(with = arbitrary_paths)]
+    pub robocopy_log_file_paths: Vec<PathBuf>,
+    /// Address to serve `/metrics` on
+    #[arg(long, default_value = "127.0.0.1:9184")]
+    #[arbitrary(with = arbitrary_listen_address)]
+    pub listen: SocketAddr,
+}
+
+fn arbitrary_listen_address(u: &mut Unstructured<'_>) -> arbitrary::Result<SocketAddr> {
+    Ok(SocketAddr::from((
+        Ipv4Addr::from(u32::arbitrary(u)?),
+        u16::arbitrary(u)?,
+    )))
+}
+
+impl ServeMetricsArgs {
+    /// Follow the logs and serve their metrics to Prometheus.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a log cannot be tailed or the address cannot be bound.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let logs = Arc::new(Mutex::new(
+            self.robocopy_log_file_paths
+                .iter()
+                .map(|path| RobocopyLogMetrics::new(path.display().to_string()))
+                .collect::<Vec<_>>(),
+        ));
+        for (index, path) in self.robocopy_log_file_paths.iter().enumerate() {
+            let rx = default_log_tailer().tail(path)?;
+            let logs = Arc::clone(&logs);
+            std::thread::spawn(move || follow(&rx, &logs, index));
+        }
+
+        let listener = TcpListener::bind(self.listen)
+            .wrap_err_with(|| format!("Failed to listen on {}", self.listen))?;
+        info!(
+            "Serving metrics on http://{}/metrics",
+            listener.local_addr()?
+        );
+        serve_metrics(&listener, || {
+            let mut logs = logs.lock().unwrap_or_else(PoisonError::into_inner);
+            for log in logs.iter_mut() {
+                log.tick(Instant::now());
+            }
+            RobocopyMetricsExposition(&logs).to_string()
+        })
+    }
+}
+
+/// Feed a log's events into its metrics until the tailer stops.
+///
+/// A log that fails to parse is reported down and skipped until it is
+/// truncated or replaced, leaving the other logs served as before.
+fn follow(rx: &Receiver<TailEvent>, logs: &Mutex<Vec<RobocopyLogMetrics>>, index: usize) {
+    let mut parser = RobocopyLogParser::new();
+    let mut caught_up = false;
+    for event in rx {
+        let mut logs = logs.lock().unwrap_or_else(PoisonError::into_inner);
+        let log = &mut logs[index];
+        if event.is_reset() {
+            log.reset();
+        } else if !log.up {
+            continue;
+        }
+        event.feed(&mut parser);
+        if let Err(error) = push_parsed(&mut parser, log) {
+            warn!("Skipping {} until it is replaced: {error:?}", log.log);
+            log.up = false;
+        }
+        // Output written before we started watching says nothing about the rate
+        if !caught_up && rx.is_empty() {
+            log.restart_clock(Instant::now());
+            caught_up = true;
+        }
+    }
+    logs.lock().unwrap_or_else(PoisonError::into_inner)[index].up = false;
+}
+
+fn push_parsed(parser: &mut RobocopyLogParser, log: &mut RobocopyLogMetrics) -> eyre::Result<()> {
+    loop {
+        let advance = parser.advance()?;
+        log.push_advance(&advance, Instant::now())?;
+        if advance == RobocopyParseAdvance::NeedMoreData {
+            return Ok(());
+        }
+    }
+}
+
+impl ToArgs for ServeMetricsArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = self
+            .robocopy_log_file_paths
+            .iter()
+            .map(|path| path.clone().into())
+            .collect();
+        args.push("--listen".into());
+        args.push(self.listen.to_string().into());
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Wait for the follower to report `up`, giving up after a few seconds.
+    fn wait_for_up(logs: &Mutex<Vec<RobocopyLogMetrics>>, up: bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if logs.lock().unwrap_or_else(PoisonError::into_inner)[0].up == up {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn unparseable_log_is_down_until_replaced() -> eyre::Result<()> {
+        let sample = include_bytes!("../../../robocopy/sample.txt").to_vec();
+        let logs = Arc::new(Mutex::new(vec![RobocopyLogMetrics::new("broken.log")]));
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let follower = {
+            let logs = Arc::clone(&logs);
+            std::thread::spawn(move || follow(&rx, &logs, 0))
+        };
+
+        tx.send(TailEvent::Data(sample.clone()))?;
+        tx.send(TailEvent::Data(
+            b"\t    New File  \t\t  lots\tJ:\\x\r\n".to_vec(),
+        ))?;
+        assert!(
+            wait_for_up(&logs, false),
+            "a parse error takes the log down"
+        );
+        let exposition =
+            RobocopyMetricsExposition(&logs.lock().unwrap_or_else(PoisonError::into_inner))
+                .to_string();
+        assert!(
+            exposition.contains("robocopy_log_up{log=\"broken.log\"} 0"),
+            "{exposition}"
+        );
+
+        tx.send(TailEvent::Rotated)?;
+        tx.send(TailEvent::Data(sample))?;
+        assert!(wait_for_up(&logs, true), "a replaced log is followed again");
+
+        drop(tx);
+        follower.join().expect("follower does not panic");
+        assert!(
+            wait_for_up(&logs, false),
+            "the log is down once the tailer stops"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+mod status_args;
+
+pub use status_args::StatusArgs;
@@ -0,0 +1,145 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::duration_arg::arbitrary_duration;
+use crate::cli::duration_arg::format_duration;
+use crate::cli::duration_arg::parse_duration;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::export::for_each_advance;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_job_status::RobocopyJobStatus;
+use crate::robocopy::robocopy_job_status::RobocopyJobThresholds;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use arbitrary::Arbitrary;
+use clap::Args;
+use eyre::WrapErr;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct StatusArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// How long the log may go without output before the job counts as stalled
+    #[arg(long, value_parser = parse_duration, default_value = "5m")]
+    #[arbitrary(with = arbitrary_duration)]
+    pub stall_after: Duration,
+    /// How long the log may go without output before the job counts as abandoned
+    #[arg(long, value_parser = parse_duration, default_value = "1h")]
+    #[arbitrary(with = arbitrary_duration)]
+    pub abandon_after: Duration,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl StatusArgs {
+    /// Print whether the last job in a log is running, waiting on a retry,
+    /// stalled, finished or abandoned, and exit with a code for scripts:
+    /// 0 finished, 3 running, 4 waiting on a retry, 5 stalled, 6 abandoned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be read or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let path = &self.robocopy_log_file_path;
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .wrap_err_with(|| {
+                format!("Failed to read the modification time of {}", path.display())
+            })?;
+        let idle = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        let mut status = RobocopyJobStatus::new();
+        let mut header: Option<RobocopyHeader> = None;
+        for_each_advance(path, |advance| {
+            status.push_advance(&advance);
+            if let RobocopyParseAdvance::Header(new) = advance {
+                header = Some(new);
+            }
+            Ok(())
+        })?;
+        let thresholds = RobocopyJobThresholds {
+            stall_after: self.stall_after,
+            abandon_after: self.abandon_after,
+        };
+        let state = status.state(idle, &thresholds);
+        // Whole seconds read better and are all the precision an mtime offers
+        let idle = Duration::from_secs(idle.as_secs());
+        match self.format {
+            OutputFormat::Human => {
+                let job = header.as_ref().map_or_else(
+                    || "no job header yet".to_string(),
+                    |header| {
+                        format!(
+                            "{} -> {}, started {}",
+                            header.source.display(),
+                            header.dest.display(),
+                            header.started
+                        )
+                    },
+                );
+                println!(
+                    "{state}: {job}; last output {} ago, {} retries",
+                    format_duration(idle),
+                    status.retries()
+                );
+            }
+            OutputFormat::Json => {
+                let report = JsonStatus {
+                    state: state.name(),
+                    exit_code: state.exit_code(),
+                    log: path.display().to_string(),
+                    source: header
+                        .as_ref()
+                        .map(|header| header.source.display().to_string()),
+                    dest: header
+                        .as_ref()
+                        .map(|header| header.dest.display().to_string()),
+                    started: header
+                        .as_ref()
+                        .map(|header| header.started.as_datetime().to_rfc3339()),
+                    idle_seconds: idle.as_secs(),
+                    retries: status.retries(),
+                };
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        if state.exit_code() != 0 {
+            std::io::stdout().flush()?;
+            std::process::exit(i32::from(state.exit_code()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonStatus {
+    state: &'static str,
+    exit_code: u8,
+    log: String,
+    source: Option<String>,
+    dest: Option<String>,
+    started: Option<String>,
+    idle_seconds: u64,
+    retries: u64,
+}
+
+impl ToArgs for StatusArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![
+            self.robocopy_log_file_path.clone().into(),
+            "--stall-after".into(),
+            format_duration(self.stall_after).into(),
+            "--abandon-after".into(),
+            format_duration(self.abandon_after).into(),
+            "--format".into(),
+            value_enum_arg(&self.format),
+        ]
+    }
+}
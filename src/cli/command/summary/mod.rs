@@ -0,0 +1,3 @@
+mod summary_args;
+
+pub use summary_args::SummaryArgs;
@@ -0,0 +1,203 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryDiscrepancy;
+use arbitrary::Arbitrary;
+use clap::Args;
+use humansize::BINARY;
+use humansize::format_size;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use thousands::Separable;
+use uom::si::information::byte;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct SummaryArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl SummaryArgs {
+    /// Rebuild the job summary of every job in the log from its entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be read or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let logs = RobocopyLog::read_all(&self.robocopy_log_file_path)?;
+        let reports: Vec<JobSummaryReport> = logs.iter().map(JobSummaryReport::new).collect();
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+            OutputFormat::Human => {
+                for (index, report) in reports.iter().enumerate() {
+                    if index > 0 {
+                        println!();
+                    }
+                    report.print_human(index + 1);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct JobSummaryReport {
+    source: String,
+    dest: String,
+    started: String,
+    #[serde(skip)]
+    started_display: String,
+    computed: JsonCounts,
+    /// The footer robocopy wrote, if the job finished and `/NJS` was not used.
+    footer: Option<JsonCounts>,
+    discrepancies: Vec<JsonDiscrepancy>,
+}
+
+#[derive(Serialize)]
+struct JsonCounts {
+    dirs: JsonRow,
+    files: JsonRow,
+    bytes: JsonRow,
+}
+
+#[derive(Serialize)]
+struct JsonRow {
+    total: u64,
+    copied: u64,
+    skipped: u64,
+    mismatch: u64,
+    failed: u64,
+    extras: u64,
+}
+
+#[derive(Serialize)]
+struct JsonDiscrepancy {
+    row: &'static str,
+    column: &'static str,
+    computed: String,
+    footer: String,
+}
+
+impl JobSummaryReport {
+    fn new(log: &RobocopyLog) -> Self {
+        let computed = RobocopySummaryCounts::from_entries(&log.parts);
+        let (footer, discrepancies) = match &log.summary {
+            Some(footer) => (
+                Some(JsonCounts::new(&RobocopySummaryCounts {
+                    dirs: footer.dirs,
+                    files: footer.files,
+                    bytes: footer.bytes,
+                })),
+                computed.discrepancies(footer),
+            ),
+            None => (None, Vec::new()),
+        };
+        Self {
+            source: log.header.source.display().to_string(),
+            dest: log.header.dest.display().to_string(),
+            started: log.header.started.to_rfc3339(),
+            started_display: log.header.started.to_string(),
+            computed: JsonCounts::new(&computed),
+            footer,
+            discrepancies: discrepancies
+                .into_iter()
+                .map(JsonDiscrepancy::from)
+                .collect(),
+        }
+    }
+
+    fn print_human(&self, job: usize) {
+        println!("Job {job}: {} -> {}", self.source, self.dest);
+        println!("  Started : {}", self.started_display);
+        println!();
+        println!(
+            "{:>10}{:>14}{:>14}{:>14}{:>14}{:>14}{:>14}",
+            "", "Total", "Copied", "Skipped", "Mismatch", "FAILED", "Extras"
+        );
+        print_row("Dirs", &self.computed.dirs, |v| v.separate_with_commas());
+        print_row("Files", &self.computed.files, |v| v.separate_with_commas());
+        print_row("Bytes", &self.computed.bytes, |v| format_size(v, BINARY));
+        println!();
+        match &self.footer {
+            None => println!("No footer; counts are computed from the entries only."),
+            Some(_) if self.discrepancies.is_empty() => println!("Footer matches the entries."),
+            Some(_) => {
+                println!("Footer disagrees with the entries:");
+                for d in &self.discrepancies {
+                    println!(
+                        "  {} {}: entries give {}, footer says {}",
+                        d.row, d.column, d.computed, d.footer
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn print_row(label: &str, row: &JsonRow, format: impl Fn(u64) -> String) {
+    let cells = [
+        row.total,
+        row.copied,
+        row.skipped,
+        row.mismatch,
+        row.failed,
+        row.extras,
+    ]
+    .map(|cell| format!("{:>14}", format(cell)));
+    println!("{label:>8} :{}", cells.concat());
+}
+
+impl JsonCounts {
+    fn new(counts: &RobocopySummaryCounts) -> Self {
+        Self {
+            dirs: JsonRow::new(counts.dirs),
+            files: JsonRow::new(counts.files),
+            bytes: JsonRow::new(RobocopySummaryRow::from_values(
+                counts.bytes.values().map(|v| v.get::<byte>() as u64),
+            )),
+        }
+    }
+}
+
+impl JsonRow {
+    fn new(row: RobocopySummaryRow<u64>) -> Self {
+        Self {
+            total: row.total,
+            copied: row.copied,
+            skipped: row.skipped,
+            mismatch: row.mismatch,
+            failed: row.failed,
+            extras: row.extras,
+        }
+    }
+}
+
+impl From<RobocopySummaryDiscrepancy> for JsonDiscrepancy {
+    fn from(d: RobocopySummaryDiscrepancy) -> Self {
+        Self {
+            row: d.row,
+            column: d.column,
+            computed: d.computed,
+            footer: d.reported,
+        }
+    }
+}
+
+impl ToArgs for SummaryArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![
+            self.robocopy_log_file_path.clone().into(),
+            "--format".into(),
+            value_enum_arg(&self.format),
+        ]
+    }
+}
@@ -0,0 +1,3 @@
+mod tree_args;
+
+pub use tree_args::TreeArgs;
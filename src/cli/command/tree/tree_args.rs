@@ -0,0 +1,176 @@
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_path_tree::RobocopyPathNodeKind;
+use crate::robocopy::robocopy_path_tree::RobocopyPathTree;
+use crate::robocopy::robocopy_path_tree::RobocopyPathTreeNodeId;
+use arbitrary::Arbitrary;
+use clap::Args;
+use humansize::BINARY;
+use humansize::format_size;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use thousands::Separable;
+use uom::si::information::byte;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct TreeArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// Levels to show below each drive or share; everything when omitted
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+    /// List files as well as directories
+    #[arg(long)]
+    pub files: bool,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl TreeArgs {
+    /// Show where the data went and where errors cluster, directory by directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log file cannot be read or parsed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let logs = RobocopyLog::read_all(&self.robocopy_log_file_path)?;
+        let tree = RobocopyPathTree::from_entries(logs.iter().flat_map(|log| &log.parts));
+        let roots = self.children(&tree, tree.root());
+        match self.format {
+            OutputFormat::Json => {
+                let nodes: Vec<JsonNode> = roots
+                    .into_iter()
+                    .map(|root| self.json_node(&tree, root, 0))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&nodes)?);
+            }
+            OutputFormat::Human => {
+                println!("{:>10} {:>10} {:>8}  Path", "Copied", "Files", "Errors");
+                for root in roots {
+                    self.print_node(&tree, root, 0, "", "");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn children(
+        &self,
+        tree: &RobocopyPathTree,
+        id: RobocopyPathTreeNodeId,
+    ) -> Vec<RobocopyPathTreeNodeId> {
+        tree.children(id)
+            .into_iter()
+            .filter(|child| self.files || tree.node(*child).kind == RobocopyPathNodeKind::Directory)
+            .collect()
+    }
+
+    fn print_node(
+        &self,
+        tree: &RobocopyPathTree,
+        id: RobocopyPathTreeNodeId,
+        depth: usize,
+        branch: &str,
+        indent: &str,
+    ) {
+        let node = tree.node(id);
+        let suffix = if node.kind == RobocopyPathNodeKind::Directory && !node.name.ends_with('\\') {
+            "\\"
+        } else {
+            ""
+        };
+        println!(
+            "{:>10} {:>10} {:>8}  {branch}{}{suffix}",
+            format_size(node.stats.bytes_copied.get::<byte>(), BINARY),
+            node.stats.file_count().separate_with_commas(),
+            node.stats.errors.separate_with_commas(),
+            node.name,
+        );
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return;
+        }
+        let children = self.children(tree, id);
+        let last = children.len().saturating_sub(1);
+        for (index, child) in children.into_iter().enumerate() {
+            let (branch, continuation) = if index == last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            self.print_node(
+                tree,
+                child,
+                depth + 1,
+                &format!("{indent}{branch}"),
+                &format!("{indent}{continuation}"),
+            );
+        }
+    }
+
+    fn json_node(
+        &self,
+        tree: &RobocopyPathTree,
+        id: RobocopyPathTreeNodeId,
+        depth: usize,
+    ) -> JsonNode {
+        let node = tree.node(id);
+        let children = if self.max_depth.is_some_and(|max| depth >= max) {
+            Vec::new()
+        } else {
+            self.children(tree, id)
+                .into_iter()
+                .map(|child| self.json_node(tree, child, depth + 1))
+                .collect()
+        };
+        JsonNode {
+            path: tree.path(id),
+            kind: match node.kind {
+                RobocopyPathNodeKind::Directory => "directory",
+                RobocopyPathNodeKind::File => "file",
+            },
+            bytes_copied: node.stats.bytes_copied.get::<byte>(),
+            files: node
+                .stats
+                .files_by_class()
+                .map(|(class, count)| (class.label().to_string(), count))
+                .collect(),
+            failed_files: node.stats.failed_files,
+            errors: node.stats.errors,
+            children,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonNode {
+    path: String,
+    kind: &'static str,
+    bytes_copied: usize,
+    /// File counts keyed by the label robocopy prints, e.g. `New File`.
+    files: BTreeMap<String, u64>,
+    failed_files: u64,
+    errors: u64,
+    children: Vec<JsonNode>,
+}
+
+impl ToArgs for TreeArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![self.robocopy_log_file_path.clone().into()];
+        if let Some(max_depth) = self.max_depth {
+            args.push("--max-depth".into());
+            args.push(max_depth.to_string().into());
+        }
+        if self.files {
+            args.push("--files".into());
+        }
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args
+    }
+}
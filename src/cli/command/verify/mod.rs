@@ -0,0 +1,3 @@
+mod verify_args;
+
+pub use verify_args::VerifyArgs;
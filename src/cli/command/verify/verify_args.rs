@@ -0,0 +1,223 @@
+use crate::cli::arbitrary_path::arbitrary_optional_path;
+use crate::cli::arbitrary_path::arbitrary_path;
+use crate::cli::duration_arg::arbitrary_duration;
+use crate::cli::duration_arg::format_duration;
+use crate::cli::duration_arg::parse_duration;
+use crate::cli::output_format::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::value_enum_arg;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use crate::robocopy::robocopy_verify::RobocopyVerifier;
+use crate::robocopy::robocopy_verify::RobocopyVerifyReport;
+use crate::robocopy::robocopy_verify::copied_files;
+use arbitrary::Arbitrary;
+use clap::Args;
+use humansize::BINARY;
+use humansize::format_size;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use thousands::Separable;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct VerifyArgs {
+    /// Path to the robocopy log file
+    #[arbitrary(with = arbitrary_path)]
+    pub robocopy_log_file_path: PathBuf,
+    /// Job to verify from the log, counting from 1. Defaults to the last job
+    #[arg(long)]
+    pub job: Option<usize>,
+    /// Where the job's source is readable now. Defaults to the source in the log
+    #[arg(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub source: Option<PathBuf>,
+    /// Where the job's destination is readable now. Defaults to the destination in the log
+    #[arg(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub dest: Option<PathBuf>,
+    /// Also hash both copies of every file and compare contents
+    #[arg(long)]
+    pub hash: bool,
+    /// How far apart modification times may be, for filesystems that store them coarsely
+    #[arg(long, value_parser = parse_duration, default_value = "0s")]
+    #[arbitrary(with = arbitrary_duration)]
+    pub time_tolerance: Duration,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+impl VerifyArgs {
+    /// Check that every file the job copied is in the destination with the
+    /// size the log gives and the source's size and modification time, and
+    /// exit with 1 if any is not. Files gone from the source are checked
+    /// against the log alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be read or lacks the job, the job
+    /// only listed files with `/L`, or a root does not exist.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let path = &self.robocopy_log_file_path;
+        let logs = RobocopyLog::read_all(path)?;
+        let job = self.job.unwrap_or(logs.len());
+        let Some(log) = job.checked_sub(1).and_then(|index| logs.get(index)) else {
+            eyre::bail!(
+                "{} has {} job(s), there is no job {job}",
+                path.display(),
+                logs.len()
+            );
+        };
+        if log
+            .header
+            .options
+            .switches()?
+            .contains(&RobocopySwitch::List)
+        {
+            eyre::bail!("Job {job} ran with /L, so it copied nothing");
+        }
+        let source = self.source.unwrap_or_else(|| log.header.source.clone());
+        let dest = self.dest.unwrap_or_else(|| log.header.dest.clone());
+        for (root, flag) in [(&source, "--source"), (&dest, "--dest")] {
+            if !root.is_dir() {
+                eyre::bail!(
+                    "{} is not a directory here; pass {flag} with where it is mounted",
+                    root.display()
+                );
+            }
+        }
+
+        let files = copied_files(log);
+        let started = Instant::now();
+        let report = RobocopyVerifier::new(&source, &dest)
+            .with_hash(self.hash)
+            .with_time_tolerance(self.time_tolerance)
+            .verify(&files);
+        let elapsed = started.elapsed();
+        match self.format {
+            OutputFormat::Human => print_human(&report, elapsed),
+            OutputFormat::Json => {
+                let json = JsonVerifyReport::new(&report, &source, &dest, elapsed);
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            }
+        }
+        if !report.problems.is_empty() {
+            std::io::stdout().flush()?;
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+fn print_human(report: &RobocopyVerifyReport, elapsed: Duration) {
+    for problem in &report.problems {
+        println!(
+            "{:<14} {}: {}",
+            problem.kind.name(),
+            problem.file.log_path.display(),
+            problem.kind
+        );
+    }
+    if !report.problems.is_empty() {
+        println!();
+    }
+    println!(
+        "{} of {} copied files verified ({}), {} problem(s) in {}",
+        (report.checked - report.problems.len()).separate_with_commas(),
+        report.checked.separate_with_commas(),
+        format_size(report.verified_bytes, BINARY),
+        report.problems.len().separate_with_commas(),
+        humantime::format_duration(Duration::from_millis(
+            u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+        ))
+    );
+    if report.log_only > 0 {
+        println!(
+            "{} file(s) no longer in the source were checked against the logged size only",
+            report.log_only.separate_with_commas()
+        );
+    }
+    if report.hashed_bytes > 0 {
+        println!("Hashed {}", format_size(report.hashed_bytes, BINARY));
+    }
+}
+
+#[derive(Serialize)]
+struct JsonVerifyReport {
+    source: String,
+    dest: String,
+    checked: usize,
+    verified_bytes: u64,
+    hashed_bytes: u64,
+    /// Files no longer in the source, checked against the logged size alone.
+    log_only: usize,
+    elapsed_seconds: f64,
+    problems: Vec<JsonProblem>,
+}
+
+#[derive(Serialize)]
+struct JsonProblem {
+    kind: &'static str,
+    /// The path as resolved from the log.
+    path: String,
+    relative: Option<String>,
+    message: String,
+}
+
+impl JsonVerifyReport {
+    fn new(report: &RobocopyVerifyReport, source: &Path, dest: &Path, elapsed: Duration) -> Self {
+        Self {
+            source: source.display().to_string(),
+            dest: dest.display().to_string(),
+            checked: report.checked,
+            verified_bytes: report.verified_bytes,
+            hashed_bytes: report.hashed_bytes,
+            log_only: report.log_only,
+            elapsed_seconds: elapsed.as_secs_f64(),
+            problems: report
+                .problems
+                .iter()
+                .map(|problem| JsonProblem {
+                    kind: problem.kind.name(),
+                    path: problem.file.log_path.display().to_string(),
+                    relative: problem
+                        .file
+                        .relative
+                        .as_ref()
+                        .map(|relative| relative.display().to_string()),
+                    message: problem.kind.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ToArgs for VerifyArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![self.robocopy_log_file_path.clone().into()];
+        if let Some(job) = self.job {
+            args.push("--job".into());
+            args.push(job.to_string().into());
+        }
+        if let Some(source) = &self.source {
+            args.push("--source".into());
+            args.push(source.clone().into());
+        }
+        if let Some(dest) = &self.dest {
+            args.push("--dest".into());
+            args.push(dest.clone().into());
+        }
+        if self.hash {
+            args.push("--hash".into());
+        }
+        args.push("--time-tolerance".into());
+        args.push(format_duration(self.time_tolerance).into());
+        args.push("--format".into());
+        args.push(value_enum_arg(&self.format));
+        args
+    }
+}
@@ -0,0 +1,23 @@
+use arbitrary::Unstructured;
+use std::time::Duration;
+
+/// Parse a duration such as `5m`, `1h 30m` or `90s`.
+///
+/// # Errors
+///
+/// Returns a message clap shows when the value is not a duration.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    humantime::parse_duration(value.trim()).map_err(|error| error.to_string())
+}
+
+/// The spelling [`parse_duration`] reads back exactly.
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    humantime::format_duration(duration).to_string()
+}
+
+/// A whole number of seconds up to a month, which survives a round trip
+/// through the command line.
+pub(crate) fn arbitrary_duration(u: &mut Unstructured<'_>) -> arbitrary::Result<Duration> {
+    Ok(Duration::from_secs(u.int_in_range(0..=31 * 24 * 60 * 60)?))
+}
@@ -1,7 +1,11 @@
 // Command submodules are declared inside the `command` module directory.
 
+pub(crate) mod arbitrary_path;
+pub mod byte_size_arg;
+pub mod duration_arg;
 pub mod global_args;
 pub mod json_log_behaviour;
+pub mod output_format;
 pub mod to_args;
 mod command;
 
@@ -0,0 +1,12 @@
+use arbitrary::Arbitrary;
+use clap::ValueEnum;
+
+/// How report subcommands print their results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Arbitrary)]
+pub enum OutputFormat {
+    /// Aligned text for people
+    #[default]
+    Human,
+    /// Machine-readable JSON
+    Json,
+}
@@ -1,3 +1,4 @@
+use clap::ValueEnum;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
@@ -15,6 +16,14 @@ impl<T: ToArgs> ToArgs for &T {
     }
 }
 
+/// The command line spelling of a `ValueEnum` value.
+pub fn value_enum_arg(value: &impl ValueEnum) -> OsString {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().into())
+        .unwrap_or_default()
+}
+
 /// Trait for providing executable and arguments for process invocation
 pub trait Invocable {
     fn path_to_exe(&self) -> PathBuf;
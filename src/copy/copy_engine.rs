@@ -0,0 +1,666 @@
+use crate::copy::copy_options::CopyOptions;
+use crate::copy::copy_plan::CopyAction;
+use crate::copy::copy_plan::CopyDirectoryPlan;
+use crate::copy::copy_plan::CopyError;
+use crate::copy::copy_plan::CopyFilePlan;
+use crate::copy::copy_plan::plan_tree;
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_exit_code::RobocopyExitCode;
+use crate::robocopy::robocopy_file_pattern::RobocopyFilePattern;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_CODE;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+use crate::robocopy::robocopy_options::RobocopyOptions;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use crate::robocopy::robocopy_summary::RobocopySpeed;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use crate::robocopy::robocopy_summary::RobocopySummaryTimes;
+use chrono::Local;
+use crossbeam_channel::Receiver;
+use std::fs::File;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+/// Appended to the destination name while a file is being copied.
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// What a copy did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyOutcome {
+    /// The job summary; `None` when the source could not be read at all.
+    pub summary: Option<RobocopySummary>,
+    pub exit_code: RobocopyExitCode,
+}
+
+/// A robocopy-compatible copy written in Rust, for hosts without robocopy.
+///
+/// Takes the same command line, classifies files the same way and writes
+/// the same log, so everything that reads robocopy logs works on its output.
+#[derive(Debug, Clone)]
+pub struct CopyEngine {
+    command: RobocopyCommand,
+    options: CopyOptions,
+    /// The roots made absolute, as robocopy prints them.
+    source: PathBuf,
+    dest: PathBuf,
+}
+
+/// The log lines for one file and how it counts in the summary.
+struct FileOutcome {
+    entries: Vec<RobocopyLogEntry>,
+    column: RobocopySummaryColumn,
+    size: u64,
+}
+
+/// The log lines for a directory once it has been created or purged, and
+/// whether its files can be copied.
+struct DirectoryOutcome {
+    entries: Vec<RobocopyLogEntry>,
+    copy_files: bool,
+}
+
+/// Running totals for the summary.
+#[derive(Default)]
+struct CopyTotals {
+    dirs: RobocopySummaryRow<u64>,
+    files: RobocopySummaryRow<u64>,
+    bytes: RobocopySummaryRow<Information>,
+    copy_time: Duration,
+}
+
+impl CopyEngine {
+    /// # Errors
+    ///
+    /// Returns an error if the command's switches conflict or use something
+    /// the engine does not implement, or a root cannot be made absolute.
+    pub fn new(command: RobocopyCommand) -> eyre::Result<Self> {
+        command.validate()?;
+        let options = CopyOptions::from_command(&command)?;
+        Ok(Self {
+            source: std::path::absolute(command.source())?,
+            dest: std::path::absolute(command.dest())?,
+            command,
+            options,
+        })
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &CopyOptions {
+        &self.options
+    }
+
+    /// Classify everything in the source and destination without changing either.
+    #[must_use]
+    pub fn plan(&self) -> Vec<CopyDirectoryPlan> {
+        plan_tree(&self.source, &self.dest, &self.options)
+    }
+
+    /// The header robocopy would print for this command, started now.
+    #[must_use]
+    pub fn header(&self) -> RobocopyHeader {
+        let files = if self.command.files().is_empty() {
+            "*.*".to_string()
+        } else {
+            self.command.files().join(" ")
+        };
+        let switches = self
+            .command
+            .switches()
+            .iter()
+            .filter(|switch| switch.log_file().is_none())
+            .map(RobocopySwitch::to_string);
+        let options: Vec<String> = std::iter::once(files.clone()).chain(switches).collect();
+        RobocopyHeader {
+            started: Local::now().into(),
+            source: directory_path(&self.source),
+            dest: directory_path(&self.dest),
+            files: RobocopyFilePattern { inner: files },
+            options: RobocopyOptions {
+                inner: options.join(" "),
+            },
+        }
+    }
+
+    /// Copy the source to the destination, writing the log as it goes.
+    ///
+    /// The writer is flushed after every directory so the log can be tailed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be written or the thread pool
+    /// cannot be started; failures to copy are logged instead.
+    pub fn run<W: Write>(&self, log: &mut RobocopyLogWriter<W>) -> eyre::Result<CopyOutcome> {
+        let header = self.header();
+        let started = Instant::now();
+        if !self.options.no_job_header {
+            log.write_header(&header)?;
+        }
+        let source = &self.source;
+        if let Err(error) = std::fs::read_dir(source) {
+            let error = CopyError::from_io(&error);
+            log.write_entry(&RobocopyLogEntry::Error {
+                when: Local::now(),
+                code: error.code,
+                operation: "Accessing Source Directory".to_string(),
+                path: directory_path(source),
+                message: error.message,
+            })?;
+            log.flush()?;
+            return Ok(CopyOutcome {
+                summary: None,
+                exit_code: RobocopyExitCode(RobocopyExitCode::FATAL),
+            });
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.options.threads)
+            .build()?;
+        let mut totals = CopyTotals::default();
+        let plan = self.plan();
+        // Every directory exists before any file is copied into it
+        let directories: Vec<DirectoryOutcome> = plan
+            .iter()
+            .map(|directory| self.prepare_directory(directory, &mut totals))
+            .collect();
+        // The threads share the files of the whole tree, as with robocopy's `/MT`
+        let copy_started = Instant::now();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        pool.in_place_scope_fifo(|scope| {
+            for (directory_index, (directory, outcome)) in plan.iter().zip(&directories).enumerate()
+            {
+                if !outcome.copy_files {
+                    continue;
+                }
+                for (file_index, file) in directory.files.iter().enumerate() {
+                    let tx = tx.clone();
+                    scope.spawn_fifo(move |_| {
+                        // The log writer only goes away when it failed
+                        let _ = tx.send((directory_index, file_index, self.copy_file(file)));
+                    });
+                }
+            }
+            drop(tx);
+            self.write_in_order(&plan, &directories, &rx, log, &mut totals)
+        })?;
+        totals.copy_time = copy_started.elapsed();
+
+        let copied_bytes = totals.bytes.copied.get::<byte>() as u64;
+        let summary = RobocopySummary {
+            dirs: totals.dirs,
+            files: totals.files,
+            bytes: totals.bytes,
+            times: RobocopySummaryTimes {
+                total: whole_seconds(started.elapsed()),
+                copied: whole_seconds(totals.copy_time),
+                failed: Duration::ZERO,
+                extras: Duration::ZERO,
+            },
+            speed: (copied_bytes > 0 && !self.options.list_only).then(|| {
+                let seconds = totals.copy_time.as_secs_f64().max(0.001);
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    clippy::cast_precision_loss,
+                    reason = "speeds are approximate"
+                )]
+                let bytes_per_second = (copied_bytes as f64 / seconds) as u64;
+                RobocopySpeed {
+                    bytes_per_second,
+                    megabytes_per_minute_milli: bytes_per_second * 60 * 1000 / (1024 * 1024),
+                }
+            }),
+            ended: Local::now().into(),
+        };
+        if !self.options.no_job_summary {
+            log.write_summary(&summary)?;
+        }
+        log.flush()?;
+        Ok(CopyOutcome {
+            exit_code: RobocopyExitCode::from_summary(&summary),
+            summary: Some(summary),
+        })
+    }
+
+    /// Create or purge a directory, counting it and, when it could not be
+    /// created, the files that were meant to go in it.
+    fn prepare_directory(
+        &self,
+        directory: &CopyDirectoryPlan,
+        totals: &mut CopyTotals,
+    ) -> DirectoryOutcome {
+        let mut outcome = DirectoryOutcome {
+            entries: Vec::new(),
+            copy_files: false,
+        };
+        if let Some(error) = &directory.error {
+            totals.dirs.add(RobocopySummaryColumn::Failed, 1);
+            let path = directory_path(&directory.source);
+            outcome.entries.push(if error.code == ACCESS_DENIED_CODE {
+                RobocopyLogEntry::AccessDeniedError {
+                    when: Local::now(),
+                    path,
+                }
+            } else {
+                RobocopyLogEntry::Error {
+                    when: Local::now(),
+                    code: error.code,
+                    operation: "Scanning Source Directory".to_string(),
+                    path,
+                    message: error.message.clone(),
+                }
+            });
+            return outcome;
+        }
+
+        let result = match directory.class {
+            _ if self.options.list_only => Ok(()),
+            RobocopyDirectoryClass::New => std::fs::create_dir_all(&directory.dest)
+                .map_err(|error| ("Creating Destination Directory", error)),
+            RobocopyDirectoryClass::Extra if self.options.purge => {
+                std::fs::remove_dir_all(&directory.dest)
+                    .map_err(|error| ("Deleting Extra Directory", error))
+            }
+            RobocopyDirectoryClass::Existing | RobocopyDirectoryClass::Extra => Ok(()),
+        };
+        // Nothing can be copied into a directory that could not be created
+        let failed = result.is_err() && directory.class == RobocopyDirectoryClass::New;
+        totals.dirs.add(
+            if failed {
+                RobocopySummaryColumn::Failed
+            } else {
+                directory.summary_column()
+            },
+            1,
+        );
+        if !self.options.no_dir_list {
+            outcome.entries.push(RobocopyLogEntry::Directory {
+                class: directory.class,
+                file_count: directory.file_count,
+                path: directory_path(&directory.source),
+            });
+        }
+        if let Err((operation, error)) = result {
+            let error = CopyError::from_io(&error);
+            outcome.entries.push(RobocopyLogEntry::Error {
+                when: Local::now(),
+                code: error.code,
+                operation: operation.to_string(),
+                path: directory_path(&directory.dest),
+                message: error.message,
+            });
+        }
+        if failed {
+            for file in &directory.files {
+                totals.files.add(RobocopySummaryColumn::Failed, 1);
+                totals.bytes.add(
+                    RobocopySummaryColumn::Failed,
+                    Information::new::<byte>(usize::try_from(file.size).unwrap_or(usize::MAX)),
+                );
+            }
+        } else {
+            outcome.copy_files = true;
+        }
+        outcome
+    }
+
+    /// Log each directory and its files in plan order as their copies finish,
+    /// flushing after every directory so the log can be tailed.
+    fn write_in_order<W: Write>(
+        &self,
+        plan: &[CopyDirectoryPlan],
+        directories: &[DirectoryOutcome],
+        rx: &Receiver<(usize, usize, FileOutcome)>,
+        log: &mut RobocopyLogWriter<W>,
+        totals: &mut CopyTotals,
+    ) -> eyre::Result<()> {
+        let mut finished: Vec<Vec<Option<FileOutcome>>> = plan
+            .iter()
+            .map(|directory| directory.files.iter().map(|_| None).collect())
+            .collect();
+        for (directory_index, (directory, outcome)) in plan.iter().zip(directories).enumerate() {
+            for entry in &outcome.entries {
+                log.write_entry(entry)?;
+            }
+            if outcome.copy_files {
+                for file_index in 0..directory.files.len() {
+                    let file = loop {
+                        if let Some(file) = finished[directory_index][file_index].take() {
+                            break file;
+                        }
+                        let (directory_index, file_index, file) = rx.recv()?;
+                        finished[directory_index][file_index] = Some(file);
+                    };
+                    self.write_file(&file, log, totals)?;
+                }
+            }
+            log.flush()?;
+        }
+        Ok(())
+    }
+
+    fn write_file<W: Write>(
+        &self,
+        file: &FileOutcome,
+        log: &mut RobocopyLogWriter<W>,
+        totals: &mut CopyTotals,
+    ) -> eyre::Result<()> {
+        for entry in &file.entries {
+            if self.options.no_file_list && entry.file_class().is_some() {
+                continue;
+            }
+            log.write_entry(entry)?;
+        }
+        totals.files.add(file.column, 1);
+        totals.bytes.add(
+            file.column,
+            Information::new::<byte>(usize::try_from(file.size).unwrap_or(usize::MAX)),
+        );
+        Ok(())
+    }
+
+    fn copy_file(&self, file: &CopyFilePlan) -> FileOutcome {
+        let size = Information::new::<byte>(usize::try_from(file.size).unwrap_or(usize::MAX));
+        let path = if self.options.full_paths {
+            if file.class == RobocopyFileClass::Extra {
+                file.dest.clone()
+            } else {
+                file.source.clone()
+            }
+        } else {
+            file.source
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_default()
+        };
+        let line =
+            |percentages| RobocopyLogEntry::file(file.class, size, path.clone(), percentages);
+        let mut outcome = FileOutcome {
+            entries: Vec::new(),
+            column: file.summary_column(),
+            size: file.size,
+        };
+        match file.action {
+            CopyAction::Skip => {
+                if file.logged {
+                    outcome.entries.push(line(Vec::new()));
+                }
+            }
+            // `/L` lists what would be done, without progress
+            CopyAction::Copy | CopyAction::Purge if self.options.list_only => {
+                outcome.entries.push(line(Vec::new()));
+            }
+            CopyAction::Purge => {
+                outcome.entries.push(line(Vec::new()));
+                if let Err(error) = std::fs::remove_file(&file.dest) {
+                    outcome
+                        .entries
+                        .push(file_error("Deleting Extra File", &file.dest, &error));
+                }
+            }
+            CopyAction::Copy => {
+                for attempt in 0..=self.options.retries {
+                    let mut percentages = Vec::new();
+                    let result = copy_contents(&file.source, &file.dest, &mut percentages);
+                    if self.options.no_progress {
+                        percentages.clear();
+                    }
+                    outcome.entries.push(line(percentages));
+                    let Err(error) = result else { break };
+                    outcome
+                        .entries
+                        .push(file_error("Copying File", &file.source, &error));
+                    if attempt == self.options.retries {
+                        outcome.entries.push(RobocopyLogEntry::RetryLimitExceeded);
+                        outcome.column = RobocopySummaryColumn::Failed;
+                        break;
+                    }
+                    outcome.entries.push(RobocopyLogEntry::Retry {
+                        wait: self.options.wait,
+                    });
+                    std::thread::sleep(self.options.wait);
+                }
+            }
+        }
+        outcome
+    }
+}
+
+/// Copy a file's bytes and modification time, recording each whole
+/// percentage reached the way robocopy prints its progress.
+///
+/// The bytes go to a file next to `dest` that replaces it once complete, so
+/// a failed copy leaves whatever was there before rather than part of a file.
+fn copy_contents(source: &Path, dest: &Path, percentages: &mut Vec<u8>) -> std::io::Result<()> {
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    let partial = PathBuf::from(partial);
+    let result =
+        copy_to(source, &partial, percentages).and_then(|()| std::fs::rename(&partial, dest));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn copy_to(source: &Path, dest: &Path, percentages: &mut Vec<u8>) -> std::io::Result<()> {
+    let mut input = File::open(source)?;
+    let metadata = input.metadata()?;
+    let mut output = File::create(dest)?;
+    let total = metadata.len();
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    let mut copied = 0_u64;
+    loop {
+        let read = input.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buf[..read])?;
+        copied += read as u64;
+        if copied < total {
+            let percent = u8::try_from(copied * 100 / total).unwrap_or(99);
+            if percentages.last().is_none_or(|last| *last < percent) {
+                percentages.push(percent);
+            }
+        }
+    }
+    percentages.push(100);
+    output.set_permissions(metadata.permissions())?;
+    output.set_modified(metadata.modified()?)?;
+    Ok(())
+}
+
+fn file_error(operation: &str, path: &Path, error: &std::io::Error) -> RobocopyLogEntry {
+    let error = CopyError::from_io(error);
+    RobocopyLogEntry::Error {
+        when: Local::now(),
+        code: error.code,
+        operation: operation.to_string(),
+        path: path.to_path_buf(),
+        message: error.message,
+    }
+}
+
+/// A directory path with the trailing separator robocopy prints.
+fn directory_path(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    if text.ends_with(std::path::MAIN_SEPARATOR) {
+        path.to_path_buf()
+    } else {
+        PathBuf::from(format!("{text}{}", std::path::MAIN_SEPARATOR))
+    }
+}
+
+fn whole_seconds(duration: Duration) -> Duration {
+    Duration::from_secs(duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_writer::LineEnding;
+
+    fn run(source: &Path, dest: &Path, switches: &str) -> eyre::Result<(CopyOutcome, RobocopyLog)> {
+        let mut args = vec![source.display().to_string(), dest.display().to_string()];
+        args.extend(switches.split_whitespace().map(String::from));
+        let engine = CopyEngine::new(RobocopyCommand::from_args(&args)?)?;
+        let mut writer = RobocopyLogWriter::new(Vec::new()).with_line_ending(LineEnding::CrLf);
+        let outcome = engine.run(&mut writer)?;
+        let text = String::from_utf8(writer.into_inner())?;
+        let mut logs = RobocopyLog::parse_all(&text)?;
+        assert_eq!(logs.len(), 1, "{text}");
+        Ok((outcome, logs.remove(0)))
+    }
+
+    fn classes(log: &RobocopyLog) -> Vec<(RobocopyFileClass, String)> {
+        log.parts
+            .iter()
+            .filter_map(|entry| {
+                Some((
+                    entry.file_class()?,
+                    entry.path()?.to_string_lossy().into_owned(),
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mirrors_a_tree_and_logs_like_robocopy() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("src");
+        let dest = dir.path().join("dst");
+        std::fs::create_dir_all(source.join("sub/empty"))?;
+        std::fs::create_dir_all(source.join("cache"))?;
+        std::fs::write(source.join("a.txt"), "hello")?;
+        std::fs::write(source.join("skip.tmp"), "temp")?;
+        std::fs::write(source.join("sub/b.txt"), vec![7u8; 3 * COPY_CHUNK_SIZE])?;
+        std::fs::write(source.join("cache/c.txt"), "cached")?;
+
+        let switches = "/MIR /XF *.tmp /XD cache /MT:4 /R:0 /W:0";
+        let (outcome, log) = run(&source, &dest, switches)?;
+        assert_eq!(
+            outcome.exit_code,
+            RobocopyExitCode(RobocopyExitCode::COPIED)
+        );
+        assert_eq!(
+            classes(&log),
+            [
+                (RobocopyFileClass::NewFile, "a.txt".to_string()),
+                (RobocopyFileClass::NewFile, "b.txt".to_string()),
+            ]
+        );
+        let summary = log.summary.expect("written without /NJS");
+        assert_eq!(summary.files.values(), [2, 2, 0, 0, 0, 0]);
+        assert_eq!(summary.dirs.values(), [3, 3, 0, 0, 0, 0]);
+        // The printed summary rounds to 0.1 m, the engine's own is exact
+        let bytes = outcome
+            .summary
+            .map(|summary| summary.bytes.copied.get::<byte>());
+        assert_eq!(bytes, Some(5 + 3 * COPY_CHUNK_SIZE));
+        assert!(dest.join("sub/empty").is_dir());
+        assert!(!dest.join("skip.tmp").exists() && !dest.join("cache").exists());
+        assert_eq!(
+            std::fs::read(dest.join("sub/b.txt"))?.len(),
+            3 * COPY_CHUNK_SIZE
+        );
+
+        // Nothing changed, and extras in the destination are purged
+        std::fs::write(dest.join("extra.txt"), "extra")?;
+        std::fs::create_dir(dest.join("old"))?;
+        let (outcome, log) = run(&source, &dest, &format!("{switches} /V"))?;
+        assert_eq!(
+            outcome.exit_code,
+            RobocopyExitCode(RobocopyExitCode::EXTRAS)
+        );
+        assert_eq!(
+            classes(&log),
+            [
+                (RobocopyFileClass::Extra, "extra.txt".to_string()),
+                (RobocopyFileClass::Same, "a.txt".to_string()),
+                (RobocopyFileClass::Same, "b.txt".to_string()),
+            ]
+        );
+        assert!(!dest.join("extra.txt").exists() && !dest.join("old").exists());
+
+        let (outcome, _) = run(&dir.path().join("missing"), &dest, "/R:0 /W:0")?;
+        assert!(outcome.exit_code.fatal());
+        Ok(())
+    }
+
+    #[test]
+    fn counts_files_under_an_uncreatable_directory_as_failed() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("src");
+        let dest = dir.path().join("dst");
+        std::fs::create_dir_all(source.join("sub"))?;
+        std::fs::create_dir_all(&dest)?;
+        std::fs::write(source.join("b.txt"), "bb")?;
+        std::fs::write(source.join("sub/a.txt"), "aaa")?;
+        // A file where the directory should go
+        std::fs::write(dest.join("sub"), "in the way")?;
+
+        let (outcome, log) = run(&source, &dest, "/E /R:0 /W:0")?;
+        let summary = outcome.summary.expect("the source exists");
+        assert_eq!(summary.dirs.values(), [2, 0, 1, 0, 1, 0]);
+        assert_eq!(summary.files.values(), [2, 1, 0, 0, 1, 0]);
+        let bytes = summary.bytes.values().map(|value| value.get::<byte>());
+        assert_eq!(bytes, [5, 2, 0, 0, 3, 0]);
+        assert_eq!(
+            outcome.exit_code,
+            RobocopyExitCode(RobocopyExitCode::COPIED | RobocopyExitCode::FAILED)
+        );
+        assert!(log.parts.iter().any(|entry| matches!(
+            entry,
+            RobocopyLogEntry::Error { operation, .. } if operation == "Creating Destination Directory"
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn logs_files_in_plan_order_while_copying_across_directories() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("src");
+        let mut expected = Vec::new();
+        for directory in 0..12 {
+            let path = source.join(format!("dir{directory:02}"));
+            std::fs::create_dir_all(&path)?;
+            for file in 0..3 {
+                let name = format!("{directory:02}-{file}.txt");
+                std::fs::write(path.join(&name), vec![b'x'; directory * 1000 + file])?;
+                expected.push((RobocopyFileClass::NewFile, name));
+            }
+        }
+
+        let (outcome, log) = run(&source, &dir.path().join("dst"), "/E /MT:8 /R:0 /W:0")?;
+        assert_eq!(classes(&log), expected);
+        let summary = outcome.summary.expect("the source exists");
+        assert_eq!(summary.files.copied, 36);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_copy_leaves_the_destination_as_it_was() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dest = dir.path().join("a.txt");
+        std::fs::write(&dest, "previous copy")?;
+        // Opening a directory works on Unix, but reading it fails
+        let mut percentages = Vec::new();
+        assert!(copy_contents(dir.path(), &dest, &mut percentages).is_err());
+        assert_eq!(std::fs::read_to_string(&dest)?, "previous copy");
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use crate::glob_match::glob_match;
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Robocopy's default `/R:n`.
+pub const DEFAULT_RETRIES: u32 = 1_000_000;
+/// Robocopy's default `/W:n`.
+pub const DEFAULT_WAIT: Duration = Duration::from_secs(30);
+/// `/MAXAGE` and `/MINAGE` values above this are dates rather than days.
+const AGE_DATE_THRESHOLD: u32 = 1900;
+
+/// What the copy engine does, resolved from a command's switches.
+#[allow(clippy::struct_excessive_bools, reason = "one field per robocopy flag")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyOptions {
+    /// File name patterns to copy; empty copies every file.
+    pub files: Vec<String>,
+    /// `/S`, `/E` or `/MIR`: descend into subdirectories.
+    pub subdirectories: bool,
+    /// `/E` or `/MIR`: create subdirectories even when nothing is copied into them.
+    pub empty_subdirectories: bool,
+    /// `/LEV:n`: levels of the source tree to copy, counting the top as 1.
+    pub levels: Option<u32>,
+    /// `/PURGE` or `/MIR`: delete extra files and directories from the destination.
+    pub purge: bool,
+    pub exclude_files: Vec<String>,
+    pub exclude_dirs: Vec<String>,
+    pub exclude_changed: bool,
+    pub exclude_newer: bool,
+    pub exclude_older: bool,
+    pub exclude_extra: bool,
+    pub include_same: bool,
+    /// `/IT`: copy files whose permissions differ even when size and time match.
+    pub include_tweaked: bool,
+    /// `/XL`: skip files and directories that are not in the destination.
+    pub exclude_lonely: bool,
+    /// `/XJ`: don't follow symbolic links to directories.
+    pub exclude_junctions: bool,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// `/MAXAGE`: files last modified before this are skipped.
+    pub max_age: Option<SystemTime>,
+    /// `/MINAGE`: files last modified after this are skipped.
+    pub min_age: Option<SystemTime>,
+    /// `/FFT`: times within two seconds of each other count as equal.
+    pub fat_file_times: bool,
+    /// `/L`: classify and log everything without changing the destination.
+    pub list_only: bool,
+    /// `/MT:n`: files copied at once.
+    pub threads: usize,
+    pub retries: u32,
+    pub wait: Duration,
+    /// `/V`: also list files that were skipped.
+    pub verbose: bool,
+    pub full_paths: bool,
+    pub no_progress: bool,
+    pub no_file_list: bool,
+    pub no_dir_list: bool,
+    pub no_job_header: bool,
+    pub no_job_summary: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            subdirectories: false,
+            empty_subdirectories: false,
+            levels: None,
+            purge: false,
+            exclude_files: Vec::new(),
+            exclude_dirs: Vec::new(),
+            exclude_changed: false,
+            exclude_newer: false,
+            exclude_older: false,
+            exclude_extra: false,
+            include_same: false,
+            include_tweaked: false,
+            exclude_lonely: false,
+            exclude_junctions: false,
+            min_size: None,
+            max_size: None,
+            max_age: None,
+            min_age: None,
+            fat_file_times: false,
+            list_only: false,
+            threads: 1,
+            retries: DEFAULT_RETRIES,
+            wait: DEFAULT_WAIT,
+            verbose: false,
+            full_paths: false,
+            no_progress: false,
+            no_file_list: false,
+            no_dir_list: false,
+            no_job_header: false,
+            no_job_summary: false,
+        }
+    }
+}
+
+impl CopyOptions {
+    /// Resolve the switches of `command`.
+    ///
+    /// Switches that only matter on Windows, such as `/COPY:DAT` or `/Z`, are
+    /// accepted and ignored; where the log is written is up to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a switch the engine does not implement, or a
+    /// `/MAXAGE` or `/MINAGE` date that does not exist.
+    pub fn from_command(command: &RobocopyCommand) -> eyre::Result<Self> {
+        let mut options = Self {
+            files: command.files().to_vec(),
+            ..Self::default()
+        };
+        let now = SystemTime::now();
+        for switch in command.switches() {
+            match switch {
+                RobocopySwitch::Subdirectories => options.subdirectories = true,
+                RobocopySwitch::EmptySubdirectories => {
+                    options.subdirectories = true;
+                    options.empty_subdirectories = true;
+                }
+                RobocopySwitch::Mirror => {
+                    options.subdirectories = true;
+                    options.empty_subdirectories = true;
+                    options.purge = true;
+                }
+                RobocopySwitch::Levels(levels) => options.levels = Some(*levels),
+                RobocopySwitch::Purge => options.purge = true,
+                RobocopySwitch::ExcludeFiles(names) => {
+                    options.exclude_files.extend_from_slice(names);
+                }
+                RobocopySwitch::ExcludeDirs(names) => options.exclude_dirs.extend_from_slice(names),
+                RobocopySwitch::ExcludeChanged => options.exclude_changed = true,
+                RobocopySwitch::ExcludeNewer => options.exclude_newer = true,
+                RobocopySwitch::ExcludeOlder => options.exclude_older = true,
+                RobocopySwitch::ExcludeExtra => options.exclude_extra = true,
+                RobocopySwitch::IncludeSame => options.include_same = true,
+                RobocopySwitch::IncludeTweaked => options.include_tweaked = true,
+                RobocopySwitch::ExcludeLonely => options.exclude_lonely = true,
+                RobocopySwitch::ExcludeJunctions => options.exclude_junctions = true,
+                RobocopySwitch::MinSize(bytes) => options.min_size = Some(*bytes),
+                RobocopySwitch::MaxSize(bytes) => options.max_size = Some(*bytes),
+                RobocopySwitch::MaxAge(age) => options.max_age = Some(age_cutoff(*age, now)?),
+                RobocopySwitch::MinAge(age) => options.min_age = Some(age_cutoff(*age, now)?),
+                RobocopySwitch::FatFileTimes => options.fat_file_times = true,
+                RobocopySwitch::List => options.list_only = true,
+                RobocopySwitch::Threads(threads) => {
+                    options.threads = usize::try_from(*threads).unwrap_or(1).max(1);
+                }
+                RobocopySwitch::Retries(retries) => options.retries = *retries,
+                RobocopySwitch::Wait(seconds) => {
+                    options.wait = Duration::from_secs(u64::from(*seconds));
+                }
+                RobocopySwitch::Verbose => options.verbose = true,
+                RobocopySwitch::FullPaths => options.full_paths = true,
+                RobocopySwitch::NoProgress => options.no_progress = true,
+                RobocopySwitch::NoFileList => options.no_file_list = true,
+                RobocopySwitch::NoDirList => options.no_dir_list = true,
+                RobocopySwitch::NoJobHeader => options.no_job_header = true,
+                RobocopySwitch::NoJobSummary => options.no_job_summary = true,
+                RobocopySwitch::Copy(_)
+                | RobocopySwitch::DirectoryCopy(_)
+                | RobocopySwitch::Restartable
+                | RobocopySwitch::Backup
+                | RobocopySwitch::RestartableBackup
+                | RobocopySwitch::Unbuffered
+                | RobocopySwitch::Log(_)
+                | RobocopySwitch::AppendLog(_)
+                | RobocopySwitch::Tee
+                | RobocopySwitch::Eta => {}
+                other => eyre::bail!("{other} is not supported by the copy engine"),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Whether a file name is one the patterns select and `/XF` does not exclude.
+    #[must_use]
+    pub fn selects_file_name(&self, name: &str) -> bool {
+        (self.files.is_empty() || self.files.iter().any(|pattern| glob_match(pattern, name)))
+            && !self
+                .exclude_files
+                .iter()
+                .any(|pattern| glob_match(pattern, name))
+    }
+
+    /// Whether `/XD` excludes a directory, by name or by full path.
+    #[must_use]
+    pub fn excludes_dir(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let full = path.to_string_lossy();
+        self.exclude_dirs.iter().any(|pattern| {
+            glob_match(pattern, &name) || glob_match(pattern.trim_end_matches(['/', '\\']), &full)
+        })
+    }
+
+    /// Whether a file passes `/MIN`, `/MAX`, `/MAXAGE` and `/MINAGE`.
+    #[must_use]
+    pub fn selects_file(&self, size: u64, modified: SystemTime) -> bool {
+        self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+            && self.max_age.is_none_or(|cutoff| modified >= cutoff)
+            && self.min_age.is_none_or(|cutoff| modified <= cutoff)
+    }
+
+    /// Whether robocopy would count two modification times as the same.
+    #[must_use]
+    pub fn same_time(&self, left: SystemTime, right: SystemTime) -> bool {
+        let difference = left
+            .duration_since(right)
+            .or_else(|_| right.duration_since(left))
+            .unwrap_or_default();
+        if self.fat_file_times {
+            difference <= Duration::from_secs(2)
+        } else {
+            difference.is_zero()
+        }
+    }
+}
+
+/// The cutoff for `/MAXAGE:n` or `/MINAGE:n`: `n` days ago, or the date `YYYYMMDD`.
+fn age_cutoff(age: u32, now: SystemTime) -> eyre::Result<SystemTime> {
+    if age < AGE_DATE_THRESHOLD {
+        return Ok(now
+            .checked_sub(Duration::from_hours(24) * age)
+            .unwrap_or(SystemTime::UNIX_EPOCH));
+    }
+    let date = NaiveDate::from_ymd_opt(
+        i32::try_from(age / 10_000).unwrap_or(i32::MAX),
+        age / 100 % 100,
+        age % 100,
+    )
+    .ok_or_else(|| eyre::eyre!("{age} is not a YYYYMMDD date"))?;
+    let midnight = Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .ok_or_else(|| eyre::eyre!("Midnight on {date} does not exist locally"))?;
+    Ok(midnight.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_switches() -> eyre::Result<()> {
+        let args = [
+            "src",
+            "dst",
+            "*.txt",
+            "*.log",
+            "/MIR",
+            "/XF",
+            "skip*",
+            "/XD",
+            "cache",
+            "/MT:4",
+            "/R:2",
+            "/W:0",
+            "/MAXAGE:20240131",
+        ]
+        .map(String::from);
+        let options = CopyOptions::from_command(&RobocopyCommand::from_args(&args)?)?;
+        assert!(options.subdirectories && options.empty_subdirectories && options.purge);
+        assert_eq!(options.threads, 4);
+        assert_eq!((options.retries, options.wait), (2, Duration::ZERO));
+        assert!(options.selects_file_name("notes.TXT"));
+        assert!(!options.selects_file_name("skip.txt"));
+        assert!(!options.selects_file_name("image.png"));
+        assert!(options.excludes_dir(Path::new("/srv/data/cache")));
+        let cutoff = options.max_age.expect("set by /MAXAGE");
+        assert!(!options.selects_file(1, cutoff - Duration::from_secs(1)));
+        assert!(options.selects_file(1, cutoff));
+
+        let unicode = RobocopyCommand::new("src", "dst").with_switch(RobocopySwitch::Unicode);
+        assert!(CopyOptions::from_command(&unicode).is_err());
+        Ok(())
+    }
+}
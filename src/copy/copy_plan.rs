@@ -0,0 +1,488 @@
+use crate::copy::copy_options::CopyOptions;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use crate::robocopy::robocopy_win32_error::win32_error_info;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+/// What the engine does with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyAction {
+    Copy,
+    Skip,
+    /// Delete an extra file from the destination.
+    Purge,
+}
+
+/// One file in the source or destination and what robocopy would make of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyFilePlan {
+    pub class: RobocopyFileClass,
+    /// Size of the source file, or of the destination file for extras.
+    pub size: u64,
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub action: CopyAction,
+    /// Whether the file gets a line in the log; skipped files only do with `/V`.
+    pub logged: bool,
+}
+
+impl CopyFilePlan {
+    /// The summary column the file counts in if carrying out the plan succeeds.
+    #[must_use]
+    pub fn summary_column(&self) -> RobocopySummaryColumn {
+        match (self.action, self.class.summary_column()) {
+            (CopyAction::Copy, _) => RobocopySummaryColumn::Copied,
+            // Files a switch such as `/XO` held back
+            (CopyAction::Skip, RobocopySummaryColumn::Copied) => RobocopySummaryColumn::Skipped,
+            (_, column) => column,
+        }
+    }
+}
+
+/// A Win32-style error, as robocopy would report it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyError {
+    pub code: u32,
+    pub message: String,
+}
+
+/// `ERROR_GEN_FAILURE`, for failures no more specific code describes.
+const GENERIC_ERROR_CODE: u32 = 31;
+
+impl CopyError {
+    /// The Win32 code robocopy would print for an I/O error.
+    ///
+    /// On Windows that is the error the OS reported. Elsewhere OS errors are
+    /// errno values, so the kind of error is mapped onto the nearest Win32
+    /// code instead, and anything without one gets a generic code and keeps
+    /// the OS's own message.
+    #[must_use]
+    pub fn from_io(error: &io::Error) -> Self {
+        let os_code = error
+            .raw_os_error()
+            .filter(|_| cfg!(windows))
+            .and_then(|code| u32::try_from(code).ok());
+        let code = os_code.unwrap_or_else(|| match error.kind() {
+            io::ErrorKind::NotFound => 2,
+            io::ErrorKind::PermissionDenied => 5,
+            io::ErrorKind::ResourceBusy => 32,
+            io::ErrorKind::StorageFull => 112,
+            io::ErrorKind::AlreadyExists => 183,
+            io::ErrorKind::DirectoryNotEmpty => 145,
+            _ => GENERIC_ERROR_CODE,
+        });
+        let message = win32_error_info(code)
+            .map_or_else(|| error.to_string(), |info| info.message.to_string());
+        Self { code, message }
+    }
+}
+
+/// One directory line and the files listed under it, in log order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyDirectoryPlan {
+    pub class: RobocopyDirectoryClass,
+    /// The source directory; for extra directories, the destination one.
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    /// Files selected in the source directory, or -1 for extra directories.
+    pub file_count: i64,
+    /// Extra files first, then source files, each sorted by name.
+    pub files: Vec<CopyFilePlan>,
+    /// Set when the source directory could not be listed.
+    pub error: Option<CopyError>,
+}
+
+impl CopyDirectoryPlan {
+    /// The summary column the directory counts in if carrying out the plan succeeds.
+    #[must_use]
+    pub fn summary_column(&self) -> RobocopySummaryColumn {
+        if self.error.is_some() {
+            return RobocopySummaryColumn::Failed;
+        }
+        match self.class {
+            RobocopyDirectoryClass::Existing => RobocopySummaryColumn::Skipped,
+            RobocopyDirectoryClass::New => RobocopySummaryColumn::Copied,
+            RobocopyDirectoryClass::Extra => RobocopySummaryColumn::Extras,
+        }
+    }
+}
+
+/// Walk `source` and `dest` and classify every directory and file the way
+/// robocopy would, in the order it would log them.
+#[must_use]
+pub fn plan_tree(source: &Path, dest: &Path, options: &CopyOptions) -> Vec<CopyDirectoryPlan> {
+    let mut plans = Vec::new();
+    plan_directory(source, dest, 1, options, &mut plans);
+    plans
+}
+
+/// The summary rows carrying out `plans` would produce if nothing failed.
+#[must_use]
+pub fn plan_counts(plans: &[CopyDirectoryPlan]) -> RobocopySummaryCounts {
+    let mut counts = RobocopySummaryCounts::default();
+    for directory in plans {
+        counts.dirs.add(directory.summary_column(), 1);
+        for file in &directory.files {
+            counts.files.add(file.summary_column(), 1);
+            counts.bytes.add(
+                file.summary_column(),
+                Information::new::<byte>(usize::try_from(file.size).unwrap_or(usize::MAX)),
+            );
+        }
+    }
+    counts
+}
+
+/// Plan one directory and everything below it; returns whether the subtree
+/// selected any source files.
+fn plan_directory(
+    source: &Path,
+    dest: &Path,
+    level: u32,
+    options: &CopyOptions,
+    plans: &mut Vec<CopyDirectoryPlan>,
+) -> bool {
+    let dest_exists = dest.is_dir();
+    let mut plan = CopyDirectoryPlan {
+        class: if dest_exists {
+            RobocopyDirectoryClass::Existing
+        } else {
+            RobocopyDirectoryClass::New
+        },
+        source: source.to_path_buf(),
+        dest: dest.to_path_buf(),
+        file_count: 0,
+        files: Vec::new(),
+        error: None,
+    };
+    let source_entries = match list(source, options.exclude_junctions) {
+        Ok(entries) => entries,
+        Err(error) => {
+            plan.error = Some(CopyError::from_io(&error));
+            plans.push(plan);
+            return false;
+        }
+    };
+    let dest_entries = if dest_exists {
+        list(dest, false).unwrap_or_default()
+    } else {
+        BTreeMap::new()
+    };
+
+    let extra_dirs = if options.exclude_extra {
+        Vec::new()
+    } else {
+        plan_extras(
+            source,
+            dest,
+            &source_entries,
+            &dest_entries,
+            options,
+            &mut plan.files,
+        )
+    };
+
+    let mut subdirectories = Vec::new();
+    for (name, metadata) in &source_entries {
+        let source_path = source.join(name);
+        let dest_path = dest.join(name);
+        if metadata.is_dir() {
+            if options.subdirectories
+                && options.levels.is_none_or(|levels| level < levels)
+                && !options.excludes_dir(&source_path)
+                && (!options.exclude_lonely || dest_entries.get(name).is_some_and(Metadata::is_dir))
+            {
+                subdirectories.push((source_path, dest_path));
+            }
+            continue;
+        }
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if !options.selects_file_name(&name.to_string_lossy())
+            || !options.selects_file(metadata.len(), modified)
+        {
+            continue;
+        }
+        plan.file_count += 1;
+        plan.files.push(plan_source_file(
+            metadata,
+            dest_entries.get(name),
+            source_path,
+            dest_path,
+            options,
+        ));
+    }
+
+    let mut has_files = plan.file_count > 0;
+    plans.push(plan);
+    for path in extra_dirs {
+        plans.push(CopyDirectoryPlan {
+            class: RobocopyDirectoryClass::Extra,
+            source: path.clone(),
+            dest: path,
+            file_count: -1,
+            files: Vec::new(),
+            error: None,
+        });
+    }
+    for (source_path, dest_path) in subdirectories {
+        let start = plans.len();
+        let subtree_has_files = plan_directory(&source_path, &dest_path, level + 1, options, plans);
+        // `/S` leaves out directories with nothing to copy beneath them
+        if !subtree_has_files && !options.empty_subdirectories {
+            plans.truncate(start);
+        }
+        has_files |= subtree_has_files;
+    }
+    has_files
+}
+
+/// Plan the files in `dest` that are not in the source, and return the
+/// directories that are not.
+fn plan_extras(
+    source: &Path,
+    dest: &Path,
+    source_entries: &BTreeMap<OsString, Metadata>,
+    dest_entries: &BTreeMap<OsString, Metadata>,
+    options: &CopyOptions,
+    files: &mut Vec<CopyFilePlan>,
+) -> Vec<PathBuf> {
+    let mut extra_dirs = Vec::new();
+    let extra_action = if options.purge {
+        CopyAction::Purge
+    } else {
+        CopyAction::Skip
+    };
+    for (name, metadata) in dest_entries {
+        if source_entries.contains_key(name) {
+            continue;
+        }
+        let path = dest.join(name);
+        if metadata.is_dir() {
+            if !options.excludes_dir(&path) && options.subdirectories {
+                extra_dirs.push(path);
+            }
+        } else if options.selects_file_name(&name.to_string_lossy()) {
+            files.push(CopyFilePlan {
+                class: RobocopyFileClass::Extra,
+                size: metadata.len(),
+                source: source.join(name),
+                dest: path,
+                action: extra_action,
+                logged: true,
+            });
+        }
+    }
+    extra_dirs
+}
+
+/// Classify a source file against what is at its destination, and decide
+/// whether to copy it.
+fn plan_source_file(
+    metadata: &Metadata,
+    existing: Option<&Metadata>,
+    source: PathBuf,
+    dest: PathBuf,
+    options: &CopyOptions,
+) -> CopyFilePlan {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let class = match existing {
+        None if options.exclude_lonely => RobocopyFileClass::Lonely,
+        None => RobocopyFileClass::NewFile,
+        Some(existing) if existing.is_dir() => RobocopyFileClass::Mismatch,
+        Some(existing) => {
+            let existing_modified = existing.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            if options.same_time(modified, existing_modified) {
+                if metadata.len() != existing.len() {
+                    RobocopyFileClass::Changed
+                } else if metadata.permissions() == existing.permissions() {
+                    RobocopyFileClass::Same
+                } else {
+                    RobocopyFileClass::Tweaked
+                }
+            } else if modified > existing_modified {
+                RobocopyFileClass::Newer
+            } else {
+                RobocopyFileClass::Older
+            }
+        }
+    };
+    let copies = match class {
+        RobocopyFileClass::NewFile => true,
+        RobocopyFileClass::Newer => !options.exclude_newer,
+        RobocopyFileClass::Older => !options.exclude_older,
+        RobocopyFileClass::Changed => !options.exclude_changed,
+        RobocopyFileClass::Same => options.include_same,
+        RobocopyFileClass::Tweaked => options.include_tweaked,
+        _ => false,
+    };
+    CopyFilePlan {
+        class,
+        size: metadata.len(),
+        source,
+        dest,
+        action: if copies {
+            CopyAction::Copy
+        } else {
+            CopyAction::Skip
+        },
+        logged: copies || options.verbose || class == RobocopyFileClass::Mismatch,
+    }
+}
+
+/// A directory's entries by name, so they come out sorted. Symbolic links
+/// are followed, and links to directories left out when `skip_dir_links`.
+fn list(directory: &Path, skip_dir_links: bool) -> io::Result<BTreeMap<OsString, Metadata>> {
+    let mut entries = BTreeMap::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let link = entry.file_type()?.is_symlink();
+        // A dangling link is listed as itself
+        let metadata = std::fs::metadata(entry.path()).or_else(|_| entry.metadata())?;
+        if link && skip_dir_links && metadata.is_dir() {
+            continue;
+        }
+        entries.insert(entry.file_name(), metadata);
+    }
+    Ok(entries)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_command::RobocopyCommand;
+    use CopyAction::Copy;
+    use CopyAction::Purge;
+    use CopyAction::Skip;
+    use RobocopyFileClass as Class;
+    use std::fs::File;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    fn options(switches: &str) -> eyre::Result<CopyOptions> {
+        let mut args = vec!["src".to_string(), "dst".to_string()];
+        args.extend(switches.split_whitespace().map(String::from));
+        CopyOptions::from_command(&RobocopyCommand::from_args(&args)?)
+    }
+
+    fn write(path: &Path, contents: &str, modified: SystemTime) -> io::Result<()> {
+        std::fs::write(path, contents)?;
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified)
+    }
+
+    fn classes(plans: &[CopyDirectoryPlan]) -> Vec<(String, RobocopyFileClass, CopyAction)> {
+        plans
+            .iter()
+            .flat_map(|plan| &plan.files)
+            .map(|file| {
+                let name = file.dest.file_name().unwrap_or_default();
+                (name.to_string_lossy().into_owned(), file.class, file.action)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classifies_files_like_robocopy() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("src");
+        let dest = dir.path().join("dst");
+        std::fs::create_dir_all(source.join("only-source"))?;
+        std::fs::create_dir_all(dest.join("only-dest"))?;
+        let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let later = then + Duration::from_mins(1);
+        for (name, source_file, dest_file) in [
+            ("changed", ("abc", then), Some(("abcd", then))),
+            ("new", ("abc", then), None),
+            ("newer", ("abc", later), Some(("abc", then))),
+            ("older", ("abc", then), Some(("abc", later))),
+            ("same", ("abc", then), Some(("abc", then))),
+            ("tweaked", ("abc", then), Some(("abc", then))),
+        ] {
+            write(&source.join(name), source_file.0, source_file.1)?;
+            if let Some((contents, modified)) = dest_file {
+                write(&dest.join(name), contents, modified)?;
+            }
+        }
+        write(&source.join("only-source/inner"), "abc", then)?;
+        write(&dest.join("extra"), "abc", then)?;
+        std::fs::set_permissions(dest.join("tweaked"), std::fs::Permissions::from_mode(0o600))?;
+        std::fs::set_permissions(
+            source.join("tweaked"),
+            std::fs::Permissions::from_mode(0o644),
+        )?;
+
+        let plans = plan_tree(&source, &dest, &options("/MIR /XO")?);
+        let expected = |name: &str, class, action| (name.to_string(), class, action);
+        assert_eq!(
+            classes(&plans),
+            [
+                expected("extra", Class::Extra, Purge),
+                expected("changed", Class::Changed, Copy),
+                expected("new", Class::NewFile, Copy),
+                expected("newer", Class::Newer, Copy),
+                expected("older", Class::Older, Skip),
+                expected("same", Class::Same, Skip),
+                expected("tweaked", Class::Tweaked, Skip),
+                expected("inner", Class::NewFile, Copy),
+            ]
+        );
+        let directories: Vec<_> = plans.iter().map(|plan| plan.class).collect();
+        assert_eq!(
+            directories,
+            [
+                RobocopyDirectoryClass::Existing,
+                RobocopyDirectoryClass::Extra,
+                RobocopyDirectoryClass::New,
+            ]
+        );
+        let counts = plan_counts(&plans);
+        assert_eq!(counts.files.values(), [7, 4, 3, 0, 0, 1]);
+        assert_eq!(counts.dirs.values(), [2, 1, 1, 0, 0, 1]);
+
+        // `/XL` leaves out whatever the destination lacks; `/IT` copies tweaked files
+        let plans = plan_tree(&source, &dest, &options("/E /XL /IT")?);
+        assert!(
+            plans
+                .iter()
+                .all(|plan| plan.class != RobocopyDirectoryClass::New)
+        );
+        let lonely_and_tweaked: Vec<_> = classes(&plans)
+            .into_iter()
+            .filter(|(name, ..)| name == "new" || name == "tweaked")
+            .collect();
+        assert_eq!(
+            lonely_and_tweaked,
+            [
+                expected("new", Class::Lonely, Skip),
+                expected("tweaked", Class::Tweaked, Copy),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn maps_io_errors_onto_win32_codes() {
+        let denied = CopyError::from_io(&io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(
+            (denied.code, denied.message.as_str()),
+            (5, "Access is denied.")
+        );
+        // errno 5 is EIO, not Win32's access denied; it keeps the OS message
+        let eio = io::Error::from_raw_os_error(5);
+        let error = CopyError::from_io(&eio);
+        assert_eq!(error.code, GENERIC_ERROR_CODE);
+        assert_eq!(error.message, eio.to_string());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod copy_engine;
+pub mod copy_options;
+pub mod copy_plan;
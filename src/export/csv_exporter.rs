@@ -0,0 +1,58 @@
+use crate::export::export_row::EXPORT_COLUMNS;
+use crate::export::export_row::RobocopyExportRow;
+use crate::export::file_class_name;
+use std::io::Write;
+
+/// Writes export rows as CSV with a header row and the columns in
+/// [`EXPORT_COLUMNS`]; missing values are empty and times are RFC 3339.
+#[derive(Debug)]
+pub struct RobocopyCsvExporter<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> RobocopyCsvExporter<W> {
+    /// Start a CSV document by writing its header row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header cannot be written.
+    pub fn new(writer: W) -> eyre::Result<Self> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(EXPORT_COLUMNS)?;
+        Ok(Self { writer })
+    }
+
+    /// Append a row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row cannot be written.
+    pub fn write(&mut self, row: &RobocopyExportRow) -> eyre::Result<()> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        self.writer.write_record([
+            row.job.to_string(),
+            row.kind.name().to_string(),
+            optional(row.class.map(file_class_name)),
+            row.path.clone(),
+            optional(row.size.map(|size| size.to_string())),
+            optional(row.percent.map(|percent| percent.to_string())),
+            row.failed.to_string(),
+            optional(row.time.map(|time| time.to_rfc3339())),
+            optional(row.code.map(|code| code.to_string())),
+            optional(row.operation.clone()),
+            optional(row.message.clone()),
+        ])?;
+        Ok(())
+    }
+
+    /// Flush buffered rows and return the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffered rows cannot be written.
+    pub fn finish(self) -> eyre::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|error| eyre::eyre!("Failed to flush CSV: {}", error.error()))
+    }
+}
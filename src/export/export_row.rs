@@ -0,0 +1,318 @@
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use chrono::DateTime;
+use chrono::Local;
+use uom::si::information::byte;
+
+/// Whether a flat export row is a file or an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobocopyExportRowKind {
+    File,
+    Error,
+}
+
+impl RobocopyExportRowKind {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            RobocopyExportRowKind::File => "file",
+            RobocopyExportRowKind::Error => "error",
+        }
+    }
+}
+
+/// One finished file or one error, with the same columns either way so the
+/// rows load into a spreadsheet or a dataframe as a single table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyExportRow {
+    /// Position of the job in its log file, from 1.
+    pub job: u32,
+    pub kind: RobocopyExportRowKind,
+    pub class: Option<RobocopyFileClass>,
+    /// Full source path.
+    pub path: String,
+    pub size: Option<u64>,
+    /// Last progress percentage robocopy printed for a file.
+    pub percent: Option<u8>,
+    /// Whether robocopy gave up: on a file after its last retry, or on the
+    /// path of an error.
+    pub failed: bool,
+    pub time: Option<DateTime<Local>>,
+    pub code: Option<u32>,
+    pub operation: Option<String>,
+    pub message: Option<String>,
+}
+
+/// The names of the columns, in order.
+pub const EXPORT_COLUMNS: [&str; 11] = [
+    "job",
+    "kind",
+    "class",
+    "path",
+    "size",
+    "percent",
+    "failed",
+    "time",
+    "code",
+    "operation",
+    "message",
+];
+
+/// Turns parser output into export rows.
+///
+/// A file is only finished once robocopy moves on, since progress ticks and
+/// retries rewrite it and a retry limit can still fail it; errors that arrive
+/// in the meantime wait behind it so rows stay in log order.
+#[derive(Debug, Default)]
+pub struct RobocopyExportRows {
+    job: u32,
+    resolver: RobocopyPathResolver,
+    current_file: Option<RobocopyExportRow>,
+    waiting: Vec<RobocopyExportRow>,
+    // Index into `waiting` of the last error, which a retry limit marks failed
+    last_error: Option<usize>,
+    directory_error: bool,
+}
+
+impl RobocopyExportRows {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a parser advance, handing every row that is now final to `emit`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from `emit`.
+    pub fn push_advance(
+        &mut self,
+        advance: &RobocopyParseAdvance,
+        emit: &mut impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        match advance {
+            RobocopyParseAdvance::Header(_) => {
+                self.flush(emit)?;
+                self.job += 1;
+                self.resolver = RobocopyPathResolver::new();
+            }
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry, emit)?,
+            RobocopyParseAdvance::Summary(_) => self.flush(emit)?,
+            RobocopyParseAdvance::NeedMoreData => {}
+        }
+        Ok(())
+    }
+
+    /// Emit whatever is still waiting, at the end of the log.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from `emit`.
+    pub fn finish(
+        &mut self,
+        emit: &mut impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        self.flush(emit)
+    }
+
+    fn push(
+        &mut self,
+        entry: &RobocopyLogEntry,
+        emit: &mut impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        let path = self
+            .resolver
+            .resolve(entry)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match entry {
+            RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                let class = entry.file_class();
+                let size = Some(size.get::<byte>() as u64);
+                let percent = entry.percentages().and_then(|p| p.last().copied());
+                self.directory_error = false;
+                if let Some(current) = &mut self.current_file
+                    && current.class == class
+                    && current.path == path
+                {
+                    current.size = size;
+                    current.percent = percent;
+                    return Ok(());
+                }
+                self.flush(emit)?;
+                self.current_file = Some(RobocopyExportRow {
+                    job: self.job,
+                    kind: RobocopyExportRowKind::File,
+                    class,
+                    path,
+                    size,
+                    percent,
+                    failed: false,
+                    time: None,
+                    code: None,
+                    operation: None,
+                    message: None,
+                });
+            }
+            RobocopyLogEntry::Directory { .. } => {
+                self.flush(emit)?;
+                self.directory_error = false;
+            }
+            RobocopyLogEntry::AccessDeniedError { when, .. } => {
+                self.directory_error = false;
+                self.push_error(
+                    RobocopyExportRow {
+                        job: self.job,
+                        kind: RobocopyExportRowKind::Error,
+                        class: None,
+                        path,
+                        size: None,
+                        percent: None,
+                        // Robocopy gives up on unreadable directories straight away
+                        failed: true,
+                        time: Some(*when),
+                        code: entry.error_code(),
+                        operation: Some(ACCESS_DENIED_OPERATION.to_string()),
+                        message: Some(ACCESS_DENIED_MESSAGE.to_string()),
+                    },
+                    emit,
+                )?;
+            }
+            RobocopyLogEntry::Error {
+                when,
+                code,
+                operation,
+                message,
+                ..
+            } => {
+                self.directory_error = operation.to_ascii_lowercase().contains("directory");
+                self.push_error(
+                    RobocopyExportRow {
+                        job: self.job,
+                        kind: RobocopyExportRowKind::Error,
+                        class: None,
+                        path,
+                        size: None,
+                        percent: None,
+                        failed: false,
+                        time: Some(*when),
+                        code: Some(*code),
+                        operation: Some(operation.clone()),
+                        message: Some(message.clone()),
+                    },
+                    emit,
+                )?;
+            }
+            RobocopyLogEntry::Retry { .. } => {}
+            RobocopyLogEntry::RetryLimitExceeded => {
+                if let Some(error) = self.last_error.take() {
+                    self.waiting[error].failed = true;
+                }
+                if !std::mem::take(&mut self.directory_error)
+                    && let Some(current) = &mut self.current_file
+                {
+                    current.failed = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_error(
+        &mut self,
+        row: RobocopyExportRow,
+        emit: &mut impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        // Only the file in progress and the last error can still fail
+        if self.current_file.is_none() {
+            self.flush(emit)?;
+        }
+        self.waiting.push(row);
+        self.last_error = Some(self.waiting.len() - 1);
+        Ok(())
+    }
+
+    fn flush(
+        &mut self,
+        emit: &mut impl FnMut(RobocopyExportRow) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        if let Some(file) = self.current_file.take() {
+            emit(file)?;
+        }
+        self.last_error = None;
+        for row in self.waiting.drain(..) {
+            emit(row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+
+    #[test]
+    fn one_row_per_file_and_error() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 120,
+            error_percent: 25,
+            retries: 2,
+            ..Default::default()
+        };
+        let generator = RobocopyLogGenerator::new(config);
+        let log = generator.generate_log();
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(&log.to_string());
+        let mut rows = RobocopyExportRows::new();
+        let mut found = Vec::new();
+        let mut emit = |row| {
+            found.push(row);
+            Ok(())
+        };
+        loop {
+            let advance = parser.advance()?;
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                break;
+            }
+            rows.push_advance(&advance, &mut emit)?;
+        }
+        rows.finish(&mut emit)?;
+
+        let counts = RobocopySummaryCounts::from_entries(&log.parts);
+        let files: Vec<_> = found
+            .iter()
+            .filter(|row| row.kind == RobocopyExportRowKind::File)
+            .collect();
+        let errors = log
+            .parts
+            .iter()
+            .filter(|entry| entry.error_code().is_some())
+            .count();
+        assert_eq!(found.len() - files.len(), errors);
+        assert!(counts.files.failed > 0);
+        assert_eq!(
+            files.iter().filter(|row| row.failed).count() as u64,
+            counts.files.failed
+        );
+        assert_eq!(
+            files.iter().filter_map(|row| row.size).sum::<u64>(),
+            counts
+                .bytes
+                .values()
+                .iter()
+                .skip(1)
+                .map(|bytes| bytes.get::<byte>() as u64)
+                .sum::<u64>()
+        );
+        assert!(found.iter().all(|row| row.job == 1 && !row.path.is_empty()));
+        Ok(())
+    }
+}
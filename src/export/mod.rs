@@ -0,0 +1,61 @@
+pub mod arrow_exporter;
+pub mod csv_exporter;
+pub mod export_row;
+pub mod sqlite_exporter;
+
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use clap::ValueEnum;
+use eyre::WrapErr;
+use std::io::Read;
+use std::path::Path;
+
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Parse a log file a chunk at a time, handing every header, entry and
+/// summary to `visit`, so logs larger than memory can be exported.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, the parser rejects it, or
+/// `visit` fails.
+pub fn for_each_advance(
+    path: &Path,
+    mut visit: impl FnMut(RobocopyParseAdvance) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let mut file =
+        std::fs::File::open(path).wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+    let mut parser = RobocopyLogParser::new();
+    let mut buf = vec![0u8; READ_CHUNK_SIZE];
+    loop {
+        let read = file
+            .read(&mut buf)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        if read > 0 {
+            parser.accept_bytes(&buf[..read]);
+        }
+        loop {
+            match parser
+                .advance()
+                .wrap_err_with(|| format!("Failed to parse {}", path.display()))?
+            {
+                RobocopyParseAdvance::NeedMoreData => break,
+                advance => visit(advance)?,
+            }
+        }
+        if read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// The `--class` spelling of a classification, e.g. `new-file`, which stays
+/// stable across robocopy's label quirks.
+#[must_use]
+pub fn file_class_name(class: RobocopyFileClass) -> String {
+    class
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,537 @@
+use crate::export::file_class_name;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use chrono::DateTime;
+use chrono::Local;
+use eyre::WrapErr;
+use rusqlite::Connection;
+use rusqlite::params;
+use std::path::Path;
+use uom::si::information::byte;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY,
+    log_path TEXT NOT NULL,
+    -- Position of the job in its log file, from 1; /LOG+ files hold several
+    job_index INTEGER NOT NULL,
+    exported_at TEXT NOT NULL,
+    finished INTEGER NOT NULL DEFAULT 0,
+    ended TEXT
+);
+CREATE TABLE IF NOT EXISTS headers (
+    job_id INTEGER PRIMARY KEY REFERENCES jobs(id),
+    started TEXT NOT NULL,
+    source TEXT NOT NULL,
+    dest TEXT NOT NULL,
+    files TEXT NOT NULL,
+    options TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS options (
+    job_id INTEGER NOT NULL REFERENCES jobs(id),
+    position INTEGER NOT NULL,
+    switch TEXT NOT NULL,
+    value TEXT,
+    PRIMARY KEY (job_id, position)
+);
+CREATE TABLE IF NOT EXISTS directories (
+    id INTEGER PRIMARY KEY,
+    job_id INTEGER NOT NULL REFERENCES jobs(id),
+    class TEXT NOT NULL,
+    file_count INTEGER,
+    path TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY,
+    job_id INTEGER NOT NULL REFERENCES jobs(id),
+    directory_id INTEGER REFERENCES directories(id),
+    class TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    percent INTEGER,
+    failed INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS errors (
+    id INTEGER PRIMARY KEY,
+    job_id INTEGER NOT NULL REFERENCES jobs(id),
+    file_id INTEGER REFERENCES files(id),
+    occurred TEXT NOT NULL,
+    code INTEGER NOT NULL,
+    operation TEXT NOT NULL,
+    path TEXT NOT NULL,
+    message TEXT NOT NULL,
+    gave_up INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS retries (
+    id INTEGER PRIMARY KEY,
+    job_id INTEGER NOT NULL REFERENCES jobs(id),
+    error_id INTEGER REFERENCES errors(id),
+    wait_seconds INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS directories_job ON directories(job_id);
+CREATE INDEX IF NOT EXISTS directories_path ON directories(path);
+CREATE INDEX IF NOT EXISTS files_job ON files(job_id);
+CREATE INDEX IF NOT EXISTS files_path ON files(path);
+CREATE INDEX IF NOT EXISTS files_class ON files(class);
+CREATE INDEX IF NOT EXISTS errors_job ON errors(job_id);
+CREATE INDEX IF NOT EXISTS errors_code ON errors(code);
+CREATE INDEX IF NOT EXISTS retries_error ON retries(error_id);
+";
+
+/// Writes parsed logs into a `SQLite` database for ad-hoc SQL.
+///
+/// Every job becomes a new row in `jobs`, so exporting into an existing
+/// database appends rather than overwrites. Everything is written in one
+/// transaction that [`Self::finish`] commits.
+#[derive(Debug)]
+pub struct RobocopySqliteExporter {
+    connection: Connection,
+    log_path: String,
+    jobs_in_log: i64,
+    job: Option<SqliteJob>,
+}
+
+#[derive(Debug)]
+struct SqliteJob {
+    id: i64,
+    resolver: RobocopyPathResolver,
+    directory_id: Option<i64>,
+    current_file: Option<PendingFile>,
+    last_error_id: Option<i64>,
+    // Whether the last error was about a directory rather than the current file
+    directory_error: bool,
+}
+
+/// The file robocopy is working on; progress ticks and retries rewrite it,
+/// so it is only written once robocopy moves on or an error needs its id.
+#[derive(Debug)]
+struct PendingFile {
+    class: RobocopyFileClass,
+    path: String,
+    size: u64,
+    percent: Option<u8>,
+    failed: bool,
+    directory_id: Option<i64>,
+    row_id: Option<i64>,
+    dirty: bool,
+}
+
+impl RobocopySqliteExporter {
+    /// Open or create the database at `database` and start a transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or the schema cannot
+    /// be created.
+    pub fn open(database: &Path, log_path: &Path) -> eyre::Result<Self> {
+        let connection = Connection::open(database)
+            .wrap_err_with(|| format!("Failed to open {}", database.display()))?;
+        Self::new(connection, log_path)
+    }
+
+    /// Export into an already open connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema cannot be created.
+    pub fn new(connection: Connection, log_path: &Path) -> eyre::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch("BEGIN")?;
+        Ok(Self {
+            connection,
+            log_path: log_path.display().to_string(),
+            jobs_in_log: 0,
+            job: None,
+        })
+    }
+
+    /// Record a header, entry or summary from the parser.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a row cannot be written.
+    pub fn push_advance(&mut self, advance: &RobocopyParseAdvance) -> eyre::Result<()> {
+        match advance {
+            RobocopyParseAdvance::Header(header) => self.start_job(header),
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry),
+            RobocopyParseAdvance::Summary(summary) => self.finish_job(summary),
+            RobocopyParseAdvance::NeedMoreData => Ok(()),
+        }
+    }
+
+    /// Commit everything written so far and return the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the last file cannot be written or the commit fails.
+    pub fn finish(mut self) -> eyre::Result<Connection> {
+        self.flush_file()?;
+        self.connection.execute_batch("COMMIT")?;
+        Ok(self.connection)
+    }
+
+    fn start_job(&mut self, header: &RobocopyHeader) -> eyre::Result<()> {
+        self.flush_file()?;
+        self.jobs_in_log += 1;
+        self.connection
+            .prepare_cached(
+                "INSERT INTO jobs (log_path, job_index, exported_at) VALUES (?1, ?2, ?3)",
+            )?
+            .execute(params![
+                self.log_path,
+                self.jobs_in_log,
+                Local::now().to_rfc3339()
+            ])?;
+        let id = self.connection.last_insert_rowid();
+        let options = header.options.to_string();
+        self.connection
+            .prepare_cached(
+                "INSERT INTO headers (job_id, started, source, dest, files, options)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute(params![
+                id,
+                header.started.to_rfc3339(),
+                header.source.display().to_string(),
+                header.dest.display().to_string(),
+                header.files.to_string(),
+                options.trim(),
+            ])?;
+        let mut insert_option = self.connection.prepare_cached(
+            "INSERT INTO options (job_id, position, switch, value) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (position, token) in options
+            .split_whitespace()
+            .filter(|token| token.starts_with('/'))
+            .enumerate()
+        {
+            // `/MT:16` is the switch `/MT` with the value `16`
+            let (switch, value) = match token.split_once(':') {
+                Some((switch, value)) => (switch, Some(value)),
+                None => (token, None),
+            };
+            insert_option.execute(params![id, position, switch, value])?;
+        }
+        drop(insert_option);
+        self.job = Some(SqliteJob {
+            id,
+            resolver: RobocopyPathResolver::new(),
+            directory_id: None,
+            current_file: None,
+            last_error_id: None,
+            directory_error: false,
+        });
+        Ok(())
+    }
+
+    fn finish_job(&mut self, summary: &RobocopySummary) -> eyre::Result<()> {
+        self.flush_file()?;
+        let Some(job) = &self.job else { return Ok(()) };
+        self.connection
+            .prepare_cached("UPDATE jobs SET finished = 1, ended = ?2 WHERE id = ?1")?
+            .execute(params![job.id, summary.ended.to_rfc3339()])?;
+        Ok(())
+    }
+
+    fn push(&mut self, entry: &RobocopyLogEntry) -> eyre::Result<()> {
+        // Entries before any header have no job to belong to
+        let Some(job) = &mut self.job else {
+            return Ok(());
+        };
+        let path = job
+            .resolver
+            .resolve(entry)
+            .map(|path| path.to_string_lossy().into_owned());
+        match entry {
+            RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                let class = entry.file_class().unwrap_or(RobocopyFileClass::NewFile);
+                let path = path.unwrap_or_default();
+                let size = size.get::<byte>() as u64;
+                let percent = entry.percentages().and_then(|p| p.last().copied());
+                job.directory_error = false;
+                if let Some(current) = &mut job.current_file
+                    && current.class == class
+                    && current.path == path
+                {
+                    current.size = size;
+                    current.percent = percent;
+                    current.dirty = true;
+                    return Ok(());
+                }
+                let directory_id = job.directory_id;
+                self.flush_file()?;
+                if let Some(job) = &mut self.job {
+                    job.current_file = Some(PendingFile {
+                        class,
+                        path,
+                        size,
+                        percent,
+                        failed: false,
+                        directory_id,
+                        row_id: None,
+                        dirty: true,
+                    });
+                }
+            }
+            RobocopyLogEntry::Directory {
+                class, file_count, ..
+            } => {
+                let job_id = job.id;
+                self.flush_file()?;
+                self.connection
+                    .prepare_cached(
+                        "INSERT INTO directories (job_id, class, file_count, path)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![
+                        job_id,
+                        directory_class_name(*class),
+                        (*file_count >= 0).then_some(*file_count),
+                        path.unwrap_or_default(),
+                    ])?;
+                let id = self.connection.last_insert_rowid();
+                if let Some(job) = &mut self.job {
+                    job.directory_id = Some(id);
+                    job.current_file = None;
+                    job.directory_error = false;
+                }
+            }
+            RobocopyLogEntry::AccessDeniedError { when, .. }
+            | RobocopyLogEntry::Error { when, .. } => {
+                self.push_error(entry, *when, &path.unwrap_or_default())?;
+            }
+            RobocopyLogEntry::Retry { wait } => {
+                self.connection
+                    .prepare_cached(
+                        "INSERT INTO retries (job_id, error_id, wait_seconds) VALUES (?1, ?2, ?3)",
+                    )?
+                    .execute(params![job.id, job.last_error_id, wait.as_secs()])?;
+            }
+            RobocopyLogEntry::RetryLimitExceeded => {
+                if !std::mem::take(&mut job.directory_error)
+                    && let Some(current) = &mut job.current_file
+                {
+                    current.failed = true;
+                    current.dirty = true;
+                }
+                if let Some(error_id) = job.last_error_id.take() {
+                    self.connection
+                        .prepare_cached("UPDATE errors SET gave_up = 1 WHERE id = ?1")?
+                        .execute(params![error_id])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_error(
+        &mut self,
+        entry: &RobocopyLogEntry,
+        when: DateTime<Local>,
+        path: &str,
+    ) -> eyre::Result<()> {
+        let Some(job) = &self.job else {
+            return Ok(());
+        };
+        let (code, operation, message, directory_error) = match entry {
+            RobocopyLogEntry::Error {
+                code,
+                operation,
+                message,
+                ..
+            } => (
+                *code,
+                operation.as_str(),
+                message.as_str(),
+                operation.to_ascii_lowercase().contains("directory"),
+            ),
+            _ => (
+                entry.error_code().unwrap_or_default(),
+                ACCESS_DENIED_OPERATION,
+                ACCESS_DENIED_MESSAGE,
+                true,
+            ),
+        };
+        let about_current_file = !directory_error
+            && job
+                .current_file
+                .as_ref()
+                .is_some_and(|current| current.path == path);
+        let file_id = if about_current_file {
+            self.file_id()?
+        } else {
+            None
+        };
+        let Some(job) = &mut self.job else {
+            return Ok(());
+        };
+        job.directory_error = directory_error;
+        let job_id = job.id;
+        self.connection
+            .prepare_cached(
+                "INSERT INTO errors (job_id, file_id, occurred, code, operation, path, message, gave_up)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?
+            .execute(params![
+                job_id,
+                file_id,
+                when.to_rfc3339(),
+                code,
+                operation,
+                path,
+                message,
+                // Robocopy gives up on unreadable directories straight away
+                matches!(entry, RobocopyLogEntry::AccessDeniedError { .. }),
+            ])?;
+        let id = self.connection.last_insert_rowid();
+        if let Some(job) = &mut self.job {
+            job.last_error_id = Some(id);
+        }
+        Ok(())
+    }
+
+    /// The row id of the current file, writing it first if needed.
+    fn file_id(&mut self) -> eyre::Result<Option<i64>> {
+        self.write_file()?;
+        Ok(self
+            .job
+            .as_ref()
+            .and_then(|job| job.current_file.as_ref())
+            .and_then(|current| current.row_id))
+    }
+
+    fn flush_file(&mut self) -> eyre::Result<()> {
+        self.write_file()?;
+        if let Some(job) = &mut self.job {
+            job.current_file = None;
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self) -> eyre::Result<()> {
+        let Some(job) = &mut self.job else {
+            return Ok(());
+        };
+        let Some(current) = &mut job.current_file else {
+            return Ok(());
+        };
+        if !current.dirty {
+            return Ok(());
+        }
+        current.dirty = false;
+        if let Some(row_id) = current.row_id {
+            self.connection
+                .prepare_cached(
+                    "UPDATE files SET size = ?2, percent = ?3, failed = ?4 WHERE id = ?1",
+                )?
+                .execute(params![
+                    row_id,
+                    current.size,
+                    current.percent,
+                    current.failed
+                ])?;
+        } else {
+            self.connection
+                .prepare_cached(
+                    "INSERT INTO files (job_id, directory_id, class, size, path, percent, failed)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?
+                .execute(params![
+                    job.id,
+                    current.directory_id,
+                    file_class_name(current.class),
+                    current.size,
+                    current.path,
+                    current.percent,
+                    current.failed,
+                ])?;
+            current.row_id = Some(self.connection.last_insert_rowid());
+        }
+        Ok(())
+    }
+}
+
+fn directory_class_name(class: RobocopyDirectoryClass) -> &'static str {
+    match class {
+        RobocopyDirectoryClass::Existing => "existing",
+        RobocopyDirectoryClass::New => "new",
+        RobocopyDirectoryClass::Extra => "extra",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::for_each_advance;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+
+    #[test]
+    fn exports_and_appends_jobs() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = RobocopyLogGeneratorConfig {
+            files: 120,
+            error_percent: 25,
+            retries: 2,
+            ..Default::default()
+        };
+        let generator = RobocopyLogGenerator::new(config);
+        let log = generator.generate_log();
+        let log_path = dir.path().join("robocopy.log");
+        std::fs::write(&log_path, generator.generate_bytes()?)?;
+        let database = dir.path().join("robocopy.sqlite");
+
+        for _ in 0..2 {
+            let mut exporter = RobocopySqliteExporter::open(&database, &log_path)?;
+            for_each_advance(&log_path, |advance| exporter.push_advance(&advance))?;
+            exporter.finish()?;
+        }
+
+        let connection = Connection::open(&database)?;
+        let count = |sql: &str| -> eyre::Result<u64> {
+            Ok(connection.query_row(sql, [], |row| row.get(0))?)
+        };
+        assert_eq!(count("SELECT COUNT(*) FROM jobs WHERE finished = 1")?, 2);
+        let counts = RobocopySummaryCounts::from_entries(&log.parts);
+        assert!(counts.files.failed > 0);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM files WHERE job_id = 2")?,
+            counts.files.total + counts.files.extras
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM files WHERE job_id = 2 AND failed = 1")?,
+            counts.files.failed
+        );
+        let retries = log
+            .parts
+            .iter()
+            .filter(|entry| matches!(entry, RobocopyLogEntry::Retry { .. }))
+            .count() as u64;
+        assert_eq!(
+            count("SELECT COUNT(*) FROM retries WHERE job_id = 1")?,
+            retries
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM retries WHERE error_id IS NULL")?,
+            0
+        );
+        // Failed files are linked to the errors that gave up on them
+        assert_eq!(
+            count(
+                "SELECT COUNT(*) FROM files JOIN errors ON errors.file_id = files.id
+                 WHERE files.failed = 1 AND errors.gave_up = 1"
+            )?,
+            2 * counts.files.failed
+        );
+        assert!(count("SELECT COUNT(*) FROM options WHERE switch = '/R'")? == 2);
+        Ok(())
+    }
+}
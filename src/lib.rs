@@ -4,9 +4,16 @@
 //! (extracted from `teamy-mft`). It also provides logging initialization helpers
 //! so consumers can initialize tracing the same way the binary does.
 
+pub mod alert;
 pub mod cli;
+pub mod copy;
+pub mod export;
+pub mod glob_match;
 pub mod logging;
+pub mod metrics;
 pub mod robocopy;
+pub mod tail;
+pub mod tui;
 
 /// Re-export the logging initializer so callers can do `teamy_robocopy::init_tracing`.
 ///
@@ -27,6 +27,10 @@ pub const DEFAULT_EXTRA_FILTERS: &str = r"bevy_shader=warn,offset_allocator=warn
 /// If initializing the global subscriber fails (commonly in test environments
 /// where multiple test harnesses attempt to initialize tracing), the function
 /// prints a diagnostic to stderr and returns Ok(()) so callers can continue.
+///
+/// # Panics
+///
+/// The JSON writer panics if the shared log file handle cannot be locked or cloned.
 pub fn init_tracing(
     level: Level,
     json_behaviour: &crate::cli::json_log_behaviour::JsonLogBehaviour,
@@ -1,13 +1,12 @@
 //! Simple CLI entrypoint that mirrors `teamy-mft` main behavior.
 //! - Installs color-eyre for better error reports
 //! - Parses CLI via `clap` using the `Cli` type from `crate::cli`
-//! - Optionally attaches to an existing console when `--console-pid` is used
+//! - Optionally attaches to an existing console when `--console-pid` is used (Windows only)
 //! - Invokes the selected command
 use clap::CommandFactory;
 use clap::FromArgMatches;
 use eyre::Result;
 use teamy_robocopy::cli::Cli;
-use teamy_windows::console::console_attach;
 
 /// Entrypoint for the program to reduce coupling to the name of this crate.
 ///
@@ -23,8 +22,9 @@ fn main() -> Result<()> {
     let cli = Cli::from_arg_matches(&clap_cmd.get_matches())?;
 
     // If requested, attach to an existing console (hidden global flag)
+    #[cfg(windows)]
     if let Some(pid) = cli.global_args.console_pid {
-        console_attach(pid)?;
+        teamy_windows::console::console_attach(pid)?;
     }
 
     // Invoke the requested command
@@ -0,0 +1,159 @@
+use crate::metrics::robocopy_metrics::PROMETHEUS_CONTENT_TYPE;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::time::Duration;
+use tracing::debug;
+use tracing::warn;
+
+/// Requests with a longer request line and headers than this are cut off.
+const MAX_REQUEST_HEAD: u64 = 16 * 1024;
+
+/// Answer scrapes on `listener` until it fails, rendering the body of
+/// `GET /metrics` with `render` for every request.
+///
+/// Each connection is answered on its own thread, so a client that stalls
+/// mid-request cannot hold up other scrapes, and closed after the response,
+/// which is all a Prometheus scraper needs.
+///
+/// # Errors
+///
+/// Does not return while the listener keeps accepting connections; errors on
+/// individual connections are logged and skipped.
+pub fn serve_metrics(
+    listener: &TcpListener,
+    render: impl Fn() -> String + Sync,
+) -> eyre::Result<()> {
+    std::thread::scope(|scope| {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let render = &render;
+                    scope.spawn(move || {
+                        if let Err(error) = respond(stream, render) {
+                            debug!("Failed to answer a metrics request: {error}");
+                        }
+                    });
+                }
+                Err(error) => warn!("Failed to accept a metrics connection: {error}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, render: &impl Fn() -> String) -> eyre::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = BufReader::new((&stream).take(MAX_REQUEST_HEAD));
+    let mut request_line = String::new();
+    request.read_line(&mut request_line)?;
+    // The headers do not matter, but the client expects them to be read
+    let mut header = String::new();
+    while request.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default();
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => ("200 OK", PROMETHEUS_CONTENT_TYPE, render()),
+        ("GET" | "HEAD", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Metrics are served at /metrics\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET and HEAD are supported\n".to_string(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(body.as_bytes())?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::robocopy_metrics::RobocopyLogMetrics;
+    use crate::metrics::robocopy_metrics::RobocopyMetricsExposition;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+    use std::net::SocketAddr;
+    use std::time::Instant;
+
+    fn get(address: SocketAddr, path: &str) -> eyre::Result<String> {
+        let mut stream = TcpStream::connect(address)?;
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: {address}\r\nAccept: */*\r\n\r\n"
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    #[test]
+    fn serves_metrics_over_http() -> eyre::Result<()> {
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(include_str!("../robocopy/sample.txt"));
+        let mut metrics = RobocopyLogMetrics::new("sample.txt");
+        loop {
+            let advance = parser.advance()?;
+            metrics.push_advance(&advance, Instant::now())?;
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                break;
+            }
+        }
+        let exposition = RobocopyMetricsExposition(std::slice::from_ref(&metrics)).to_string();
+
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let body = exposition.clone();
+        std::thread::spawn(move || serve_metrics(&listener, || body.clone()));
+
+        let response = get(address, "/metrics")?;
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .expect("response has a head");
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+        assert!(head.contains(PROMETHEUS_CONTENT_TYPE));
+        assert_eq!(body, exposition);
+        assert!(body.contains(r#"robocopy_errors_total{log="sample.txt",source="#));
+
+        assert!(get(address, "/")?.starts_with("HTTP/1.1 404"));
+        Ok(())
+    }
+
+    #[test]
+    fn stalled_client_does_not_hold_up_scrapes() -> eyre::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        std::thread::spawn(move || serve_metrics(&listener, || "up 1\n".to_string()));
+
+        // Connects but never finishes its request
+        let mut stalled = TcpStream::connect(address)?;
+        write!(stalled, "GET /metrics HTTP/1.1\r\n")?;
+        let started = Instant::now();
+        assert!(get(address, "/metrics")?.ends_with("up 1\n"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,2 @@
+pub mod metrics_server;
+pub mod robocopy_metrics;
@@ -0,0 +1,431 @@
+use crate::export::export_row::RobocopyExportRow;
+use crate::export::export_row::RobocopyExportRowKind;
+use crate::export::export_row::RobocopyExportRows;
+use crate::export::file_class_name;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use chrono::DateTime;
+use chrono::Local;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::time::Instant;
+use uom::si::information::byte;
+
+/// The content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Counters for one robocopy job, identified by its source and destination.
+#[derive(Debug, Clone)]
+pub struct RobocopyJobMetrics {
+    pub source: String,
+    pub dest: String,
+    pub started: DateTime<Local>,
+    /// Finished files by `--class` name, e.g. `new-file`.
+    pub files: BTreeMap<String, u64>,
+    /// Bytes of finished files by `--class` name.
+    pub bytes: BTreeMap<String, u64>,
+    /// Files robocopy gave up on after its last retry.
+    pub failed_files: u64,
+    /// Errors by Win32 error code, counted as soon as they are logged.
+    pub errors: BTreeMap<u32, u64>,
+    pub retries: u64,
+    pub progress: RobocopyProgress,
+}
+
+impl RobocopyJobMetrics {
+    #[must_use]
+    pub fn new(header: &RobocopyHeader) -> Self {
+        Self {
+            source: header.source.display().to_string(),
+            dest: header.dest.display().to_string(),
+            started: *header.started.as_datetime(),
+            files: BTreeMap::new(),
+            bytes: BTreeMap::new(),
+            failed_files: 0,
+            errors: BTreeMap::new(),
+            retries: 0,
+            progress: RobocopyProgress::new(),
+        }
+    }
+
+    fn record_row(&mut self, row: &RobocopyExportRow) {
+        if row.kind != RobocopyExportRowKind::File {
+            return;
+        }
+        let Some(class) = row.class else { return };
+        let class = file_class_name(class);
+        *self.bytes.entry(class.clone()).or_default() += row.size.unwrap_or(0);
+        *self.files.entry(class).or_default() += 1;
+        if row.failed {
+            self.failed_files += 1;
+        }
+    }
+}
+
+/// Metrics for every job seen in one log file.
+///
+/// Jobs are keyed by source and destination, so a nightly job appending to
+/// the same log keeps its series and its counters start over, which
+/// Prometheus reads as a counter reset.
+#[derive(Debug)]
+pub struct RobocopyLogMetrics {
+    pub log: String,
+    /// Whether the log is being followed; cleared when it fails to parse.
+    pub up: bool,
+    pub jobs: Vec<RobocopyJobMetrics>,
+    current: Option<usize>,
+    rows: RobocopyExportRows,
+}
+
+impl RobocopyLogMetrics {
+    #[must_use]
+    pub fn new(log: impl Into<String>) -> Self {
+        Self {
+            log: log.into(),
+            up: true,
+            jobs: Vec::new(),
+            current: None,
+            rows: RobocopyExportRows::new(),
+        }
+    }
+
+    /// Handle anything the parser produced, seen at `now`.
+    ///
+    /// # Errors
+    ///
+    /// Does not currently fail; the signature follows [`RobocopyExportRows`].
+    pub fn push_advance(
+        &mut self,
+        advance: &RobocopyParseAdvance,
+        now: Instant,
+    ) -> eyre::Result<()> {
+        let Self {
+            jobs,
+            current,
+            rows,
+            ..
+        } = self;
+        // Rows still waiting belong to the job that is ending
+        rows.push_advance(advance, &mut |row| {
+            if let Some(job) = current.and_then(|index| jobs.get_mut(index)) {
+                job.record_row(&row);
+            }
+            Ok(())
+        })?;
+        if let RobocopyParseAdvance::Header(header) = advance {
+            let job = RobocopyJobMetrics::new(header);
+            let existing = jobs
+                .iter()
+                .position(|existing| existing.source == job.source && existing.dest == job.dest);
+            let index = if let Some(index) = existing {
+                jobs[index] = job;
+                index
+            } else {
+                jobs.push(job);
+                jobs.len() - 1
+            };
+            *current = Some(index);
+        }
+        let Some(job) = current.and_then(|index| jobs.get_mut(index)) else {
+            return Ok(());
+        };
+        if let RobocopyParseAdvance::LogEntry(entry) = advance {
+            if let Some(code) = entry.error_code() {
+                *job.errors.entry(code).or_default() += 1;
+            }
+            if matches!(entry, RobocopyLogEntry::Retry { .. }) {
+                job.retries += 1;
+            }
+        }
+        job.progress.push_advance(advance, now);
+        Ok(())
+    }
+
+    /// Take a rate sample for every job, so a stalled job's throughput decays
+    /// between scrapes.
+    pub fn tick(&mut self, now: Instant) {
+        for job in &mut self.jobs {
+            job.progress.tick(now);
+        }
+    }
+
+    /// Forget the rates measured while catching up on existing output.
+    pub fn restart_clock(&mut self, now: Instant) {
+        for job in &mut self.jobs {
+            job.progress.restart_clock(now);
+        }
+    }
+
+    /// Start over after the log was truncated or replaced; jobs keep their
+    /// series until their header shows up again.
+    pub fn reset(&mut self) {
+        self.up = true;
+        self.current = None;
+        self.rows = RobocopyExportRows::new();
+    }
+}
+
+/// Renders logs in the Prometheus text exposition format.
+#[derive(Debug, Clone, Copy)]
+pub struct RobocopyMetricsExposition<'a>(pub &'a [RobocopyLogMetrics]);
+
+struct MetricFamily {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    // Labels beyond the job's, and the value
+    samples: Vec<(String, String)>,
+}
+
+impl RobocopyMetricsExposition<'_> {
+    fn jobs(&self) -> impl Iterator<Item = (String, &RobocopyJobMetrics)> {
+        self.0.iter().flat_map(|log| {
+            log.jobs.iter().map(|job| {
+                let labels = format!(
+                    "log=\"{}\",source=\"{}\",dest=\"{}\"",
+                    escape_label_value(&log.log),
+                    escape_label_value(&job.source),
+                    escape_label_value(&job.dest)
+                );
+                (labels, job)
+            })
+        })
+    }
+
+    fn family(
+        &self,
+        name: &'static str,
+        kind: &'static str,
+        help: &'static str,
+        samples: impl Fn(&RobocopyJobMetrics) -> Vec<(String, String)>,
+    ) -> MetricFamily {
+        MetricFamily {
+            name,
+            kind,
+            help,
+            samples: self
+                .jobs()
+                .flat_map(|(labels, job)| {
+                    samples(job).into_iter().map(move |(extra, value)| {
+                        let labels = if extra.is_empty() {
+                            labels.clone()
+                        } else {
+                            format!("{labels},{extra}")
+                        };
+                        (labels, value)
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn families(&self) -> Vec<MetricFamily> {
+        let single = |value: String| vec![(String::new(), value)];
+        vec![
+            MetricFamily {
+                name: "robocopy_log_up",
+                kind: "gauge",
+                help: "Whether the log is being followed, 0 after it failed to parse.",
+                samples: self
+                    .0
+                    .iter()
+                    .map(|log| {
+                        (
+                            format!("log=\"{}\"", escape_label_value(&log.log)),
+                            u8::from(log.up).to_string(),
+                        )
+                    })
+                    .collect(),
+            },
+            self.family(
+                "robocopy_files_total",
+                "counter",
+                "Files robocopy finished, by classification.",
+                |job| by_label("class", &job.files),
+            ),
+            self.family(
+                "robocopy_file_bytes_total",
+                "counter",
+                "Size of the files robocopy finished, by classification.",
+                |job| by_label("class", &job.bytes),
+            ),
+            self.family(
+                "robocopy_failed_files_total",
+                "counter",
+                "Files robocopy gave up on after its last retry.",
+                |job| single(job.failed_files.to_string()),
+            ),
+            self.family(
+                "robocopy_errors_total",
+                "counter",
+                "Errors robocopy logged, by Win32 error code.",
+                |job| by_label("code", &job.errors),
+            ),
+            self.family(
+                "robocopy_retries_total",
+                "counter",
+                "Times robocopy waited to retry an operation.",
+                |job| single(job.retries.to_string()),
+            ),
+            self.family(
+                "robocopy_copied_bytes",
+                "gauge",
+                "Bytes copied so far, including the file in flight.",
+                |job| single(job.progress.bytes_done().get::<byte>().to_string()),
+            ),
+            self.family(
+                "robocopy_current_file_size_bytes",
+                "gauge",
+                "Size of the file being copied.",
+                |job| {
+                    let snapshot = job.progress.snapshot();
+                    snapshot
+                        .current_file
+                        .map_or_else(Vec::new, |file| single(file.size.get::<byte>().to_string()))
+                },
+            ),
+            self.family(
+                "robocopy_current_file_percent",
+                "gauge",
+                "Latest percentage robocopy printed for the file being copied.",
+                |job| {
+                    let snapshot = job.progress.snapshot();
+                    snapshot
+                        .current_file
+                        .and_then(|file| file.percent)
+                        .map_or_else(Vec::new, |percent| single(percent.to_string()))
+                },
+            ),
+            self.family(
+                "robocopy_throughput_bytes_per_second",
+                "gauge",
+                "Smoothed copy rate.",
+                |job| {
+                    let snapshot = job.progress.snapshot();
+                    snapshot
+                        .bytes_per_second
+                        .map_or_else(Vec::new, |rate| single(format!("{rate:.1}")))
+                },
+            ),
+            self.family(
+                "robocopy_job_start_time_seconds",
+                "gauge",
+                "When the job started, in seconds since the Unix epoch.",
+                |job| single(job.started.timestamp().to_string()),
+            ),
+            self.family(
+                "robocopy_job_finished",
+                "gauge",
+                "Whether the job summary has been logged.",
+                |job| single(u8::from(job.progress.snapshot().finished).to_string()),
+            ),
+        ]
+    }
+}
+
+impl Display for RobocopyMetricsExposition<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for family in self.families() {
+            writeln!(f, "# HELP {} {}", family.name, family.help)?;
+            writeln!(f, "# TYPE {} {}", family.name, family.kind)?;
+            for (labels, value) in family.samples {
+                writeln!(f, "{}{{{labels}}} {value}", family.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn by_label<K: Display>(label: &str, counts: &BTreeMap<K, u64>) -> Vec<(String, String)> {
+    counts
+        .iter()
+        .map(|(key, count)| {
+            (
+                format!("{label}=\"{}\"", escape_label_value(&key.to_string())),
+                count.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Escape a label value: Windows paths are full of backslashes.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+
+    fn sample(exposition: &str, prefix: &str) -> u64 {
+        exposition
+            .lines()
+            .filter(|line| line.starts_with(prefix))
+            .filter_map(|line| line.rsplit_once(' ')?.1.parse::<u64>().ok())
+            .sum()
+    }
+
+    #[test]
+    fn counts_match_the_summary() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 80,
+            error_percent: 25,
+            retries: 2,
+            ..Default::default()
+        };
+        let log = RobocopyLogGenerator::new(config).generate_log();
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(&log.to_string());
+        let mut metrics = RobocopyLogMetrics::new(r#"C:\logs\"nightly".log"#);
+        loop {
+            let advance = parser.advance()?;
+            metrics.push_advance(&advance, Instant::now())?;
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                break;
+            }
+        }
+
+        let exposition = RobocopyMetricsExposition(std::slice::from_ref(&metrics)).to_string();
+        let counts = RobocopySummaryCounts::from_entries(&log.parts);
+        let labels = format!(
+            r#"log="C:\\logs\\\"nightly\".log",source="{}",dest="{}""#,
+            log.header.source.display().to_string().replace('\\', r"\\"),
+            log.header.dest.display().to_string().replace('\\', r"\\"),
+        );
+        assert!(
+            exposition.contains(&format!("robocopy_job_finished{{{labels}}} 1")),
+            "{exposition}"
+        );
+        assert_eq!(
+            sample(&exposition, "robocopy_files_total{"),
+            counts.files.values().iter().skip(1).sum::<u64>()
+        );
+        assert_eq!(
+            sample(&exposition, "robocopy_failed_files_total{"),
+            counts.files.failed
+        );
+        let errors = log
+            .parts
+            .iter()
+            .filter(|entry| entry.error_code().is_some())
+            .count() as u64;
+        assert!(errors > 0);
+        assert_eq!(sample(&exposition, "robocopy_errors_total{"), errors);
+        assert_eq!(
+            exposition
+                .matches("# TYPE robocopy_files_total counter")
+                .count(),
+            1
+        );
+        Ok(())
+    }
+}
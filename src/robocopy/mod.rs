@@ -1,7 +1,27 @@
+pub mod robocopy_command;
+pub mod robocopy_error_report;
+pub mod robocopy_exit_code;
 pub mod robocopy_file_pattern;
 pub mod robocopy_header;
+pub mod robocopy_job_status;
 pub mod robocopy_log;
+pub mod robocopy_log_arbitrary;
+pub mod robocopy_log_encoding;
 pub mod robocopy_log_entry;
+pub mod robocopy_log_generator;
 pub mod robocopy_log_parser;
+pub mod robocopy_log_stream;
+pub mod robocopy_log_writer;
 pub mod robocopy_options;
+pub mod robocopy_path_resolver;
+pub mod robocopy_path_tree;
+pub mod robocopy_progress;
+pub mod robocopy_query;
+pub mod robocopy_run_diff;
+pub mod robocopy_runner;
+pub mod robocopy_size;
 pub mod robocopy_start_datetime;
+pub mod robocopy_summary;
+pub mod robocopy_summary_tally;
+pub mod robocopy_verify;
+pub mod robocopy_win32_error;
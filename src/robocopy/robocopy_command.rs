@@ -0,0 +1,379 @@
+use crate::cli::to_args::Invocable;
+use crate::cli::to_args::ToArgs;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use std::ffi::OsString;
+use std::mem::discriminant;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Pairs of switches robocopy would either reject or quietly let one win.
+const CONFLICTS: &[(RobocopySwitch, RobocopySwitch, &str)] = &[
+    (
+        RobocopySwitch::Mirror,
+        RobocopySwitch::MoveFiles,
+        "mirroring while moving deletes the source and purges the destination",
+    ),
+    (
+        RobocopySwitch::Mirror,
+        RobocopySwitch::Move,
+        "mirroring while moving deletes the source and purges the destination",
+    ),
+    (
+        RobocopySwitch::MoveFiles,
+        RobocopySwitch::Move,
+        "/MOVE already moves files",
+    ),
+    (
+        RobocopySwitch::Restartable,
+        RobocopySwitch::Backup,
+        "use /ZB for restartable mode with a backup fallback",
+    ),
+    (
+        RobocopySwitch::Restartable,
+        RobocopySwitch::RestartableBackup,
+        "/ZB already copies in restartable mode",
+    ),
+    (
+        RobocopySwitch::Backup,
+        RobocopySwitch::RestartableBackup,
+        "/ZB already falls back to backup mode",
+    ),
+    (
+        RobocopySwitch::Archive,
+        RobocopySwitch::ArchiveReset,
+        "/M already selects files with the archive attribute",
+    ),
+    (
+        RobocopySwitch::NoCopy,
+        RobocopySwitch::Security,
+        "/NOCOPY copies no file info",
+    ),
+    (
+        RobocopySwitch::NoCopy,
+        RobocopySwitch::CopyAll,
+        "/NOCOPY copies no file info",
+    ),
+    (
+        RobocopySwitch::Create,
+        RobocopySwitch::MoveFiles,
+        "/CREATE writes zero-length files, so moving would lose the data",
+    ),
+    (
+        RobocopySwitch::Create,
+        RobocopySwitch::Move,
+        "/CREATE writes zero-length files, so moving would lose the data",
+    ),
+];
+
+/// A robocopy command line: source, destination, file patterns and switches.
+///
+/// Renders to the arguments robocopy expects, so it can be run directly or
+/// through anything that takes an [`Invocable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyCommand {
+    executable: PathBuf,
+    source: PathBuf,
+    dest: PathBuf,
+    files: Vec<String>,
+    switches: Vec<RobocopySwitch>,
+}
+
+impl RobocopyCommand {
+    /// Copy `source` to `dest` with robocopy's defaults: every file, top level only.
+    pub fn new(source: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        Self {
+            executable: PathBuf::from("robocopy"),
+            source: source.into(),
+            dest: dest.into(),
+            files: Vec::new(),
+            switches: Vec::new(),
+        }
+    }
+
+    /// The command that produced a log's header, to run the same job again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header's options cannot be parsed or conflict.
+    pub fn from_header(header: &RobocopyHeader) -> eyre::Result<Self> {
+        let mut files: Vec<String> = header
+            .options
+            .file_patterns()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if files.is_empty() {
+            files = header
+                .files
+                .to_string()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+        }
+        // Robocopy lists `*.*` when no patterns were given
+        if files == ["*.*"] {
+            files.clear();
+        }
+        let command = Self::new(&header.source, &header.dest)
+            .with_files(files)
+            .with_switches(header.options.switches()?);
+        command.validate()?;
+        Ok(command)
+    }
+
+    /// Parse robocopy's own command line: source, destination, file patterns
+    /// and then switches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source or destination is missing, a switch
+    /// cannot be parsed, or switches conflict.
+    pub fn from_args(args: &[String]) -> eyre::Result<Self> {
+        let [source, dest, rest @ ..] = args else {
+            eyre::bail!("Expected robocopy arguments starting with a source and a destination");
+        };
+        let files = rest.iter().take_while(|arg| !arg.starts_with('/'));
+        let switches = RobocopySwitch::parse_all(
+            rest.iter()
+                .skip_while(|arg| !arg.starts_with('/'))
+                .map(String::as_str),
+        )?;
+        let command = Self::new(source, dest)
+            .with_files(files)
+            .with_switches(switches);
+        command.validate()?;
+        Ok(command)
+    }
+
+    /// Run a robocopy-compatible executable instead of `robocopy` from the `PATH`.
+    #[must_use]
+    pub fn with_executable(mut self, executable: impl Into<PathBuf>) -> Self {
+        self.executable = executable.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_file(mut self, pattern: impl Into<String>) -> Self {
+        self.files.push(pattern.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_files(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.files.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub fn with_switch(mut self, switch: RobocopySwitch) -> Self {
+        self.switches.push(switch);
+        self
+    }
+
+    #[must_use]
+    pub fn with_switches(mut self, switches: impl IntoIterator<Item = RobocopySwitch>) -> Self {
+        self.switches.extend(switches);
+        self
+    }
+
+    #[must_use]
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    #[must_use]
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    #[must_use]
+    pub fn dest(&self) -> &Path {
+        &self.dest
+    }
+
+    #[must_use]
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    #[must_use]
+    pub fn switches(&self) -> &[RobocopySwitch] {
+        &self.switches
+    }
+
+    /// Whether the command already has `switch`, ignoring its value.
+    #[must_use]
+    pub fn has_switch(&self, switch: &RobocopySwitch) -> bool {
+        self.switches
+            .iter()
+            .any(|existing| discriminant(existing) == discriminant(switch))
+    }
+
+    /// The file robocopy writes its log to, if a `/LOG` switch is set.
+    #[must_use]
+    pub fn log_file(&self) -> Option<&Path> {
+        self.switches
+            .iter()
+            .find_map(RobocopySwitch::log_file)
+            .map(PathBuf::as_path)
+    }
+
+    /// Check for switches that contradict each other.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first conflict found.
+    pub fn validate(&self) -> eyre::Result<()> {
+        for (left, right, reason) in CONFLICTS {
+            if self.switches.contains(left) && self.switches.contains(right) {
+                eyre::bail!("{left} conflicts with {right}: {reason}");
+            }
+        }
+        // A switch with a value given twice leaves robocopy to pick one
+        for (index, switch) in self.switches.iter().enumerate() {
+            let repeatable = matches!(
+                switch,
+                RobocopySwitch::ExcludeFiles(_)
+                    | RobocopySwitch::ExcludeDirs(_)
+                    | RobocopySwitch::Other(_)
+            );
+            if !repeatable
+                && let Some(other) = self.switches[..index]
+                    .iter()
+                    .find(|other| discriminant(*other) == discriminant(switch) && *other != switch)
+            {
+                eyre::bail!("{other} conflicts with {switch}: the switch is given twice");
+            }
+        }
+        let logs: Vec<_> = self
+            .switches
+            .iter()
+            .filter(|switch| switch.log_file().is_some())
+            .collect();
+        if let [first, second, ..] = logs.as_slice() {
+            eyre::bail!("{first} conflicts with {second}: robocopy writes a single log");
+        }
+        if let Some(RobocopySwitch::Threads(threads)) = self
+            .switches
+            .iter()
+            .find(|switch| matches!(switch, RobocopySwitch::Threads(_)))
+            && !(1..=128).contains(threads)
+        {
+            eyre::bail!("/MT:{threads} is out of range: robocopy takes 1 to 128 threads");
+        }
+        let min_size = self.switches.iter().find_map(|switch| match switch {
+            RobocopySwitch::MinSize(bytes) => Some(*bytes),
+            _ => None,
+        });
+        let max_size = self.switches.iter().find_map(|switch| match switch {
+            RobocopySwitch::MaxSize(bytes) => Some(*bytes),
+            _ => None,
+        });
+        if let (Some(min), Some(max)) = (min_size, max_size)
+            && min > max
+        {
+            eyre::bail!("/MIN:{min} conflicts with /MAX:{max}: no file can match both");
+        }
+        Ok(())
+    }
+
+    /// A process ready to spawn.
+    #[must_use]
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.executable);
+        command.args(self.to_args());
+        command
+    }
+}
+
+impl ToArgs for RobocopyCommand {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![self.source.clone().into(), self.dest.clone().into()];
+        args.extend(self.files.iter().map(OsString::from));
+        args.extend(
+            self.switches
+                .iter()
+                .flat_map(RobocopySwitch::tokens)
+                .map(OsString::from),
+        );
+        args
+    }
+}
+
+impl Invocable for RobocopyCommand {
+    fn path_to_exe(&self) -> PathBuf {
+        self.executable.clone()
+    }
+
+    fn args(&self) -> Vec<OsString> {
+        self.to_args()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuilds_the_command_behind_a_log() -> eyre::Result<()> {
+        let log =
+            crate::robocopy::robocopy_log::RobocopyLog::parse_all(include_str!("sample.txt"))?
+                .remove(0);
+        let command = RobocopyCommand::from_header(&log.header)?;
+        assert_eq!(
+            command.to_args(),
+            [
+                r"J:\",
+                r"K:\",
+                "/TEE",
+                "/S",
+                "/E",
+                "/DCOPY:DA",
+                "/COPY:DAT",
+                "/MT:16",
+                "/R:1000000",
+                "/W:5",
+            ]
+            .map(OsString::from)
+        );
+        assert_eq!(command.path_to_exe(), PathBuf::from("robocopy"));
+
+        let args = ["src", "dst", "*.txt", "/xd", "cache", "/LOG:run.log"].map(String::from);
+        assert_eq!(
+            RobocopyCommand::from_args(&args)?,
+            RobocopyCommand::new("src", "dst")
+                .with_file("*.txt")
+                .with_switch(RobocopySwitch::ExcludeDirs(vec!["cache".to_string()]))
+                .with_switch(RobocopySwitch::Log("run.log".into()))
+        );
+        assert!(RobocopyCommand::from_args(&args[..1]).is_err());
+        let command = RobocopyCommand::new("src", "dst")
+            .with_file("*.txt")
+            .with_switch(RobocopySwitch::ExcludeDirs(vec!["cache".to_string()]))
+            .with_switch(RobocopySwitch::Log("run.log".into()));
+        command.validate()?;
+        assert_eq!(command.log_file(), Some(Path::new("run.log")));
+        assert_eq!(
+            command.args(),
+            ["src", "dst", "*.txt", "/XD", "cache", "/LOG:run.log"].map(OsString::from)
+        );
+
+        let conflicts = [
+            vec![RobocopySwitch::MoveFiles, RobocopySwitch::Mirror],
+            vec![RobocopySwitch::Threads(8), RobocopySwitch::Threads(16)],
+            vec![RobocopySwitch::Threads(200)],
+            vec![RobocopySwitch::MinSize(10), RobocopySwitch::MaxSize(5)],
+            vec![
+                RobocopySwitch::Log("a".into()),
+                RobocopySwitch::AppendLog("b".into()),
+            ],
+        ];
+        for switches in conflicts {
+            let command = RobocopyCommand::new("src", "dst").with_switches(switches.clone());
+            assert!(command.validate().is_err(), "{switches:?} should conflict");
+        }
+        Ok(())
+    }
+}
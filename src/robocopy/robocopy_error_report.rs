@@ -0,0 +1,194 @@
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_win32_error::Win32ErrorInfo;
+use crate::robocopy::robocopy_win32_error::win32_error_info;
+use chrono::DateTime;
+use chrono::Local;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Errors that share a Win32 code, an operation and a parent directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyErrorGroup {
+    pub code: u32,
+    pub operation: String,
+    /// The directory holding the paths that failed, with a trailing backslash.
+    pub parent: String,
+    /// The message robocopy printed for the first occurrence.
+    pub message: String,
+    /// Error lines, one per attempt.
+    pub count: u64,
+    /// Distinct paths that failed.
+    pub paths: u64,
+    pub first: DateTime<Local>,
+    pub last: DateTime<Local>,
+    /// `Waiting ... Retrying...` lines after these errors.
+    pub retries: u64,
+    /// Time spent waiting between those retries.
+    pub retry_wait: Duration,
+    /// Times robocopy hit its retry limit and gave up.
+    pub gave_up: u64,
+}
+
+impl RobocopyErrorGroup {
+    /// Explanation and suggested fix, for codes robocopy commonly reports.
+    #[must_use]
+    pub fn info(&self) -> Option<&'static Win32ErrorInfo> {
+        win32_error_info(self.code)
+    }
+}
+
+/// Group the errors in a log, most frequent first.
+///
+/// Retry lines and retry-limit lines count towards the error they follow.
+pub fn group_errors<'a>(
+    entries: impl IntoIterator<Item = &'a RobocopyLogEntry>,
+) -> Vec<RobocopyErrorGroup> {
+    let mut groups: Vec<RobocopyErrorGroup> = Vec::new();
+    let mut index: HashMap<(u32, String, String), usize> = HashMap::new();
+    let mut paths: HashMap<usize, HashSet<String>> = HashMap::new();
+    let mut last_group: Option<usize> = None;
+    for entry in entries {
+        let (when, code, operation, path, message) = match entry {
+            RobocopyLogEntry::Error {
+                when,
+                code,
+                operation,
+                path,
+                message,
+            } => (*when, *code, operation.as_str(), path, message.as_str()),
+            RobocopyLogEntry::AccessDeniedError { when, path } => (
+                *when,
+                entry.error_code().unwrap_or_default(),
+                ACCESS_DENIED_OPERATION,
+                path,
+                win32_error_info(5).map_or("", |info| info.message),
+            ),
+            RobocopyLogEntry::Retry { wait } => {
+                if let Some(group) = last_group {
+                    let group = &mut groups[group];
+                    group.retries += 1;
+                    group.retry_wait += *wait;
+                }
+                continue;
+            }
+            RobocopyLogEntry::RetryLimitExceeded => {
+                if let Some(group) = last_group.take() {
+                    groups[group].gave_up += 1;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let path = path.to_string_lossy();
+        let key = (code, operation.to_string(), parent_directory(&path));
+        let group = *index
+            .entry(key)
+            .or_insert_with_key(|(code, operation, parent)| {
+                groups.push(RobocopyErrorGroup {
+                    code: *code,
+                    operation: operation.clone(),
+                    parent: parent.clone(),
+                    message: message.to_string(),
+                    count: 0,
+                    paths: 0,
+                    first: when,
+                    last: when,
+                    retries: 0,
+                    retry_wait: Duration::ZERO,
+                    gave_up: 0,
+                });
+                groups.len() - 1
+            });
+        let entry = &mut groups[group];
+        entry.count += 1;
+        entry.first = entry.first.min(when);
+        entry.last = entry.last.max(when);
+        if paths.entry(group).or_default().insert(path.into_owned()) {
+            entry.paths += 1;
+        }
+        last_group = Some(group);
+    }
+    // Stable, so ties keep the order they first appeared in
+    groups.sort_by_key(|group| std::cmp::Reverse(group.count));
+    groups
+}
+
+/// The directory containing a Windows path, with a trailing backslash.
+fn parent_directory(path: &str) -> String {
+    let trimmed = path.trim_end_matches('\\');
+    match trimmed.rsplit_once('\\') {
+        Some((parent, _)) => format!("{parent}\\"),
+        // A drive root or a bare name
+        None if path.ends_with('\\') => path.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+
+    #[test]
+    fn finds_parent_directories() {
+        assert_eq!(parent_directory(r"J:\$RECYCLE.BIN\"), r"J:\");
+        assert_eq!(parent_directory(r"J:\a\b.txt"), r"J:\a\");
+        assert_eq!(parent_directory(r"J:\"), r"J:\");
+        assert_eq!(parent_directory("b.txt"), "");
+    }
+
+    #[test]
+    fn groups_sample_access_denied_errors() -> eyre::Result<()> {
+        let logs = RobocopyLog::parse_all(include_str!("sample.txt"))?;
+        let groups = group_errors(&logs[0].parts);
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.code, 5);
+        assert_eq!(group.operation, "Copying Directory");
+        assert_eq!(group.parent, r"J:\");
+        assert_eq!((group.count, group.paths), (2, 2));
+        assert!(group.first <= group.last);
+        assert!(group.info().is_some_and(|info| !info.fix.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn attributes_retries_to_the_error_before_them() {
+        let config = RobocopyLogGeneratorConfig {
+            files: 150,
+            error_percent: 30,
+            retries: 2,
+            ..Default::default()
+        };
+        let log = RobocopyLogGenerator::new(config).generate_log();
+        let groups = group_errors(&log.parts);
+        let retries = log
+            .parts
+            .iter()
+            .filter(|entry| matches!(entry, RobocopyLogEntry::Retry { .. }))
+            .count();
+        assert!(retries > 0);
+        assert_eq!(
+            groups.iter().map(|g| g.retries).sum::<u64>(),
+            retries as u64
+        );
+        let counts = RobocopySummaryCounts::from_entries(&log.parts);
+        assert_eq!(
+            groups.iter().map(|g| g.gave_up).sum::<u64>(),
+            counts.files.failed + counts.dirs.failed - access_denied(&log.parts)
+        );
+        assert!(groups.windows(2).all(|pair| pair[0].count >= pair[1].count));
+    }
+
+    fn access_denied(entries: &[RobocopyLogEntry]) -> u64 {
+        entries
+            .iter()
+            .filter(|entry| matches!(entry, RobocopyLogEntry::AccessDeniedError { .. }))
+            .count() as u64
+    }
+}
@@ -0,0 +1,220 @@
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use arbitrary::Arbitrary;
+use clap::ValueEnum;
+use std::fmt::Display;
+
+/// The exit code robocopy returns: a bitmask of what happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct RobocopyExitCode(pub u32);
+
+/// Which exit codes count as success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, ValueEnum, Arbitrary)]
+pub enum RobocopyExitPolicy {
+    /// Nothing failed: codes below 8, the usual convention for scripts
+    #[default]
+    NoFailures,
+    /// Nothing failed and no mismatches: codes below 4
+    NoMismatches,
+    /// Only files copied or nothing to do: codes 0 and 1
+    Clean,
+}
+
+impl RobocopyExitCode {
+    /// One or more files were copied.
+    pub const COPIED: u32 = 1;
+    /// Extra files or directories were found in the destination.
+    pub const EXTRAS: u32 = 2;
+    /// Mismatched files or directories were found.
+    pub const MISMATCHES: u32 = 4;
+    /// Some files or directories could not be copied.
+    pub const FAILED: u32 = 8;
+    /// A fatal error stopped the job, e.g. bad arguments or an unreachable source.
+    pub const FATAL: u32 = 16;
+
+    const FLAGS: [(u32, &'static str); 5] = [
+        (Self::COPIED, "One or more files were copied successfully."),
+        (
+            Self::EXTRAS,
+            "Extra files or directories were detected in the destination.",
+        ),
+        (
+            Self::MISMATCHES,
+            "Mismatched files or directories were detected; a file and a directory share a name.",
+        ),
+        (
+            Self::FAILED,
+            "Some files or directories could not be copied and the retry limit was exceeded.",
+        ),
+        (
+            Self::FATAL,
+            "Robocopy did not copy anything: usage error, insufficient access to the source or destination, or a fatal error.",
+        ),
+    ];
+
+    #[must_use]
+    pub fn copied(self) -> bool {
+        self.0 & Self::COPIED != 0
+    }
+
+    #[must_use]
+    pub fn extras(self) -> bool {
+        self.0 & Self::EXTRAS != 0
+    }
+
+    #[must_use]
+    pub fn mismatches(self) -> bool {
+        self.0 & Self::MISMATCHES != 0
+    }
+
+    #[must_use]
+    pub fn failed(self) -> bool {
+        self.0 & Self::FAILED != 0
+    }
+
+    #[must_use]
+    pub fn fatal(self) -> bool {
+        self.0 & Self::FATAL != 0
+    }
+
+    /// Whether robocopy can return this code; anything above 31 came from
+    /// somewhere else, such as a wrapper or the process being killed.
+    #[must_use]
+    pub fn is_robocopy_code(self) -> bool {
+        self.0 < 32
+    }
+
+    /// The names of the flags that are set, e.g. `["copied", "extras"]`.
+    #[must_use]
+    pub fn flag_names(self) -> Vec<&'static str> {
+        [
+            (self.copied(), "copied"),
+            (self.extras(), "extras"),
+            (self.mismatches(), "mismatches"),
+            (self.failed(), "failed"),
+            (self.fatal(), "fatal"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+
+    /// A sentence per flag that is set, in the words of robocopy's documentation.
+    #[must_use]
+    pub fn descriptions(self) -> Vec<&'static str> {
+        if self.0 == 0 {
+            return vec!["No files were copied; the source and destination are already in sync."];
+        }
+        let mut descriptions: Vec<_> = Self::FLAGS
+            .iter()
+            .filter(|(flag, _)| self.0 & flag != 0)
+            .map(|(_, description)| *description)
+            .collect();
+        if !self.is_robocopy_code() {
+            descriptions.push("Robocopy never returns codes above 31.");
+        }
+        descriptions
+    }
+
+    #[must_use]
+    pub fn is_success(self, policy: RobocopyExitPolicy) -> bool {
+        match policy {
+            RobocopyExitPolicy::NoFailures => self.0 < Self::FAILED,
+            RobocopyExitPolicy::NoMismatches => self.0 < Self::MISMATCHES,
+            RobocopyExitPolicy::Clean => self.0 <= Self::COPIED,
+        }
+    }
+
+    /// The code robocopy returns for a job that ended with this summary.
+    ///
+    /// A summary is only written when the job ran, so the fatal flag is never set.
+    #[must_use]
+    pub fn from_summary(summary: &RobocopySummary) -> Self {
+        Self::from_rows(&summary.dirs, &summary.files)
+    }
+
+    /// The code implied by a tally of the entries, for logs written with
+    /// `/NJS` or cut short.
+    #[must_use]
+    pub fn from_counts(counts: &RobocopySummaryCounts) -> Self {
+        Self::from_rows(&counts.dirs, &counts.files)
+    }
+
+    fn from_rows(dirs: &RobocopySummaryRow<u64>, files: &RobocopySummaryRow<u64>) -> Self {
+        let mut code = 0;
+        if files.copied > 0 {
+            code |= Self::COPIED;
+        }
+        if files.extras > 0 || dirs.extras > 0 {
+            code |= Self::EXTRAS;
+        }
+        if files.mismatch > 0 || dirs.mismatch > 0 {
+            code |= Self::MISMATCHES;
+        }
+        if files.failed > 0 || dirs.failed > 0 {
+            code |= Self::FAILED;
+        }
+        Self(code)
+    }
+}
+
+impl From<u32> for RobocopyExitCode {
+    fn from(code: u32) -> Self {
+        Self(code)
+    }
+}
+
+impl Display for RobocopyExitCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self.flag_names();
+        if names.is_empty() {
+            write!(f, "{} (no change)", self.0)
+        } else {
+            write!(f, "{} ({})", self.0, names.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+
+    #[test]
+    fn decodes_flags_and_derives_from_summaries() -> eyre::Result<()> {
+        let code = RobocopyExitCode(11);
+        assert!(code.copied() && code.extras() && code.failed());
+        assert!(!code.mismatches() && !code.fatal());
+        assert_eq!(code.to_string(), "11 (copied, extras, failed)");
+        assert_eq!(code.descriptions().len(), 3);
+        assert!(!code.is_success(RobocopyExitPolicy::NoFailures));
+        assert!(RobocopyExitCode(3).is_success(RobocopyExitPolicy::NoFailures));
+        assert!(!RobocopyExitCode(3).is_success(RobocopyExitPolicy::Clean));
+        assert!(RobocopyExitCode(0).is_success(RobocopyExitPolicy::Clean));
+        assert!(!RobocopyExitCode(259).is_robocopy_code());
+
+        let config = RobocopyLogGeneratorConfig {
+            files: 60,
+            error_percent: 20,
+            ..Default::default()
+        };
+        let text = RobocopyLogGenerator::new(config).generate_log().to_string();
+        let log = RobocopyLog::parse_all(&text)?.remove(0);
+        let summary = log.summary.as_ref().expect("generated logs have a summary");
+        let expected = RobocopyExitCode::from_summary(summary);
+        assert_eq!(expected.copied(), summary.files.copied > 0);
+        assert_eq!(
+            expected.failed(),
+            summary.files.failed + summary.dirs.failed > 0
+        );
+        assert!(!expected.fatal());
+        assert_eq!(
+            RobocopyExitCode::from_counts(&RobocopySummaryCounts::from_entries(&log.parts)),
+            expected
+        );
+        Ok(())
+    }
+}
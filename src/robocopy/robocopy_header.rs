@@ -24,7 +24,7 @@ use std::str::FromStr;
 
 ------------------------------------------------------------------------------
 */
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct RobocopyHeader {
     pub started: RobocopyStartDateTime,
     pub source: PathBuf,
@@ -67,6 +67,10 @@ impl FromStr for RobocopyHeader {
                 let key = key_part.trim();
                 // skip ':'
                 let value = value_part[1..].trim();
+                #[allow(
+                    clippy::collapsible_match,
+                    reason = "only the first occurrence of each field counts"
+                )]
                 match key.to_ascii_lowercase().as_str() {
                     "started" => {
                         if started.is_none() {
@@ -125,6 +129,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(not(windows), ignore = "`J:\\` only equals `J:/` as a Windows path")]
     fn parse_header_from_sample() -> eyre::Result<()> {
         // Load full sample log
         let content = include_str!("sample.txt");
@@ -164,4 +169,18 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn parse_header_keeps_paths_as_written() -> eyre::Result<()> {
+        let content = include_str!("sample.txt");
+        let end = content.find("\n\r\n\t    ").unwrap_or(content.len());
+        let header: RobocopyHeader = content[..end].parse()?;
+        assert_eq!(header.source, PathBuf::from(r"J:\"));
+        assert_eq!(header.dest, PathBuf::from(r"K:\"));
+        assert_eq!(
+            header.options.to_string(),
+            "*.* /TEE /S /E /DCOPY:DA /COPY:DAT /MT:16 /R:1000000 /W:5"
+        );
+        Ok(())
+    }
 }
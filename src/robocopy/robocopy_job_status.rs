@@ -0,0 +1,203 @@
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use std::fmt::Display;
+use std::time::Duration;
+
+/// Where a job is in its lifecycle, as far as its log can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RobocopyJobState {
+    /// Output is arriving.
+    Running,
+    /// Robocopy logged an error and is sitting out its `/W` wait.
+    WaitingOnRetry,
+    /// No output for longer than expected; robocopy may be stuck on a slow file.
+    Stalled,
+    /// The job summary has been logged.
+    Finished,
+    /// The log stopped growing long ago without a summary, so robocopy was
+    /// most likely killed.
+    Abandoned,
+}
+
+impl RobocopyJobState {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            RobocopyJobState::Running => "running",
+            RobocopyJobState::WaitingOnRetry => "waiting-on-retry",
+            RobocopyJobState::Stalled => "stalled",
+            RobocopyJobState::Finished => "finished",
+            RobocopyJobState::Abandoned => "abandoned",
+        }
+    }
+
+    /// The exit code of the `status` subcommand: 0 once finished, and codes
+    /// from 3 up otherwise so they do not collide with errors (1) or usage
+    /// mistakes (2).
+    #[must_use]
+    pub fn exit_code(self) -> u8 {
+        match self {
+            RobocopyJobState::Finished => 0,
+            RobocopyJobState::Running => 3,
+            RobocopyJobState::WaitingOnRetry => 4,
+            RobocopyJobState::Stalled => 5,
+            RobocopyJobState::Abandoned => 6,
+        }
+    }
+}
+
+impl Display for RobocopyJobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How long a job may go quiet before it counts as stalled or abandoned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RobocopyJobThresholds {
+    pub stall_after: Duration,
+    pub abandon_after: Duration,
+}
+
+impl Default for RobocopyJobThresholds {
+    fn default() -> Self {
+        Self {
+            stall_after: Duration::from_mins(5),
+            abandon_after: Duration::from_hours(1),
+        }
+    }
+}
+
+/// Follows the last job in a log closely enough to tell its
+/// [`RobocopyJobState`] once told how long the log has been quiet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobocopyJobStatus {
+    started: bool,
+    finished: bool,
+    // The wait robocopy announced, while no other output followed it
+    retry_wait: Option<Duration>,
+    retries: u64,
+}
+
+impl RobocopyJobStatus {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle anything the parser produced; a header starts over for a new job.
+    pub fn push_advance(&mut self, advance: &RobocopyParseAdvance) {
+        match advance {
+            RobocopyParseAdvance::NeedMoreData => {}
+            RobocopyParseAdvance::Header(_) => {
+                *self = Self {
+                    started: true,
+                    ..Self::default()
+                };
+            }
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry),
+            RobocopyParseAdvance::Summary(_) => {
+                self.finished = true;
+                self.retry_wait = None;
+            }
+        }
+    }
+
+    pub fn push(&mut self, entry: &RobocopyLogEntry) {
+        if let RobocopyLogEntry::Retry { wait } = entry {
+            self.retry_wait = Some(*wait);
+            self.retries += 1;
+        } else {
+            self.retry_wait = None;
+        }
+    }
+
+    /// The state of the job when the log has had no new output for `idle`.
+    ///
+    /// A pending retry is not idle time: robocopy is expected to be quiet
+    /// for the wait it announced.
+    #[must_use]
+    pub fn state(&self, idle: Duration, thresholds: &RobocopyJobThresholds) -> RobocopyJobState {
+        if self.finished {
+            return RobocopyJobState::Finished;
+        }
+        let wait = self.retry_wait.unwrap_or_default();
+        if self.retry_wait.is_some() && idle < wait + thresholds.stall_after {
+            return RobocopyJobState::WaitingOnRetry;
+        }
+        let idle = idle.saturating_sub(wait);
+        if idle >= thresholds.abandon_after {
+            RobocopyJobState::Abandoned
+        } else if idle >= thresholds.stall_after {
+            RobocopyJobState::Stalled
+        } else {
+            RobocopyJobState::Running
+        }
+    }
+
+    /// Whether a header has been seen.
+    #[must_use]
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    /// Retries announced in the current job.
+    #[must_use]
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+
+    #[test]
+    fn state_follows_output_and_idle_time() {
+        let log = RobocopyLogGenerator::new(RobocopyLogGeneratorConfig::default()).generate_log();
+        let thresholds = RobocopyJobThresholds::default();
+        let minutes = Duration::from_mins;
+        let mut status = RobocopyJobStatus::new();
+        status.push_advance(&RobocopyParseAdvance::Header(log.header.clone()));
+        assert!(status.started());
+        assert_eq!(
+            status.state(minutes(1), &thresholds),
+            RobocopyJobState::Running
+        );
+        assert_eq!(
+            status.state(minutes(6), &thresholds),
+            RobocopyJobState::Stalled
+        );
+        assert_eq!(
+            status.state(minutes(90), &thresholds),
+            RobocopyJobState::Abandoned
+        );
+
+        // A 30 minute wait is not a stall, until well after it should have ended
+        status.push(&RobocopyLogEntry::Retry { wait: minutes(30) });
+        assert_eq!(
+            status.state(minutes(20), &thresholds),
+            RobocopyJobState::WaitingOnRetry
+        );
+        assert_eq!(
+            status.state(minutes(40), &thresholds),
+            RobocopyJobState::Stalled
+        );
+        status.push(&log.parts[0]);
+        assert_eq!(
+            status.state(minutes(1), &thresholds),
+            RobocopyJobState::Running
+        );
+        assert_eq!(status.retries(), 1);
+
+        let summary = log.summary.clone().expect("generated logs have a summary");
+        status.push_advance(&RobocopyParseAdvance::Summary(summary));
+        assert_eq!(
+            status.state(minutes(600), &thresholds),
+            RobocopyJobState::Finished
+        );
+        assert_eq!(RobocopyJobState::Finished.exit_code(), 0);
+    }
+}
@@ -1,8 +1,104 @@
 use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_encoding::RobocopyLogEncoding;
 use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use eyre::WrapErr;
+use std::fmt::Display;
+use std::path::Path;
 
-#[derive(Debug)]
+/// One robocopy job: its header, entries and, if it finished, its summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RobocopyLog {
     pub header: RobocopyHeader,
     pub parts: Vec<RobocopyLogEntry>,
+    pub summary: Option<RobocopySummary>,
+}
+
+impl RobocopyLog {
+    #[must_use]
+    pub fn new(header: RobocopyHeader) -> Self {
+        Self {
+            header,
+            parts: Vec::new(),
+            summary: None,
+        }
+    }
+
+    /// Record a parsed entry.
+    ///
+    /// The parser emits a file entry again for every progress tick; those
+    /// updates replace the previous entry for that file instead of being
+    /// appended, so `parts` holds one entry per line robocopy wrote.
+    pub fn push(&mut self, entry: RobocopyLogEntry) {
+        if let Some(last) = self.parts.last_mut()
+            && is_progress_update(last, &entry)
+        {
+            *last = entry;
+            return;
+        }
+        self.parts.push(entry);
+    }
+
+    /// Parse every job in `text`; a log written with `/LOG+` can hold several.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parser rejects the text.
+    pub fn parse_all(text: &str) -> eyre::Result<Vec<RobocopyLog>> {
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(text);
+        let mut logs: Vec<RobocopyLog> = Vec::new();
+        loop {
+            match parser.advance()? {
+                RobocopyParseAdvance::NeedMoreData => break,
+                RobocopyParseAdvance::Header(header) => logs.push(RobocopyLog::new(header)),
+                RobocopyParseAdvance::LogEntry(entry) => {
+                    if let Some(log) = logs.last_mut() {
+                        log.push(entry);
+                    }
+                }
+                RobocopyParseAdvance::Summary(summary) => {
+                    if let Some(log) = logs.last_mut() {
+                        log.summary = Some(summary);
+                    }
+                }
+            }
+        }
+        Ok(logs)
+    }
+
+    /// Read every job in a log file, detecting UTF-8 or UTF-16 (`/UNILOG`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or the parser rejects it.
+    pub fn read_all(path: &Path) -> eyre::Result<Vec<RobocopyLog>> {
+        let bytes =
+            std::fs::read(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        RobocopyLog::parse_all(&RobocopyLogEncoding::decode(&bytes))
+            .wrap_err_with(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+fn is_progress_update(previous: &RobocopyLogEntry, next: &RobocopyLogEntry) -> bool {
+    match (previous.percentages(), next.percentages()) {
+        (Some(prev_pct), Some(next_pct)) => {
+            previous.file_class() == next.file_class()
+                && previous.path() == next.path()
+                && next_pct.len() > prev_pct.len()
+                && next_pct.starts_with(prev_pct)
+        }
+        _ => false,
+    }
+}
+
+impl Display for RobocopyLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut writer = RobocopyLogWriter::new(Vec::new());
+        writer.write_log(self).map_err(|_| std::fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&writer.into_inner()))
+    }
 }
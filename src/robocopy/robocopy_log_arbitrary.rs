@@ -0,0 +1,270 @@
+//! `Arbitrary` implementations producing logs robocopy could have written.
+//!
+//! Every generated value renders to text that the parser reads back into an
+//! equal value, so property tests and fuzz targets can compare the parsed
+//! output against the original document.
+
+use crate::robocopy::robocopy_file_pattern::RobocopyFilePattern;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_CODE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_options::RobocopyOptions;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_size::parse_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySpeed;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use crate::robocopy::robocopy_summary::RobocopySummaryTimes;
+use crate::robocopy::robocopy_win32_error::KNOWN_WIN32_ERRORS;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use std::path::PathBuf;
+use std::time::Duration;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+const PATH_CHARS: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'B', 'Z', '0', '1', '9', '_', '-', '.', '$', ' ', 'é', '日',
+];
+const SWITCHES: &[&str] = &[
+    "/S",
+    "/E",
+    "/MIR",
+    "/MOV",
+    "/PURGE",
+    "/Z",
+    "/B",
+    "/MT:16",
+    "/R:3",
+    "/W:5",
+    "/NP",
+    "/FP",
+    "/DCOPY:DA",
+    "/COPY:DAT",
+    "/XF *.tmp",
+    "/XD cache",
+];
+const OPERATIONS: &[&str] = &[
+    "Copying File",
+    "Copying Directory",
+    "Deleting Extra File",
+    "Scanning Source Directory",
+    "Creating Destination Directory",
+    "Changing File Attributes",
+];
+/// Largest size generated; summaries print sizes in ten character columns.
+const MAX_SIZE: usize = 1 << 50;
+
+impl<'a> Arbitrary<'a> for RobocopyLog {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let header = RobocopyHeader::arbitrary(u)?;
+        let count = u.int_in_range(0..=40)?;
+        let mut parts = Vec::with_capacity(count);
+        for index in 0..count {
+            parts.push(arbitrary_entry(u, index)?);
+        }
+        let summary = if bool::arbitrary(u)? {
+            Some(RobocopySummary::arbitrary(u)?)
+        } else {
+            None
+        };
+        Ok(RobocopyLog {
+            header,
+            parts,
+            summary,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for RobocopyHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut options = vec!["*.*"];
+        for _ in 0..u.int_in_range(0..=6)? {
+            options.push(u.choose(SWITCHES)?);
+        }
+        Ok(RobocopyHeader {
+            started: arbitrary_datetime(u)?.into(),
+            source: arbitrary_directory(u)?,
+            dest: arbitrary_directory(u)?,
+            files: RobocopyFilePattern {
+                inner: u.choose(&["*.*", "*.bucket", "*.txt *.log"])?.to_string(),
+            },
+            options: RobocopyOptions {
+                inner: options.join(" "),
+            },
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for RobocopySummary {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let count = |u: &mut Unstructured<'a>| u.int_in_range(0..=999_999_999_u64);
+        let size = |u: &mut Unstructured<'a>| arbitrary_size(u);
+        let seconds = |u: &mut Unstructured<'a>| {
+            Ok::<_, arbitrary::Error>(Duration::from_secs(u.int_in_range(0..=3_599_999)?))
+        };
+        let speed = if bool::arbitrary(u)? {
+            Some(RobocopySpeed {
+                bytes_per_second: u.int_in_range(0..=999_999_999_999)?,
+                megabytes_per_minute_milli: u.int_in_range(0..=999_999_999_999)?,
+            })
+        } else {
+            None
+        };
+        Ok(RobocopySummary {
+            dirs: arbitrary_row(u, count)?,
+            files: arbitrary_row(u, count)?,
+            bytes: arbitrary_row(u, size)?,
+            times: RobocopySummaryTimes {
+                total: seconds(u)?,
+                copied: seconds(u)?,
+                failed: seconds(u)?,
+                extras: seconds(u)?,
+            },
+            speed,
+            ended: arbitrary_datetime(u)?.into(),
+        })
+    }
+}
+
+/// One entry; `index` keeps file paths distinct so consecutive file entries
+/// are never mistaken for progress updates of each other.
+fn arbitrary_entry(u: &mut Unstructured<'_>, index: usize) -> arbitrary::Result<RobocopyLogEntry> {
+    Ok(match u.int_in_range(0..=5)? {
+        0 => {
+            let class = RobocopyFileClass::arbitrary(u)?;
+            let name = format!("{}{index}", arbitrary_segment(u)?);
+            let path = if bool::arbitrary(u)? {
+                PathBuf::from(name)
+            } else {
+                PathBuf::from(format!("{}{name}", arbitrary_directory(u)?.display()))
+            };
+            let mut percentages = Vec::new();
+            for _ in 0..u.int_in_range(0..=5)? {
+                percentages.push(u.int_in_range(0..=99)?);
+            }
+            if bool::arbitrary(u)? {
+                percentages.push(100);
+            }
+            RobocopyLogEntry::file(class, arbitrary_size(u)?, path, percentages)
+        }
+        1 => {
+            let class = u.choose(&[
+                RobocopyDirectoryClass::Existing,
+                RobocopyDirectoryClass::New,
+                RobocopyDirectoryClass::Extra,
+            ])?;
+            let file_count = if *class == RobocopyDirectoryClass::Extra {
+                -1
+            } else {
+                u.int_in_range(0..=99_999)?
+            };
+            RobocopyLogEntry::Directory {
+                class: *class,
+                file_count,
+                path: arbitrary_directory(u)?,
+            }
+        }
+        2 => {
+            let error = u.choose(KNOWN_WIN32_ERRORS)?;
+            let operation = *u.choose(OPERATIONS)?;
+            let when = arbitrary_datetime(u)?;
+            let path = arbitrary_directory(u)?;
+            if error.code == ACCESS_DENIED_CODE
+                && operation == ACCESS_DENIED_OPERATION
+                && error.message == ACCESS_DENIED_MESSAGE
+            {
+                RobocopyLogEntry::AccessDeniedError { when, path }
+            } else {
+                RobocopyLogEntry::Error {
+                    when,
+                    code: error.code,
+                    operation: operation.to_string(),
+                    path,
+                    message: error.message.to_string(),
+                }
+            }
+        }
+        3 => RobocopyLogEntry::AccessDeniedError {
+            when: arbitrary_datetime(u)?,
+            path: arbitrary_directory(u)?,
+        },
+        4 => RobocopyLogEntry::Retry {
+            wait: Duration::from_secs(u.int_in_range(0..=3600)?),
+        },
+        _ => RobocopyLogEntry::RetryLimitExceeded,
+    })
+}
+
+fn arbitrary_row<'a, T: Copy>(
+    u: &mut Unstructured<'a>,
+    mut cell: impl FnMut(&mut Unstructured<'a>) -> arbitrary::Result<T>,
+) -> arbitrary::Result<RobocopySummaryRow<T>> {
+    Ok(RobocopySummaryRow::from_values([
+        cell(u)?,
+        cell(u)?,
+        cell(u)?,
+        cell(u)?,
+        cell(u)?,
+        cell(u)?,
+    ]))
+}
+
+/// A whole-second local time; robocopy never prints fractions.
+fn arbitrary_datetime(u: &mut Unstructured<'_>) -> arbitrary::Result<DateTime<Local>> {
+    // 2000-01-01 to 2100-01-01
+    let secs = u.int_in_range(946_684_800..=4_102_444_800_i64)?;
+    let naive = DateTime::from_timestamp(secs, 0)
+        .ok_or(arbitrary::Error::IncorrectFormat)?
+        .naive_utc();
+    // Times skipped by a daylight saving change do not exist locally
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(naive + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .ok_or(arbitrary::Error::IncorrectFormat)
+}
+
+/// A size as robocopy prints it, i.e. already rounded to one decimal place.
+fn arbitrary_size(u: &mut Unstructured<'_>) -> arbitrary::Result<Information> {
+    let bits = u.int_in_range(0..=MAX_SIZE.trailing_zeros())?;
+    let size = Information::new::<byte>(u.int_in_range(0..=(1 << bits))?);
+    Ok(parse_robocopy_size(&format_robocopy_size(size)).unwrap_or(size))
+}
+
+/// A path segment without leading or trailing spaces.
+fn arbitrary_segment(u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+    let mut segment = String::new();
+    for _ in 0..u.int_in_range(1..=12)? {
+        segment.push(*u.choose(PATH_CHARS)?);
+    }
+    let segment = segment.trim();
+    Ok(if segment.is_empty() {
+        "x".to_string()
+    } else {
+        segment.to_string()
+    })
+}
+
+/// An absolute Windows directory path with a trailing backslash.
+fn arbitrary_directory(u: &mut Unstructured<'_>) -> arbitrary::Result<PathBuf> {
+    let drive = char::from(u.int_in_range(b'A'..=b'Z')?);
+    let mut path = format!("{drive}:\\");
+    for _ in 0..u.int_in_range(0..=3)? {
+        path.push_str(&arbitrary_segment(u)?);
+        path.push('\\');
+    }
+    Ok(PathBuf::from(path))
+}
@@ -0,0 +1,86 @@
+use arbitrary::Arbitrary;
+use clap::ValueEnum;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+
+/// Text encoding of a robocopy log file.
+///
+/// `/LOG` writes the console code page (UTF-8 on modern systems), `/UNILOG`
+/// writes UTF-16LE with a byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Arbitrary)]
+pub enum RobocopyLogEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+}
+
+impl RobocopyLogEncoding {
+    /// Detect the encoding from the first bytes of a log.
+    ///
+    /// Returns the encoding and the length of the byte order mark to skip.
+    /// Logs without a BOM are recognised as UTF-16LE when the first character
+    /// is ASCII followed by a zero byte, as every robocopy header starts with `-`.
+    #[must_use]
+    pub fn detect(prefix: &[u8]) -> (Self, usize) {
+        if prefix.starts_with(UTF16LE_BOM) {
+            (RobocopyLogEncoding::Utf16Le, UTF16LE_BOM.len())
+        } else if prefix.starts_with(UTF8_BOM) {
+            (RobocopyLogEncoding::Utf8, UTF8_BOM.len())
+        } else if prefix.len() >= 2 && prefix[0] != 0 && prefix[0].is_ascii() && prefix[1] == 0 {
+            (RobocopyLogEncoding::Utf16Le, 0)
+        } else {
+            (RobocopyLogEncoding::Utf8, 0)
+        }
+    }
+
+    /// Encode `text` the way robocopy writes it, including the BOM for UTF-16LE.
+    #[must_use]
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            RobocopyLogEncoding::Utf8 => text.as_bytes().to_vec(),
+            RobocopyLogEncoding::Utf16Le => {
+                let mut bytes = UTF16LE_BOM.to_vec();
+                bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+                bytes
+            }
+        }
+    }
+
+    /// Decode a complete log, skipping any byte order mark.
+    #[must_use]
+    pub fn decode(bytes: &[u8]) -> String {
+        let (encoding, bom_len) = Self::detect(bytes);
+        let bytes = &bytes[bom_len..];
+        match encoding {
+            RobocopyLogEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            RobocopyLogEncoding::Utf16Le => char::decode_utf16(
+                bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]])),
+            )
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_then_decode_roundtrips() {
+        let text = "---\n  Source : C:\\données\\\n";
+        for encoding in [RobocopyLogEncoding::Utf8, RobocopyLogEncoding::Utf16Le] {
+            let bytes = encoding.encode(text);
+            assert_eq!(RobocopyLogEncoding::detect(&bytes).0, encoding);
+            assert_eq!(RobocopyLogEncoding::decode(&bytes), text);
+        }
+        let no_bom: Vec<u8> = "-".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(
+            RobocopyLogEncoding::detect(&no_bom),
+            (RobocopyLogEncoding::Utf16Le, 0)
+        );
+    }
+}
@@ -1,9 +1,16 @@
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use arbitrary::Arbitrary;
 use chrono::DateTime;
 use chrono::Local;
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 use uom::si::usize::Information;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum RobocopyLogEntry {
     AccessDeniedError {
         when: DateTime<Local>,
@@ -14,4 +21,243 @@ pub enum RobocopyLogEntry {
         path: PathBuf,
         percentages: Vec<u8>,
     },
+    /// A file line with any classification other than `New File`.
+    File {
+        class: RobocopyFileClass,
+        size: Information,
+        path: PathBuf,
+        percentages: Vec<u8>,
+    },
+    /// A directory line. `file_count` is the number of files robocopy found in it,
+    /// or `-1` for extra directories that only exist in the destination.
+    Directory {
+        class: RobocopyDirectoryClass,
+        file_count: i64,
+        path: PathBuf,
+    },
+    /// Any error block other than an access-denied directory copy.
+    Error {
+        when: DateTime<Local>,
+        code: u32,
+        operation: String,
+        path: PathBuf,
+        message: String,
+    },
+    /// `Waiting 30 seconds... Retrying...`
+    Retry { wait: Duration },
+    /// `ERROR: RETRY LIMIT EXCEEDED.`
+    RetryLimitExceeded,
+}
+
+impl RobocopyLogEntry {
+    /// Build a file entry, using the dedicated `NewFile` variant when applicable.
+    #[must_use]
+    pub fn file(
+        class: RobocopyFileClass,
+        size: Information,
+        path: PathBuf,
+        percentages: Vec<u8>,
+    ) -> Self {
+        match class {
+            RobocopyFileClass::NewFile => RobocopyLogEntry::NewFile {
+                size,
+                path,
+                percentages,
+            },
+            class => RobocopyLogEntry::File {
+                class,
+                size,
+                path,
+                percentages,
+            },
+        }
+    }
+
+    /// The classification of a file entry.
+    #[must_use]
+    pub fn file_class(&self) -> Option<RobocopyFileClass> {
+        match self {
+            RobocopyLogEntry::NewFile { .. } => Some(RobocopyFileClass::NewFile),
+            RobocopyLogEntry::File { class, .. } => Some(*class),
+            _ => None,
+        }
+    }
+
+    /// The size of a file entry.
+    #[must_use]
+    pub fn file_size(&self) -> Option<Information> {
+        match self {
+            RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                Some(*size)
+            }
+            _ => None,
+        }
+    }
+
+    /// The progress percentages reported so far for a file entry.
+    #[must_use]
+    pub fn percentages(&self) -> Option<&[u8]> {
+        match self {
+            RobocopyLogEntry::NewFile { percentages, .. }
+            | RobocopyLogEntry::File { percentages, .. } => Some(percentages),
+            _ => None,
+        }
+    }
+
+    /// The path as written in the log, for entries that have one.
+    ///
+    /// Without `/FP` robocopy writes bare file names, relative to the last directory line.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            RobocopyLogEntry::AccessDeniedError { path, .. }
+            | RobocopyLogEntry::NewFile { path, .. }
+            | RobocopyLogEntry::File { path, .. }
+            | RobocopyLogEntry::Directory { path, .. }
+            | RobocopyLogEntry::Error { path, .. } => Some(path),
+            RobocopyLogEntry::Retry { .. } | RobocopyLogEntry::RetryLimitExceeded => None,
+        }
+    }
+
+    /// The Win32 error code of an error entry.
+    #[must_use]
+    pub fn error_code(&self) -> Option<u32> {
+        match self {
+            RobocopyLogEntry::AccessDeniedError { .. } => Some(ACCESS_DENIED_CODE),
+            RobocopyLogEntry::Error { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) const ACCESS_DENIED_CODE: u32 = 5;
+pub(crate) const ACCESS_DENIED_OPERATION: &str = "Copying Directory";
+pub(crate) const ACCESS_DENIED_MESSAGE: &str = "Access is denied.";
+
+/// How robocopy classified a file, as shown in the first column of a file line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum, Arbitrary)]
+pub enum RobocopyFileClass {
+    NewFile,
+    Newer,
+    Older,
+    Changed,
+    Tweaked,
+    Same,
+    Modified,
+    Extra,
+    Mismatch,
+    Lonely,
+}
+
+impl RobocopyFileClass {
+    pub const ALL: [RobocopyFileClass; 10] = [
+        RobocopyFileClass::NewFile,
+        RobocopyFileClass::Newer,
+        RobocopyFileClass::Older,
+        RobocopyFileClass::Changed,
+        RobocopyFileClass::Tweaked,
+        RobocopyFileClass::Same,
+        RobocopyFileClass::Modified,
+        RobocopyFileClass::Extra,
+        RobocopyFileClass::Mismatch,
+        RobocopyFileClass::Lonely,
+    ];
+
+    /// The label robocopy writes for this classification.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            RobocopyFileClass::NewFile => "New File",
+            RobocopyFileClass::Newer => "Newer",
+            RobocopyFileClass::Older => "Older",
+            RobocopyFileClass::Changed => "Changed",
+            RobocopyFileClass::Tweaked => "Tweaked",
+            RobocopyFileClass::Same => "same",
+            RobocopyFileClass::Modified => "Modified",
+            RobocopyFileClass::Extra => "*EXTRA File",
+            RobocopyFileClass::Mismatch => "*Mismatch",
+            RobocopyFileClass::Lonely => "lonely",
+        }
+    }
+}
+
+impl RobocopyFileClass {
+    /// The summary column robocopy counts a file with this classification in,
+    /// assuming default switches and that copying did not fail.
+    #[must_use]
+    pub fn summary_column(self) -> RobocopySummaryColumn {
+        match self {
+            RobocopyFileClass::NewFile
+            | RobocopyFileClass::Newer
+            | RobocopyFileClass::Older
+            | RobocopyFileClass::Changed
+            | RobocopyFileClass::Modified => RobocopySummaryColumn::Copied,
+            RobocopyFileClass::Same | RobocopyFileClass::Tweaked | RobocopyFileClass::Lonely => {
+                RobocopySummaryColumn::Skipped
+            }
+            RobocopyFileClass::Mismatch => RobocopySummaryColumn::Mismatch,
+            RobocopyFileClass::Extra => RobocopySummaryColumn::Extras,
+        }
+    }
+}
+
+impl Display for RobocopyFileClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl FromStr for RobocopyFileClass {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        RobocopyFileClass::ALL
+            .into_iter()
+            .find(|class| class.label().eq_ignore_ascii_case(s))
+            .ok_or_else(|| eyre::eyre!("Unknown file classification: '{s}'"))
+    }
+}
+
+/// How robocopy classified a directory, as shown before the file count of a directory line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RobocopyDirectoryClass {
+    /// Present in both source and destination; robocopy leaves the label blank.
+    Existing,
+    New,
+    Extra,
+}
+
+impl RobocopyDirectoryClass {
+    /// The label robocopy writes for this classification.
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            RobocopyDirectoryClass::Existing => "",
+            RobocopyDirectoryClass::New => "New Dir",
+            RobocopyDirectoryClass::Extra => "*EXTRA Dir",
+        }
+    }
+}
+
+impl Display for RobocopyDirectoryClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl FromStr for RobocopyDirectoryClass {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        [
+            RobocopyDirectoryClass::Existing,
+            RobocopyDirectoryClass::New,
+            RobocopyDirectoryClass::Extra,
+        ]
+        .into_iter()
+        .find(|class| class.label().eq_ignore_ascii_case(s))
+        .ok_or_else(|| eyre::eyre!("Unknown directory classification: '{s}'"))
+    }
 }
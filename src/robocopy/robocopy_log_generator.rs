@@ -0,0 +1,662 @@
+use crate::robocopy::robocopy_file_pattern::RobocopyFilePattern;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_encoding::RobocopyLogEncoding;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_writer::LineEnding;
+use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+use crate::robocopy::robocopy_options::RobocopyOptions;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_size::parse_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySpeed;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use crate::robocopy::robocopy_summary::RobocopySummaryTimes;
+use crate::robocopy::robocopy_win32_error::win32_error_info;
+use arbitrary::Arbitrary;
+use chrono::DateTime;
+use chrono::Local;
+use chrono::TimeZone;
+use clap::ValueEnum;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::seq::IndexedRandom;
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+const DIRECTORY_NAMES: &[&str] = &[
+    "Pool", "photos", "docs", "backup", "src", "cache", "projects", "music", "archive", "données",
+];
+const FILE_STEMS: &[&str] = &[
+    "data",
+    "report",
+    "locked db",
+    "IMG_",
+    "notes",
+    "résumé",
+    "chunk",
+];
+const FILE_EXTENSIONS: &[&str] = &[
+    "bucket", "index", "lock", "txt", "jpg", "mp4", "json", "vhdx", "sqlite", "log",
+];
+/// Error codes robocopy hits while copying files, most common first.
+const FILE_ERROR_CODES: &[u32] = &[32, 32, 32, 5, 64, 53, 121, 112, 1392, 2];
+/// Simulated copy throughput range, in bytes per second.
+const COPY_RATE: std::ops::RangeInclusive<usize> = 20 * 1024 * 1024..=200 * 1024 * 1024;
+
+/// The switches a generated job pretends to have been run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Arbitrary)]
+pub enum RobocopyGeneratorOptionSet {
+    /// `/S /E`: copy everything, never touch the destination's extras.
+    #[default]
+    Copy,
+    /// `/MIR`: extra files and directories in the destination are reported.
+    Mirror,
+    /// `/MOV`: move files, deleting them from the source after copying.
+    Move,
+}
+
+/// Knobs for [`RobocopyLogGenerator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyLogGeneratorConfig {
+    /// The same seed and config always produce the same log.
+    pub seed: u64,
+    /// Number of files in the source tree.
+    pub files: usize,
+    /// Maximum directory nesting below the source root.
+    pub max_depth: usize,
+    pub max_file_size: Information,
+    /// Classifications to pick from with equal weight; empty picks a
+    /// realistic mix for the option set.
+    pub classes: Vec<RobocopyFileClass>,
+    /// Chance, in percent, that copying a file fails at least once.
+    pub error_percent: u8,
+    /// `/R:n`, the number of retries after a failure.
+    pub retries: u32,
+    /// `/W:n`, the wait between retries.
+    pub retry_wait: Duration,
+    /// `/MT:n`; output of up to `n` directories is interleaved, so paths are
+    /// written in full (`/FP`).
+    pub threads: Option<u8>,
+    pub option_set: RobocopyGeneratorOptionSet,
+    /// Write progress percentages (omit for `/NP`).
+    pub progress: bool,
+    /// Write the job summary (omit for `/NJS`).
+    pub summary: bool,
+    pub encoding: RobocopyLogEncoding,
+    pub line_ending: LineEnding,
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub started: DateTime<Local>,
+}
+
+impl Default for RobocopyLogGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            files: 100,
+            max_depth: 3,
+            max_file_size: Information::new::<byte>(256 * 1024 * 1024),
+            classes: Vec::new(),
+            error_percent: 2,
+            retries: 2,
+            retry_wait: Duration::from_secs(5),
+            threads: None,
+            option_set: RobocopyGeneratorOptionSet::Copy,
+            progress: true,
+            summary: true,
+            encoding: RobocopyLogEncoding::Utf8,
+            line_ending: LineEnding::Lf,
+            source: PathBuf::from(r"C:\source\"),
+            dest: PathBuf::from(r"D:\dest\"),
+            started: Local
+                .with_ymd_and_hms(2025, 8, 27, 22, 19, 37)
+                .earliest()
+                .expect("the default start time exists in every time zone"),
+        }
+    }
+}
+
+/// Produces synthetic robocopy logs for tests, benchmarks and demos.
+///
+/// The generated log is always one the parser reads back losslessly.
+#[derive(Debug, Clone)]
+pub struct RobocopyLogGenerator {
+    config: RobocopyLogGeneratorConfig,
+}
+
+impl RobocopyLogGenerator {
+    #[must_use]
+    pub fn new(config: RobocopyLogGeneratorConfig) -> Self {
+        Self { config }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &RobocopyLogGeneratorConfig {
+        &self.config
+    }
+
+    /// Generate one job.
+    #[must_use]
+    pub fn generate_log(&self) -> RobocopyLog {
+        GeneratorRun::new(&self.config).run()
+    }
+
+    /// Generate one job rendered with the configured line ending and encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering the log fails.
+    pub fn generate_bytes(&self) -> eyre::Result<Vec<u8>> {
+        let log = self.generate_log();
+        let mut writer =
+            RobocopyLogWriter::new(Vec::new()).with_line_ending(self.config.line_ending);
+        writer.write_log(&log)?;
+        let text = String::from_utf8(writer.into_inner())?;
+        Ok(self.config.encoding.encode(&text))
+    }
+}
+
+struct GeneratedDir {
+    /// Relative to the source root, with a trailing separator; empty for the root.
+    relative: String,
+    depth: usize,
+    class: RobocopyDirectoryClass,
+    files: Vec<GeneratedFile>,
+}
+
+struct GeneratedFile {
+    name: String,
+    class: RobocopyFileClass,
+    size: Information,
+}
+
+/// The state of a single `generate_log` call.
+struct GeneratorRun<'a> {
+    config: &'a RobocopyLogGeneratorConfig,
+    rng: ChaCha8Rng,
+    clock: DateTime<Local>,
+    copy_time: Duration,
+    full_paths: bool,
+    dirs: RobocopySummaryRow<u64>,
+    files: RobocopySummaryRow<u64>,
+    bytes: RobocopySummaryRow<Information>,
+}
+
+impl<'a> GeneratorRun<'a> {
+    fn new(config: &'a RobocopyLogGeneratorConfig) -> Self {
+        Self {
+            config,
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            clock: config.started,
+            copy_time: Duration::ZERO,
+            full_paths: config.threads.is_some(),
+            dirs: RobocopySummaryRow::default(),
+            files: RobocopySummaryRow::default(),
+            bytes: RobocopySummaryRow::default(),
+        }
+    }
+
+    fn run(mut self) -> RobocopyLog {
+        let header = self.header();
+        let (dirs, denied) = self.tree();
+
+        // One queue per directory; each queue item is a group of entries
+        // that robocopy writes without interruption.
+        let mut queues: VecDeque<VecDeque<Vec<RobocopyLogEntry>>> = VecDeque::new();
+        let mut parts: Vec<RobocopyLogEntry> = Vec::new();
+        for relative in denied {
+            self.dirs.add(RobocopySummaryColumn::Failed, 1);
+            parts.push(RobocopyLogEntry::AccessDeniedError {
+                when: self.clock,
+                path: windows_join(&self.config.source, &relative),
+            });
+        }
+        for dir in dirs {
+            queues.push_back(self.dir_groups(dir));
+        }
+
+        let threads = usize::from(self.config.threads.unwrap_or(1).max(1));
+        let mut active: Vec<VecDeque<Vec<RobocopyLogEntry>>> = Vec::new();
+        loop {
+            while active.len() < threads
+                && let Some(queue) = queues.pop_front()
+            {
+                active.push(queue);
+            }
+            if active.is_empty() {
+                break;
+            }
+            let index = self.rng.random_range(0..active.len());
+            match active[index].pop_front() {
+                Some(group) => parts.extend(group),
+                None => {
+                    active.swap_remove(index);
+                }
+            }
+        }
+
+        let summary = self.config.summary.then(|| self.summary());
+        RobocopyLog {
+            header,
+            parts,
+            summary,
+        }
+    }
+
+    fn header(&self) -> RobocopyHeader {
+        let config = self.config;
+        let mut options: Vec<String> = ["*.*", "/S", "/E", "/DCOPY:DA", "/COPY:DAT"]
+            .map(String::from)
+            .to_vec();
+        match config.option_set {
+            RobocopyGeneratorOptionSet::Copy => {}
+            RobocopyGeneratorOptionSet::Mirror => {
+                options.extend(["/PURGE".to_string(), "/MIR".to_string()]);
+            }
+            RobocopyGeneratorOptionSet::Move => options.push("/MOV".to_string()),
+        }
+        if let Some(threads) = config.threads {
+            options.push(format!("/MT:{}", threads.max(1)));
+            options.push("/FP".to_string());
+        }
+        if !config.progress {
+            options.push("/NP".to_string());
+        }
+        if !config.summary {
+            options.push("/NJS".to_string());
+        }
+        options.push(format!("/R:{}", config.retries));
+        options.push(format!("/W:{}", config.retry_wait.as_secs()));
+        RobocopyHeader {
+            started: config.started.into(),
+            source: config.source.clone(),
+            dest: config.dest.clone(),
+            files: RobocopyFilePattern {
+                inner: "*.*".to_string(),
+            },
+            options: RobocopyOptions {
+                inner: options.join(" "),
+            },
+        }
+    }
+
+    /// Build the directory tree and distribute files over it.
+    ///
+    /// Also returns directories that could not be read.
+    fn tree(&mut self) -> (Vec<GeneratedDir>, Vec<String>) {
+        let mut dirs = vec![GeneratedDir {
+            relative: String::new(),
+            depth: 0,
+            class: RobocopyDirectoryClass::Existing,
+            files: Vec::new(),
+        }];
+        let dir_count = if self.config.max_depth == 0 {
+            1
+        } else {
+            1 + self.config.files / 8
+        };
+        for i in 1..dir_count {
+            let parents: Vec<usize> = (0..dirs.len())
+                .filter(|&p| dirs[p].depth < self.config.max_depth)
+                .collect();
+            let parent = *parents.choose(&mut self.rng).unwrap_or(&0);
+            let name = DIRECTORY_NAMES.choose(&mut self.rng).unwrap_or(&"dir");
+            let class = if self.rng.random_bool(0.4) {
+                RobocopyDirectoryClass::New
+            } else {
+                RobocopyDirectoryClass::Existing
+            };
+            dirs.push(GeneratedDir {
+                relative: format!("{}{name}{i}\\", dirs[parent].relative),
+                depth: dirs[parent].depth + 1,
+                class,
+                files: Vec::new(),
+            });
+        }
+        if self.config.option_set == RobocopyGeneratorOptionSet::Mirror {
+            for i in 0..dir_count.div_ceil(10) {
+                dirs.push(GeneratedDir {
+                    relative: format!("stale{i}\\"),
+                    depth: 1,
+                    class: RobocopyDirectoryClass::Extra,
+                    files: Vec::new(),
+                });
+            }
+        }
+
+        let weights = self.class_weights();
+        let total_weight: u32 = weights.iter().map(|(_, weight)| weight).sum();
+        for i in 0..self.config.files {
+            let mut pick = self.rng.random_range(0..total_weight.max(1));
+            let class = weights
+                .iter()
+                .find(|(_, weight)| {
+                    let found = pick < *weight;
+                    pick = pick.saturating_sub(*weight);
+                    found
+                })
+                .map_or(RobocopyFileClass::NewFile, |(class, _)| *class);
+            // Extra directories only hold extra files
+            let in_extra_dir = class == RobocopyFileClass::Extra
+                && dirs.len() > dir_count
+                && self.rng.random_bool(0.3);
+            let candidates: Vec<usize> = (0..dirs.len())
+                .filter(|&d| (dirs[d].class == RobocopyDirectoryClass::Extra) == in_extra_dir)
+                .collect();
+            let dir = *candidates.choose(&mut self.rng).unwrap_or(&0);
+            let stem = FILE_STEMS.choose(&mut self.rng).unwrap_or(&"file");
+            let extension = FILE_EXTENSIONS.choose(&mut self.rng).unwrap_or(&"bin");
+            let size = self.file_size();
+            dirs[dir].files.push(GeneratedFile {
+                name: format!("{stem}{i}.{extension}"),
+                class,
+                size,
+            });
+        }
+
+        let denied_count = dir_count * usize::from(self.config.error_percent.min(100)) / 100;
+        let denied = (0..denied_count)
+            .map(|i| format!("System Volume Information{i}\\"))
+            .collect();
+        (dirs, denied)
+    }
+
+    fn class_weights(&self) -> Vec<(RobocopyFileClass, u32)> {
+        if !self.config.classes.is_empty() {
+            return self
+                .config
+                .classes
+                .iter()
+                .map(|&class| (class, 1))
+                .collect();
+        }
+        let mut weights = vec![
+            (RobocopyFileClass::NewFile, 60),
+            (RobocopyFileClass::Newer, 10),
+            (RobocopyFileClass::Older, 3),
+            (RobocopyFileClass::Changed, 2),
+            (RobocopyFileClass::Modified, 1),
+            (RobocopyFileClass::Tweaked, 1),
+            (RobocopyFileClass::Same, 22),
+            (RobocopyFileClass::Mismatch, 1),
+        ];
+        if self.config.option_set == RobocopyGeneratorOptionSet::Mirror {
+            weights.push((RobocopyFileClass::Extra, 8));
+        }
+        weights
+    }
+
+    /// A size spread evenly over orders of magnitude, rounded the way robocopy prints it.
+    fn file_size(&mut self) -> Information {
+        let max = self.config.max_file_size.get::<byte>();
+        let bits = self.rng.random_range(0..=usize::BITS - max.leading_zeros());
+        let size = self
+            .rng
+            .random_range(0..=(1_usize << bits.min(usize::BITS - 1)))
+            .min(max);
+        let size = Information::new::<byte>(size);
+        parse_robocopy_size(&format_robocopy_size(size)).unwrap_or(size)
+    }
+
+    fn dir_groups(&mut self, dir: GeneratedDir) -> VecDeque<Vec<RobocopyLogEntry>> {
+        let mut groups = VecDeque::new();
+        let root = if dir.class == RobocopyDirectoryClass::Extra {
+            &self.config.dest
+        } else {
+            &self.config.source
+        };
+        let dir_path = windows_join(root, &dir.relative);
+        let file_count = if dir.class == RobocopyDirectoryClass::Extra {
+            -1
+        } else {
+            dir.files
+                .iter()
+                .filter(|file| file.class != RobocopyFileClass::Extra)
+                .count()
+                .try_into()
+                .unwrap_or(i64::MAX)
+        };
+        let column = match dir.class {
+            RobocopyDirectoryClass::Existing => RobocopySummaryColumn::Skipped,
+            RobocopyDirectoryClass::New => RobocopySummaryColumn::Copied,
+            RobocopyDirectoryClass::Extra => RobocopySummaryColumn::Extras,
+        };
+        self.dirs.add(column, 1);
+        groups.push_back(vec![RobocopyLogEntry::Directory {
+            class: dir.class,
+            file_count,
+            path: dir_path.clone(),
+        }]);
+        for file in dir.files {
+            let full_path = if file.class == RobocopyFileClass::Extra {
+                windows_join(&self.config.dest, &format!("{}{}", dir.relative, file.name))
+            } else {
+                windows_join(&dir_path, &file.name)
+            };
+            let path = if self.full_paths {
+                full_path.clone()
+            } else {
+                PathBuf::from(&file.name)
+            };
+            groups.push_back(self.file_group(&file, path, &full_path));
+        }
+        groups
+    }
+
+    fn file_group(
+        &mut self,
+        file: &GeneratedFile,
+        path: PathBuf,
+        full_path: &Path,
+    ) -> Vec<RobocopyLogEntry> {
+        let column = file.class.summary_column();
+        let copies = column == RobocopySummaryColumn::Copied;
+        let percentages = if copies && self.config.progress {
+            self.percentages(file.size)
+        } else {
+            Vec::new()
+        };
+        let mut group = Vec::new();
+
+        let error_percent = f64::from(self.config.error_percent.min(100)) / 100.0;
+        if copies && self.rng.random_bool(error_percent) {
+            for attempt in 0..=self.config.retries {
+                let partial = self.rng.random_range(0..percentages.len().max(1));
+                group.push(RobocopyLogEntry::file(
+                    file.class,
+                    file.size,
+                    path.clone(),
+                    percentages.get(..partial).unwrap_or_default().to_vec(),
+                ));
+                let code = *FILE_ERROR_CODES.choose(&mut self.rng).unwrap_or(&32);
+                group.push(RobocopyLogEntry::Error {
+                    when: self.clock,
+                    code,
+                    operation: "Copying File".to_string(),
+                    path: full_path.to_path_buf(),
+                    message: win32_error_info(code)
+                        .map(|info| info.message.to_string())
+                        .unwrap_or_default(),
+                });
+                if attempt == self.config.retries {
+                    group.push(RobocopyLogEntry::RetryLimitExceeded);
+                    self.files.add(RobocopySummaryColumn::Failed, 1);
+                    self.bytes.add(RobocopySummaryColumn::Failed, file.size);
+                    return group;
+                }
+                group.push(RobocopyLogEntry::Retry {
+                    wait: self.config.retry_wait,
+                });
+                self.advance(self.config.retry_wait);
+                if self.rng.random_bool(0.5) {
+                    break;
+                }
+            }
+        }
+
+        if copies {
+            let rate = self.rng.random_range(COPY_RATE);
+            let elapsed = Duration::from_secs((file.size.get::<byte>() / rate) as u64);
+            self.copy_time += elapsed;
+            self.advance(elapsed);
+        }
+        group.push(RobocopyLogEntry::file(
+            file.class,
+            file.size,
+            path,
+            percentages,
+        ));
+        self.files.add(column, 1);
+        self.bytes.add(column, file.size);
+        group
+    }
+
+    /// Increasing progress percentages ending at 100, as robocopy prints them.
+    fn percentages(&mut self, size: Information) -> Vec<u8> {
+        if size.get::<byte>() < 1024 * 1024 {
+            return vec![100];
+        }
+        let mut percentages = Vec::new();
+        let mut pct = 0_u8;
+        while pct < 100 {
+            pct = pct.saturating_add(self.rng.random_range(1..=12)).min(100);
+            percentages.push(pct);
+        }
+        percentages
+    }
+
+    fn advance(&mut self, elapsed: Duration) {
+        if let Ok(elapsed) = chrono::Duration::from_std(elapsed) {
+            self.clock += elapsed;
+        }
+    }
+
+    fn summary(&self) -> RobocopySummary {
+        let total = (self.clock - self.config.started)
+            .to_std()
+            .unwrap_or_default();
+        let copied_bytes = self.bytes.copied.get::<byte>() as u64;
+        let speed = (copied_bytes > 0).then(|| {
+            let bytes_per_second = copied_bytes / self.copy_time.as_secs().max(1);
+            RobocopySpeed {
+                bytes_per_second,
+                megabytes_per_minute_milli: bytes_per_second * 60 * 1000 / (1024 * 1024),
+            }
+        });
+        RobocopySummary {
+            dirs: self.dirs,
+            files: self.files,
+            bytes: self.bytes,
+            times: RobocopySummaryTimes {
+                total,
+                copied: self.copy_time,
+                failed: Duration::ZERO,
+                extras: Duration::ZERO,
+            },
+            speed,
+            ended: self.clock.into(),
+        }
+    }
+}
+
+/// Append `relative` to a directory path that ends in a backslash.
+///
+/// `Path::join` would use the host's separator, which is `/` off Windows.
+fn windows_join(dir: &Path, relative: &str) -> PathBuf {
+    PathBuf::from(format!("{}{relative}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+
+    fn configs() -> Vec<RobocopyLogGeneratorConfig> {
+        let base = RobocopyLogGeneratorConfig {
+            files: 60,
+            error_percent: 20,
+            ..Default::default()
+        };
+        vec![
+            base.clone(),
+            RobocopyLogGeneratorConfig {
+                seed: 1,
+                threads: Some(8),
+                option_set: RobocopyGeneratorOptionSet::Mirror,
+                ..base.clone()
+            },
+            RobocopyLogGeneratorConfig {
+                seed: 2,
+                retries: 0,
+                progress: false,
+                summary: false,
+                line_ending: LineEnding::CrLf,
+                ..base.clone()
+            },
+            RobocopyLogGeneratorConfig {
+                seed: 3,
+                encoding: RobocopyLogEncoding::Utf16Le,
+                line_ending: LineEnding::CrLf,
+                max_depth: 0,
+                classes: vec![RobocopyFileClass::Newer, RobocopyFileClass::Lonely],
+                ..base
+            },
+        ]
+    }
+
+    #[test]
+    fn same_seed_same_log() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig::default();
+        let first = RobocopyLogGenerator::new(config.clone()).generate_bytes()?;
+        let second = RobocopyLogGenerator::new(config.clone()).generate_bytes()?;
+        assert_eq!(first, second);
+        let other = RobocopyLogGenerator::new(RobocopyLogGeneratorConfig { seed: 7, ..config })
+            .generate_bytes()?;
+        assert_ne!(first, other);
+        Ok(())
+    }
+
+    #[test]
+    fn generated_logs_parse_losslessly() -> eyre::Result<()> {
+        for config in configs() {
+            let generator = RobocopyLogGenerator::new(config.clone());
+            let bytes = generator.generate_bytes()?;
+
+            let mut parser = RobocopyLogParser::new();
+            let mut writer =
+                RobocopyLogWriter::new(Vec::new()).with_line_ending(config.line_ending);
+            let mut files = 0;
+            for chunk in bytes.chunks(29) {
+                parser.accept_bytes(chunk);
+                loop {
+                    let advance = parser.advance()?;
+                    if advance == RobocopyParseAdvance::NeedMoreData {
+                        break;
+                    }
+                    if let RobocopyParseAdvance::Summary(summary) = &advance {
+                        files = summary.files.total + summary.files.extras;
+                    }
+                    writer.write_advance(&advance)?;
+                }
+            }
+            let rendered = config
+                .encoding
+                .encode(&String::from_utf8(writer.into_inner())?);
+            assert_eq!(rendered, bytes, "seed {} did not round trip", config.seed);
+            if config.summary {
+                assert_eq!(files, config.files as u64, "seed {}", config.seed);
+            }
+        }
+        Ok(())
+    }
+}
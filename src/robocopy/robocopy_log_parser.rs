@@ -1,36 +1,75 @@
 use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_encoding::RobocopyLogEncoding;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_CODE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
 use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_size::parse_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use chrono::DateTime;
 use chrono::Local;
+use chrono::NaiveDateTime;
 use chrono::TimeZone;
 use eyre::WrapErr;
 use std::path::PathBuf;
-use uom::si::information::byte;
+use std::time::Duration;
 use uom::si::usize::Information;
 
+/// Consumed bytes are only dropped from the front of the buffer once at least
+/// this many have accumulated, so large logs are not re-copied for every line.
+const COMPACT_THRESHOLD: usize = 64 * 1024;
+
+#[allow(
+    clippy::enum_variant_names,
+    reason = "each state names the part of the log being read"
+)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum InternalState {
     ReadingHeader,
     ReadingEntries,
+    ReadingSummary,
 }
 
 #[derive(Debug)]
 pub struct RobocopyLogParser {
     buf: String,
+    // Bytes of `buf` that have already been consumed
+    pos: usize,
     state: InternalState,
     // header building helpers
     header_dash_count: u8,
     header_scan_pos: usize,
-    // For tracking an in‑progress New File entry
-    pending_new_file: Option<PendingNewFile>,
+    // summary building helper
+    summary_scan_pos: usize,
+    // For tracking an in‑progress file entry
+    pending_file: Option<PendingFile>,
+    // Encoding of bytes given to `accept_bytes`, detected from the first bytes
+    encoding: Option<RobocopyLogEncoding>,
+    // Trailing bytes of an incomplete character from the last `accept_bytes` call
+    pending_bytes: Vec<u8>,
 }
 
 #[derive(Debug)]
-struct PendingNewFile {
+struct PendingFile {
+    class: RobocopyFileClass,
     size: Information,
     path: PathBuf,
     percentages: Vec<u8>,
 }
 
+impl PendingFile {
+    fn to_entry(&self) -> RobocopyLogEntry {
+        RobocopyLogEntry::file(
+            self.class,
+            self.size,
+            self.path.clone(),
+            self.percentages.clone(),
+        )
+    }
+}
+
 impl Default for RobocopyLogParser {
     fn default() -> Self {
         Self::new()
@@ -42,48 +81,151 @@ impl RobocopyLogParser {
     pub fn new() -> Self {
         Self {
             buf: String::new(),
+            pos: 0,
             state: InternalState::ReadingHeader,
             header_dash_count: 0,
             header_scan_pos: 0,
-            pending_new_file: None,
+            summary_scan_pos: 0,
+            pending_file: None,
+            encoding: None,
+            pending_bytes: Vec::new(),
         }
     }
 
+    /// Discard all buffered data and state, e.g. after the tailed file was truncated.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
     /// Accept a newly tailed chunk from the log file.
     pub fn accept(&mut self, chunk: &str) {
         self.buf.push_str(chunk);
     }
 
+    /// Accept a newly tailed chunk of raw bytes from the log file.
+    ///
+    /// The encoding (UTF-8, or UTF-16LE as written by `/UNILOG`) is detected
+    /// from the first bytes. Characters split across chunk boundaries are
+    /// carried over to the next call instead of being replaced; genuinely
+    /// invalid bytes are decoded lossily.
+    pub fn accept_bytes(&mut self, chunk: &[u8]) {
+        let mut bytes = std::mem::take(&mut self.pending_bytes);
+        bytes.extend_from_slice(chunk);
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            // Wait for enough bytes to recognise a byte order mark
+            None if bytes.len() < 3 => {
+                self.pending_bytes = bytes;
+                return;
+            }
+            None => {
+                let (encoding, bom_len) = RobocopyLogEncoding::detect(&bytes);
+                bytes.drain(..bom_len);
+                self.encoding = Some(encoding);
+                encoding
+            }
+        };
+        match encoding {
+            RobocopyLogEncoding::Utf8 => {
+                let complete_len = bytes.len() - incomplete_utf8_suffix_len(&bytes);
+                self.pending_bytes = bytes.split_off(complete_len);
+                self.buf.push_str(&String::from_utf8_lossy(&bytes));
+            }
+            RobocopyLogEncoding::Utf16Le => {
+                let mut complete_len = bytes.len() - bytes.len() % 2;
+                // Keep a trailing high surrogate until its pair arrives
+                if complete_len >= 2
+                    && (0xD800..=0xDBFF).contains(&u16::from_le_bytes([
+                        bytes[complete_len - 2],
+                        bytes[complete_len - 1],
+                    ]))
+                {
+                    complete_len -= 2;
+                }
+                self.pending_bytes = bytes.split_off(complete_len);
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+                self.buf.extend(
+                    char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)),
+                );
+            }
+        }
+    }
+
+    /// Mark the end of the input, e.g. at EOF of a log that is not followed.
+    ///
+    /// Bytes held back for an incomplete character are decoded lossily and an
+    /// unterminated last line is ended, so `advance` returns everything left.
+    pub fn finish(&mut self) {
+        let mut bytes = std::mem::take(&mut self.pending_bytes);
+        let encoding = *self.encoding.get_or_insert_with(|| {
+            let (encoding, bom_len) = RobocopyLogEncoding::detect(&bytes);
+            bytes.drain(..bom_len);
+            encoding
+        });
+        match encoding {
+            RobocopyLogEncoding::Utf8 => self.buf.push_str(&String::from_utf8_lossy(&bytes)),
+            RobocopyLogEncoding::Utf16Le => {
+                let units = bytes
+                    .chunks(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], *pair.get(1).unwrap_or(&0)]));
+                self.buf.extend(
+                    char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)),
+                );
+            }
+        }
+        if self.pos < self.buf.len() && !self.buf.ends_with('\n') {
+            self.buf.push('\n');
+        }
+    }
+
     /// Attempt to advance the parser. Returns `NeedMoreData` if no complete item yet.
     ///
     /// # Errors
     ///
     /// Returns an error if parsing a header or log entry fails.
     pub fn advance(&mut self) -> eyre::Result<RobocopyParseAdvance> {
+        self.compact();
         match self.state {
             InternalState::ReadingHeader => self.try_parse_header(),
             InternalState::ReadingEntries => self.try_parse_entry(),
+            InternalState::ReadingSummary => self.try_parse_summary(),
+        }
+    }
+
+    fn compact(&mut self) {
+        if self.pos == self.buf.len() || self.pos >= COMPACT_THRESHOLD {
+            self.buf.drain(..self.pos);
+            self.header_scan_pos = self.header_scan_pos.saturating_sub(self.pos);
+            self.summary_scan_pos = self.summary_scan_pos.saturating_sub(self.pos);
+            self.pos = 0;
         }
     }
 
+    fn start_header(&mut self) {
+        self.state = InternalState::ReadingHeader;
+        self.header_dash_count = 0;
+        self.header_scan_pos = self.pos;
+    }
+
     fn try_parse_header(&mut self) -> eyre::Result<RobocopyParseAdvance> {
         // Stream over new data only (from header_scan_pos)
-        let mut scan_pos = self.header_scan_pos;
+        let mut scan_pos = self.header_scan_pos.max(self.pos);
         while let Some(rel_nl) = self.buf[scan_pos..].find('\n') {
             let line_end = scan_pos + rel_nl + 1; // include \n
             let line = &self.buf[scan_pos..line_end];
             let trimmed = line.trim_end_matches(['\r', '\n']).trim();
-            if !trimmed.is_empty() && trimmed.chars().all(|c| c == '-') {
+            if is_separator_line(trimmed) {
                 self.header_dash_count += 1;
                 if self.header_dash_count == 3 {
                     // header complete including this dashed line
-                    let header_block = self.buf[..line_end].to_string();
-                    // consume all header bytes
-                    self.buf.drain(..line_end);
-                    self.header_scan_pos = 0; // reset (buffer now shorter)
-                    let header: RobocopyHeader = header_block
-                        .parse()
-                        .wrap_err("Failed to parse robocopy header")?;
+                    let header_block = &self.buf[self.pos..line_end];
+                    let parsed = header_block.parse::<RobocopyHeader>();
+                    // consume all header bytes, even if they were invalid
+                    self.pos = line_end;
+                    self.start_header();
+                    let header = parsed.wrap_err("Failed to parse robocopy header")?;
                     self.state = InternalState::ReadingEntries;
                     return Ok(RobocopyParseAdvance::Header(header));
                 }
@@ -95,135 +237,133 @@ impl RobocopyLogParser {
         Ok(RobocopyParseAdvance::NeedMoreData)
     }
 
-    #[allow(
-        clippy::too_many_lines,
-        reason = "state-machine parsing of streamed robocopy entries"
-    )]
     fn try_parse_entry(&mut self) -> eyre::Result<RobocopyParseAdvance> {
         loop {
-            if let Some(pending) = &mut self.pending_new_file {
-                // try to read next segment (handle CR or LF)
-                let pos = match (self.buf.find('\n'), self.buf.find('\r')) {
-                    (Some(nl), Some(cr)) => Some(nl.min(cr)),
-                    (Some(nl), None) => Some(nl),
-                    (None, Some(cr)) => Some(cr),
-                    (None, None) => None,
-                };
-                let Some(end) = pos else {
-                    return Ok(RobocopyParseAdvance::NeedMoreData);
-                };
-                let seg_with_term = self.buf[..=end].to_string();
-                self.buf.drain(..=end);
-                for piece in seg_with_term
-                    .split(['\r', '\n'])
-                    .filter(|p| !p.trim().is_empty())
-                {
-                    let trimmed = piece.trim();
-                    if let Some(pct) = parse_percentage_line(trimmed) {
-                        pending.percentages.push(pct);
-                        if pct == 100 {
-                            // finalize and emit final state
-                            let finished = self.pending_new_file.take().unwrap();
-                            return Ok(RobocopyParseAdvance::LogEntry(RobocopyLogEntry::NewFile {
-                                size: finished.size,
-                                path: finished.path,
-                                percentages: finished.percentages,
-                            }));
-                        }
-                        // Emit incremental state (clone path)
-                        return Ok(RobocopyParseAdvance::LogEntry(RobocopyLogEntry::NewFile {
-                            size: pending.size,
-                            path: pending.path.clone(),
-                            percentages: pending.percentages.clone(),
-                        }));
-                    } else if is_new_file_line(trimmed) {
-                        // finalize previous without adding new pct and reprocess line
-                        let finished = self.pending_new_file.take().unwrap();
-                        self.buf.insert_str(0, &format!("{trimmed}\n"));
-                        return Ok(RobocopyParseAdvance::LogEntry(RobocopyLogEntry::NewFile {
-                            size: finished.size,
-                            path: finished.path,
-                            percentages: finished.percentages,
-                        }));
-                    }
-                    // ignore noise
-                }
-                // more data is required to make progress for pending item
-                continue;
-            }
-
-            // read line/segment for new entry when no pending file
-            let pos = match (self.buf.find('\n'), self.buf.find('\r')) {
-                (Some(nl), Some(cr)) => Some(nl.min(cr)),
-                (Some(nl), None) => Some(nl),
-                (None, Some(cr)) => Some(cr),
-                (None, None) => None,
-            };
-            let Some(end) = pos else {
+            let Some((line, next)) = line_at(&self.buf, self.pos) else {
                 return Ok(RobocopyParseAdvance::NeedMoreData);
             };
-            let seg_with_term = self.buf[..=end].to_string();
-            self.buf.drain(..=end);
-            let mut pieces: Vec<&str> = seg_with_term
-                .split(['\r', '\n'])
-                .filter(|p| !p.trim().is_empty())
-                .collect();
-            if pieces.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                self.pos = next;
                 continue;
             }
-            let first = pieces.remove(0);
-            if !pieces.is_empty() {
-                let rest = pieces.join("\n");
-                self.buf.insert_str(0, &format!("{rest}\n"));
+
+            if let Some(pending) = &mut self.pending_file {
+                if let Some(pct) = parse_percentage_line(trimmed) {
+                    self.pos = next;
+                    pending.percentages.push(pct);
+                    let entry = pending.to_entry();
+                    if pct == 100 {
+                        self.pending_file = None;
+                    }
+                    return Ok(RobocopyParseAdvance::LogEntry(entry));
+                }
+                // Anything else means robocopy moved on; the line is processed below
+                self.pending_file = None;
             }
-            let trimmed = first.trim();
-            if trimmed.is_empty() {
-                continue;
+
+            if is_separator_line(trimmed) {
+                // Start of the job summary; it is consumed as a whole once complete
+                self.state = InternalState::ReadingSummary;
+                self.summary_scan_pos = self.pos;
+                return self.try_parse_summary();
             }
-            if let Some(access_err) = parse_access_denied_first_line(trimmed)? {
-                // Peek if we have a following line terminator at all
-                let pos2 = match (self.buf.find('\n'), self.buf.find('\r')) {
-                    (Some(nl), Some(cr)) => Some(nl.min(cr)),
-                    (Some(nl), None) => Some(nl),
-                    (None, Some(cr)) => Some(cr),
-                    (None, None) => None,
-                };
-                let Some(end2) = pos2 else {
-                    // not enough data, push back and wait
-                    self.buf.insert_str(0, &format!("{trimmed}\n"));
-                    return Ok(RobocopyParseAdvance::NeedMoreData);
+
+            let error = parse_error_first_line(trimmed).inspect_err(|_| {
+                // Skip the bad line so the next call makes progress
+                self.pos = next;
+            })?;
+            if let Some(error) = error {
+                // The explanation is on the next non-blank line
+                let mut after = next;
+                let message = loop {
+                    let Some((candidate, candidate_next)) = line_at(&self.buf, after) else {
+                        return Ok(RobocopyParseAdvance::NeedMoreData);
+                    };
+                    let candidate = candidate.trim();
+                    if candidate.is_empty() {
+                        after = candidate_next;
+                        continue;
+                    }
+                    if is_entry_line(candidate) {
+                        // No explanation; leave the line for the next entry
+                        after = next;
+                        break String::new();
+                    }
+                    after = candidate_next;
+                    break candidate.to_string();
                 };
-                let second_line = self.buf[..=end2].to_string();
-                self.buf.drain(..=end2);
-                if second_line.trim().eq_ignore_ascii_case("Access is denied.") {
-                    return Ok(RobocopyParseAdvance::LogEntry(access_err));
-                } else if second_line.trim().is_empty() {
-                    // blank line after; accept the error anyway
-                    return Ok(RobocopyParseAdvance::LogEntry(access_err));
-                }
-                // Unexpected; reinsert consumed second line for future processing and still emit error.
-                self.buf.insert_str(0, &second_line);
-                return Ok(RobocopyParseAdvance::LogEntry(access_err));
+                self.pos = after;
+                return Ok(RobocopyParseAdvance::LogEntry(error.into_entry(message)));
+            }
+
+            self.pos = next;
+            if let Some(wait) = parse_retry_line(trimmed) {
+                return Ok(RobocopyParseAdvance::LogEntry(RobocopyLogEntry::Retry {
+                    wait,
+                }));
+            }
+            if is_retry_limit_line(trimmed) {
+                return Ok(RobocopyParseAdvance::LogEntry(
+                    RobocopyLogEntry::RetryLimitExceeded,
+                ));
+            }
+            if let Some((class, size, path)) = parse_file_line(trimmed) {
+                self.pending_file = Some(PendingFile {
+                    class,
+                    size,
+                    path,
+                    percentages: Vec::new(),
+                });
+                // emit initial file state with empty percentages
+                let entry = self
+                    .pending_file
+                    .as_ref()
+                    .map(PendingFile::to_entry)
+                    .expect("pending file was just set");
+                return Ok(RobocopyParseAdvance::LogEntry(entry));
             }
             if is_new_file_line(trimmed) {
-                if let Some((size, path)) = parse_new_file_line(trimmed) {
-                    self.pending_new_file = Some(PendingNewFile {
-                        size,
-                        path: path.clone(),
-                        percentages: Vec::new(),
-                    });
-                    // emit initial new file with empty percentages
-                    return Ok(RobocopyParseAdvance::LogEntry(RobocopyLogEntry::NewFile {
-                        size,
-                        path,
-                        percentages: Vec::new(),
-                    }));
-                }
                 eyre::bail!("Failed to parse New File line: '{}'", trimmed);
             }
-            // percentage lines at top-level are ignored; nothing to do here
+            if let Some((class, file_count, path)) = parse_directory_line(trimmed) {
+                return Ok(RobocopyParseAdvance::LogEntry(
+                    RobocopyLogEntry::Directory {
+                        class,
+                        file_count,
+                        path,
+                    },
+                ));
+            }
+            // percentage lines at top-level and unrecognised noise are ignored
         }
     }
+
+    fn try_parse_summary(&mut self) -> eyre::Result<RobocopyParseAdvance> {
+        let mut scan_pos = self.summary_scan_pos.max(self.pos);
+        while let Some((line, next)) = line_at(&self.buf, scan_pos) {
+            let trimmed = line.trim();
+            if trimmed.starts_with("ROBOCOPY") && trimmed.contains("::") {
+                // A new job started without a summary (e.g. the previous run was killed);
+                // the separator we took for the summary is actually the new banner.
+                self.start_header();
+                return self.try_parse_header();
+            }
+            if trimmed
+                .split_once(':')
+                .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("Ended"))
+            {
+                let parsed = self.buf[self.pos..next].parse::<RobocopySummary>();
+                self.pos = next;
+                self.start_header();
+                let summary = parsed.wrap_err("Failed to parse robocopy summary")?;
+                return Ok(RobocopyParseAdvance::Summary(summary));
+            }
+            scan_pos = next;
+        }
+        self.summary_scan_pos = scan_pos;
+        Ok(RobocopyParseAdvance::NeedMoreData)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -231,6 +371,50 @@ pub enum RobocopyParseAdvance {
     NeedMoreData,
     Header(RobocopyHeader),
     LogEntry(RobocopyLogEntry),
+    Summary(RobocopySummary),
+}
+
+/// Number of trailing bytes that form the start of a multi-byte UTF-8 sequence
+/// which has not been completed yet.
+fn incomplete_utf8_suffix_len(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - back];
+        if b & 0b1100_0000 == 0b1000_0000 {
+            // continuation byte, keep looking for the lead byte
+            continue;
+        }
+        let expected = match b {
+            0b1100_0000..=0b1101_1111 => 2,
+            0b1110_0000..=0b1110_1111 => 3,
+            0b1111_0000..=0b1111_0111 => 4,
+            _ => 1,
+        };
+        return if expected > back { back } else { 0 };
+    }
+    0
+}
+
+/// The line of `buf` starting at `from`, without its terminator, and the
+/// offset just past the terminator. Lines end at either `\r` or `\n`.
+fn line_at(buf: &str, from: usize) -> Option<(&str, usize)> {
+    let rel_end = buf[from..].find(['\r', '\n'])?;
+    Some((&buf[from..from + rel_end], from + rel_end + 1))
+}
+
+fn is_separator_line(trimmed: &str) -> bool {
+    !trimmed.is_empty() && trimmed.chars().all(|c| c == '-')
+}
+
+/// Whether a trimmed line starts an entry of its own (used to tell error
+/// explanations apart from the next entry).
+fn is_entry_line(trimmed: &str) -> bool {
+    is_separator_line(trimmed)
+        || parse_percentage_line(trimmed).is_some()
+        || parse_retry_line(trimmed).is_some()
+        || is_retry_limit_line(trimmed)
+        || parse_file_line(trimmed).is_some()
+        || parse_directory_line(trimmed).is_some()
+        || matches!(parse_error_first_line(trimmed), Ok(Some(_)))
 }
 
 fn parse_percentage_line(s: &str) -> Option<u8> {
@@ -248,117 +432,165 @@ fn parse_percentage_line(s: &str) -> Option<u8> {
     None
 }
 
-fn parse_access_denied_first_line(line: &str) -> eyre::Result<Option<RobocopyLogEntry>> {
-    // Format: YYYY/MM/DD HH:MM:SS ERROR <code> (<hex>) Copying Directory <PATH>\
+/// The first line of an error block, waiting for its explanation line.
+struct ErrorFirstLine {
+    when: DateTime<Local>,
+    code: u32,
+    operation: String,
+    path: PathBuf,
+}
+
+impl ErrorFirstLine {
+    fn into_entry(self, message: String) -> RobocopyLogEntry {
+        if self.code == ACCESS_DENIED_CODE
+            && self.operation.eq_ignore_ascii_case(ACCESS_DENIED_OPERATION)
+            && (message.is_empty() || message.eq_ignore_ascii_case(ACCESS_DENIED_MESSAGE))
+        {
+            return RobocopyLogEntry::AccessDeniedError {
+                when: self.when,
+                path: self.path,
+            };
+        }
+        RobocopyLogEntry::Error {
+            when: self.when,
+            code: self.code,
+            operation: self.operation,
+            path: self.path,
+            message,
+        }
+    }
+}
+
+fn parse_error_first_line(line: &str) -> eyre::Result<Option<ErrorFirstLine>> {
+    // Format: YYYY/MM/DD HH:MM:SS ERROR <code> (<hex>) <operation> <PATH>
     // We'll be lenient.
     let parts: Vec<&str> = line.split_whitespace().collect();
-    if parts.len() < 7 {
+    if parts.len() < 6 {
         return Ok(None);
     }
-    if parts[0].len() == 10
+    let date_like = parts[0].len() == 10
         && parts[0].chars().nth(4) == Some('/')
-        && parts[0].chars().nth(7) == Some('/')
+        && parts[0].chars().nth(7) == Some('/');
+    let time_like = parts[1].len() == 8
+        && parts[1].chars().nth(2) == Some(':')
+        && parts[1].chars().nth(5) == Some(':');
+    if !date_like
+        || !time_like
+        || !parts[2].eq_ignore_ascii_case("ERROR")
+        || !parts[4].starts_with("(0x")
     {
-        // date-like
-        if parts[1].len() == 8
-            && parts[1].chars().nth(2) == Some(':')
-            && parts[1].chars().nth(5) == Some(':')
-            && parts[2].eq_ignore_ascii_case("ERROR")
-            && parts[4].starts_with("(0x")
-        {
-            // find "Copying" and "Directory"
-            if parts[5].eq_ignore_ascii_case("Copying")
-                && parts[6].eq_ignore_ascii_case("Directory")
-            {
-                // path remainder (joined with spaces) after 'Directory'
-                // Slice after the "Directory" token occurrence.
-                if let Some(dir_pos) = line.find("Directory") {
-                    let after = &line[dir_pos + "Directory".len()..].trim();
-                    let path_str = after; // includes trailing backslash per format
-                    // reconstruct datetime
-                    let date = parts[0];
-                    let time = parts[1];
-                    // date format yyyy/mm/dd time HH:MM:SS
-                    let year: i32 = date[0..4].parse()?;
-                    let month: u32 = date[5..7].parse()?;
-                    let day: u32 = date[8..10].parse()?;
-                    let hour: u32 = time[0..2].parse()?;
-                    let minute: u32 = time[3..5].parse()?;
-                    let second: u32 = time[6..8].parse()?;
-                    let when = Local
-                        .with_ymd_and_hms(year, month, day, hour, minute, second)
-                        .single()
-                        .ok_or_else(|| eyre::eyre!("Invalid timestamp in access denied line"))?;
-                    return Ok(Some(RobocopyLogEntry::AccessDeniedError {
-                        when,
-                        path: PathBuf::from(path_str),
-                    }));
-                }
-            }
+        return Ok(None);
+    }
+    let Ok(code) = parts[3].parse::<u32>() else {
+        return Ok(None);
+    };
+    let naive =
+        NaiveDateTime::parse_from_str(&format!("{} {}", parts[0], parts[1]), "%Y/%m/%d %H:%M:%S")
+            .wrap_err("Invalid timestamp in error line")?;
+    let when = Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| eyre::eyre!("Invalid timestamp in error line"))?;
+
+    // Everything after the hex code is "<operation> <path>"
+    let hex_end = line
+        .find(parts[4])
+        .map_or(line.len(), |i| i + parts[4].len());
+    let rest = line[hex_end..].trim();
+    let (operation, path) = match find_path_start(rest) {
+        Some(idx) => (rest[..idx].trim(), rest[idx..].trim()),
+        None => (rest, ""),
+    };
+    Ok(Some(ErrorFirstLine {
+        when,
+        code,
+        operation: operation.to_string(),
+        path: PathBuf::from(path),
+    }))
+}
+
+/// Find where the path starts in `"<operation words> <path>"`.
+fn find_path_start(s: &str) -> Option<usize> {
+    let mut offset = 0;
+    for word in s.split(' ') {
+        let bytes = word.as_bytes();
+        let is_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+        if is_drive || word.starts_with('\\') || word.starts_with('/') {
+            return Some(offset);
         }
+        offset += word.len() + 1;
+    }
+    None
+}
+
+fn parse_retry_line(line: &str) -> Option<Duration> {
+    // Format: Waiting 30 seconds... Retrying...
+    let rest = line.strip_prefix("Waiting ")?;
+    let (secs, rest) = rest.split_once(' ')?;
+    if !rest.starts_with("seconds") {
+        return None;
     }
-    Ok(None)
+    Some(Duration::from_secs(secs.parse().ok()?))
+}
+
+fn is_retry_limit_line(line: &str) -> bool {
+    line.eq_ignore_ascii_case("ERROR: RETRY LIMIT EXCEEDED.")
 }
 
 fn is_new_file_line(line: &str) -> bool {
-    line.contains("New File")
+    line.split('\t')
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .is_some_and(|first| first.eq_ignore_ascii_case("New File"))
 }
 
-fn parse_new_file_line(line: &str) -> Option<(Information, PathBuf)> {
+fn parse_file_line(line: &str) -> Option<(RobocopyFileClass, Information, PathBuf)> {
     // Strategy: split by tabs; filter out empty trimmed segments.
     let segs: Vec<&str> = line
         .split('\t')
         .map(str::trim)
         .filter(|s| !s.is_empty())
         .collect();
-    if segs.is_empty() {
-        return None;
-    }
-    if !segs[0].eq_ignore_ascii_case("New File") {
+    let [class, .., size_seg, path_str] = segs[..] else {
         return None;
-    }
-    if segs.len() < 3 {
-        return None;
-    }
-    let path_str = segs.last().unwrap();
+    };
+    let class: RobocopyFileClass = class.parse().ok()?;
     // size may be like "50.0 m" or "204576"
-    let size_seg = segs[segs.len() - 2];
-    let bytes = parse_size_to_bytes(size_seg)?;
-    Some((bytes, PathBuf::from(path_str)))
+    let size = parse_robocopy_size(size_seg)?;
+    Some((class, size, PathBuf::from(path_str)))
 }
 
-#[allow(
-    clippy::cast_possible_truncation,
-    clippy::cast_sign_loss,
-    reason = "robocopy output contains human-readable floats that we round to usize"
-)]
-fn parse_size_to_bytes(s: &str) -> Option<Information> {
-    let t = s.trim().to_lowercase();
-    if t.is_empty() {
+fn parse_directory_line(line: &str) -> Option<(RobocopyDirectoryClass, i64, PathBuf)> {
+    // Format: [label] <file count>\t<path>
+    let segs: Vec<&str> = line
+        .split('\t')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let [label_and_count, path_str] = segs[..] else {
         return None;
-    }
-    let mut chars = t.chars().rev();
-    let unit_char = chars.next().unwrap();
-    let (num_str, unit) = if unit_char.is_ascii_alphabetic() {
-        (&t[..t.len() - 1], Some(unit_char))
-    } else {
-        ((&*t), None)
-    };
-    let number: f64 = num_str.trim().parse().ok()?;
-    let factor = match unit {
-        None => 1.0,
-        Some('k') => 1024.0,
-        Some('m') => 1024.0 * 1024.0,
-        Some('g') => 1024.0 * 1024.0 * 1024.0,
-        Some('t') => 1024.0 * 1024.0 * 1024.0 * 1024.0,
-        Some(_) => return None,
     };
-    Some(Information::new::<byte>((number * factor) as usize))
+    let (label, count) = label_and_count
+        .rsplit_once(' ')
+        .unwrap_or(("", label_and_count));
+    let file_count: i64 = count.parse().ok()?;
+    let class: RobocopyDirectoryClass = label.parse().ok()?;
+    Some((class, file_count, PathBuf::from(path_str)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_writer::LineEnding;
+    use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+    use arbitrary::Arbitrary;
+    use rand::Rng;
+    use rand::RngCore;
+    use rand::SeedableRng;
+    use rand::seq::IndexedRandom;
+    use rand_chacha::ChaCha8Rng;
+    use uom::si::information::byte;
     use uom::si::information::mebibyte;
 
     fn push_new_file(
@@ -385,6 +617,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg_attr(not(windows), ignore = "`J:\\` only equals `J:/` as a Windows path")]
     fn parse_header_and_first_entries_streaming() -> eyre::Result<()> {
         let sample = include_str!("sample.txt");
         let mut parser = RobocopyLogParser::new();
@@ -404,6 +637,7 @@ mod tests {
                         header = Some(h);
                     }
                     RobocopyParseAdvance::LogEntry(entry) => entries.push(entry),
+                    RobocopyParseAdvance::Summary(_) => panic!("Sample has no summary"),
                 }
             }
         }
@@ -463,4 +697,166 @@ mod tests {
         assert_eq!(entries, expected, "Parsed entries mismatch");
         Ok(())
     }
+
+    #[test]
+    fn streamed_header_keeps_paths_as_written() -> eyre::Result<()> {
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(include_str!("sample.txt"));
+        let RobocopyParseAdvance::Header(header) = parser.advance()? else {
+            panic!("Sample starts with a header");
+        };
+        assert_eq!(header.source, PathBuf::from(r"J:\"));
+        assert_eq!(header.dest, PathBuf::from(r"K:\"));
+        Ok(())
+    }
+
+    /// Parse `bytes` fed in chunks of the given sizes (cycled), recording
+    /// errors instead of stopping at them.
+    fn parse_chunked(
+        bytes: &[u8],
+        chunk_sizes: &[usize],
+    ) -> Vec<Result<RobocopyParseAdvance, String>> {
+        let mut parser = RobocopyLogParser::new();
+        let mut out = Vec::new();
+        let mut rest = bytes;
+        let mut sizes = chunk_sizes.iter().cycle();
+        while !rest.is_empty() {
+            let size = (*sizes.next().unwrap()).clamp(1, rest.len());
+            let (chunk, tail) = rest.split_at(size);
+            rest = tail;
+            parser.accept_bytes(chunk);
+            loop {
+                match parser.advance() {
+                    Ok(RobocopyParseAdvance::NeedMoreData) => break,
+                    Ok(advance) => out.push(Ok(advance)),
+                    Err(e) => out.push(Err(format!("{e:#}"))),
+                }
+                assert!(out.len() < 1_000_000, "parser stopped making progress");
+            }
+        }
+        out
+    }
+
+    /// Deterministic pseudo-random input for `Unstructured`.
+    fn random_bytes(rng: &mut ChaCha8Rng, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0; len];
+        rng.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn render(logs: &[RobocopyLog], line_ending: LineEnding) -> eyre::Result<String> {
+        let mut writer = RobocopyLogWriter::new(Vec::new()).with_line_ending(line_ending);
+        for log in logs {
+            writer.write_log(log)?;
+        }
+        Ok(String::from_utf8(writer.into_inner())?)
+    }
+
+    #[test]
+    fn arbitrary_documents_parse_back_in_any_chunking() -> eyre::Result<()> {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for iteration in 0..300 {
+            let data = random_bytes(&mut rng, 8192);
+            let mut u = arbitrary::Unstructured::new(&data);
+            let logs = (0..u.int_in_range(1..=3)?)
+                .map(|_| RobocopyLog::arbitrary(&mut u))
+                .collect::<arbitrary::Result<Vec<_>>>()?;
+            let line_ending = if rng.random_bool(0.5) {
+                LineEnding::CrLf
+            } else {
+                LineEnding::Lf
+            };
+            let text = render(&logs, line_ending)?;
+
+            assert_eq!(
+                RobocopyLog::parse_all(&text)?,
+                logs,
+                "iteration {iteration} parsed differently:\n{text}"
+            );
+            let whole = parse_chunked(text.as_bytes(), &[text.len()]);
+            let chunk_sizes: Vec<usize> = (0..8).map(|_| rng.random_range(1..=64)).collect();
+            assert_eq!(
+                parse_chunked(text.as_bytes(), &chunk_sizes),
+                whole,
+                "iteration {iteration} depends on chunks {chunk_sizes:?}"
+            );
+            let utf16 = RobocopyLogEncoding::Utf16Le.encode(&text);
+            assert_eq!(
+                parse_chunked(&utf16, &chunk_sizes),
+                whole,
+                "iteration {iteration} differs when UTF-16 encoded"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted_documents_never_panic_and_ignore_chunking() -> eyre::Result<()> {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for iteration in 0..300 {
+            let data = random_bytes(&mut rng, 4096);
+            let mut u = arbitrary::Unstructured::new(&data);
+            let mut bytes =
+                render(&[RobocopyLog::arbitrary(&mut u)?], LineEnding::CrLf)?.into_bytes();
+            // Corrupt the document: overwrite, insert and delete random spans
+            for _ in 0..rng.random_range(1..=8) {
+                let at = rng.random_range(0..=bytes.len());
+                let len = rng.random_range(0..=16).min(bytes.len() - at);
+                match rng.random_range(0..4) {
+                    0 => bytes[at..at + len].fill_with(|| rng.random()),
+                    1 => {
+                        let noise = random_bytes(&mut rng, len);
+                        bytes.splice(at..at, noise);
+                    }
+                    2 => {
+                        bytes.drain(at..at + len);
+                    }
+                    _ => {
+                        // Digits and separators are where the line parsers do arithmetic
+                        let noise: Vec<u8> = (0..len)
+                            .map(|_| *b"0123456789:%-/\t ".choose(&mut rng).unwrap())
+                            .collect();
+                        bytes.splice(at..at, noise);
+                    }
+                }
+            }
+            let whole = parse_chunked(&bytes, &[bytes.len()]);
+            let chunk_sizes: Vec<usize> = (0..8).map(|_| rng.random_range(1..=64)).collect();
+            assert_eq!(
+                parse_chunked(&bytes, &chunk_sizes),
+                whole,
+                "iteration {iteration} depends on chunks {chunk_sizes:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_bytes_never_panic() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        for _ in 0..200 {
+            let mut bytes = SAMPLE_PREFIX.as_bytes().to_vec();
+            let len = rng.random_range(0..2048);
+            bytes.extend(random_bytes(&mut rng, len));
+            let chunk = rng.random_range(1..=97);
+            parse_chunked(&bytes, &[chunk]);
+        }
+    }
+
+    /// A header, so that random bytes after it reach the entry parsers.
+    const SAMPLE_PREFIX: &str = "\
+-------------------------------------------------------------------------------
+   ROBOCOPY     ::     Robust File Copy for Windows
+-------------------------------------------------------------------------------
+
+  Started : August 27, 2025 10:19:37 PM
+   Source : J:\\
+     Dest : K:\\
+
+    Files : *.*
+
+  Options : *.* /S /E
+
+------------------------------------------------------------------------------
+";
 }
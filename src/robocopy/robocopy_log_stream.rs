@@ -0,0 +1,277 @@
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use eyre::WrapErr;
+use futures::Stream;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::io::ReadBuf;
+use tokio::time::Sleep;
+
+const DEFAULT_READ_CHUNK_SIZE: usize = 64 * 1024;
+const DEFAULT_FOLLOW_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Async adapter that drives a [`RobocopyLogParser`] from any [`AsyncRead`].
+///
+/// The stream yields every parsed header and log entry; it never yields
+/// [`RobocopyParseAdvance::NeedMoreData`]. Reads are only issued when the
+/// consumer polls and the parser has nothing buffered, so a slow consumer
+/// applies backpressure all the way to the reader.
+///
+/// When following, reaching EOF waits for the follow interval and tries again
+/// instead of ending the stream, which makes it suitable for tailing a log that
+/// robocopy is still writing.
+#[derive(Debug)]
+pub struct RobocopyLogStream<R> {
+    reader: R,
+    parser: RobocopyLogParser,
+    read_buf: Box<[u8]>,
+    follow_interval: Option<Duration>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    /// Set once EOF was reached without following and the parser was told so.
+    flushed: bool,
+    finished: bool,
+}
+
+impl<R: AsyncRead + Unpin> RobocopyLogStream<R> {
+    /// Parse `reader` until EOF.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            parser: RobocopyLogParser::new(),
+            read_buf: vec![0u8; DEFAULT_READ_CHUNK_SIZE].into_boxed_slice(),
+            follow_interval: None,
+            sleep: None,
+            flushed: false,
+            finished: false,
+        }
+    }
+
+    /// Keep polling the reader after EOF, waiting `interval` between attempts.
+    #[must_use]
+    pub fn follow(mut self, interval: Duration) -> Self {
+        self.follow_interval = Some(interval);
+        self
+    }
+
+    /// Set how many bytes are requested from the reader at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    #[must_use]
+    pub fn with_read_chunk_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "read chunk size must be non-zero");
+        self.read_buf = vec![0u8; size].into_boxed_slice();
+        self
+    }
+
+    /// Access the underlying parser, e.g. to inspect its state between items.
+    #[must_use]
+    pub fn parser(&self) -> &RobocopyLogParser {
+        &self.parser
+    }
+
+    /// Consume the stream and return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl RobocopyLogStream<tokio::fs::File> {
+    /// Open a robocopy log file and parse it to EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub async fn open(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path)
+            .await
+            .wrap_err_with(|| format!("Failed to open robocopy log: {}", path.display()))?;
+        Ok(Self::new(file))
+    }
+
+    /// Open a robocopy log file and keep following it as it grows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub async fn tail(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(Self::open(path).await?.follow(DEFAULT_FOLLOW_INTERVAL))
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for RobocopyLogStream<R> {
+    type Item = eyre::Result<RobocopyParseAdvance>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            // Drain whatever the parser can already produce before reading more
+            match this.parser.advance() {
+                Ok(RobocopyParseAdvance::NeedMoreData) => {}
+                Ok(item) => return Poll::Ready(Some(Ok(item))),
+                Err(e) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+
+            if let Some(sleep) = &mut this.sleep {
+                match sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => this.sleep = None,
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+
+            let mut read_buf = ReadBuf::new(&mut this.read_buf);
+            match Pin::new(&mut this.reader).poll_read(cx, &mut read_buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(e).wrap_err("Failed to read from robocopy log")));
+                }
+                Poll::Ready(Ok(())) => {}
+            }
+
+            let filled = read_buf.filled();
+            if filled.is_empty() {
+                match this.follow_interval {
+                    Some(interval) => {
+                        this.sleep = Some(Box::pin(tokio::time::sleep(interval)));
+                    }
+                    // Let the parser hand out an unterminated last line first
+                    None if !this.flushed => {
+                        this.parser.finish();
+                        this.flushed = true;
+                    }
+                    None => {
+                        this.finished = true;
+                    }
+                }
+                continue;
+            }
+            this.parser.accept_bytes(filled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+    use futures::StreamExt;
+    use std::path::PathBuf;
+    use tokio::io::AsyncWriteExt;
+
+    const LOG: &str = "\
+-------------------------------------------------------------------------------
+   ROBOCOPY     ::     Robust File Copy for Windows
+-------------------------------------------------------------------------------
+
+  Started : August 27, 2025 10:19:37 PM
+   Source : J:\\
+     Dest : K:\\
+
+    Files : *.*
+
+  Options : *.* /TEE /S /E /DCOPY:DA /COPY:DAT /MT:16 /R:1000000 /W:5
+
+------------------------------------------------------------------------------
+
+\t    New File  \t\t  204576\tJ:\\docs\\r\u{e9}sum\u{e9}.txt
+100%
+";
+
+    fn entry_paths(items: &[RobocopyParseAdvance]) -> Vec<PathBuf> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                RobocopyParseAdvance::LogEntry(RobocopyLogEntry::NewFile { path, .. }) => {
+                    Some(path.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn parses_reader_to_eof_with_tiny_reads() -> eyre::Result<()> {
+        // A 3 byte chunk size splits the multi-byte 'é' across reads
+        let stream = RobocopyLogStream::new(LOG.as_bytes()).with_read_chunk_size(3);
+        let items: Vec<_> = stream.collect::<Vec<_>>().await;
+        let items = items.into_iter().collect::<eyre::Result<Vec<_>>>()?;
+        assert!(matches!(items[0], RobocopyParseAdvance::Header(_)));
+        assert_eq!(
+            entry_paths(&items),
+            vec![PathBuf::from(r"J:\docs\résumé.txt"); 2]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parses_last_line_without_newline() -> eyre::Result<()> {
+        let log = LOG.trim_end_matches('\n');
+        let stream = RobocopyLogStream::new(log.as_bytes()).with_read_chunk_size(5);
+        let items = stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<eyre::Result<Vec<_>>>()?;
+        assert_eq!(
+            entry_paths(&items),
+            vec![PathBuf::from(r"J:\docs\résumé.txt"); 2]
+        );
+
+        // A character cut off by EOF is replaced rather than dropped
+        let mut bytes = log.as_bytes().to_vec();
+        bytes.extend_from_slice(b"\n\t    New File  \t\t       0\tJ:\\docs\\\xc3");
+        let items = RobocopyLogStream::new(bytes.as_slice())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<eyre::Result<Vec<_>>>()?;
+        assert_eq!(
+            entry_paths(&items).last(),
+            Some(&PathBuf::from("J:\\docs\\\u{fffd}"))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn follow_waits_for_more_data() -> eyre::Result<()> {
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let mut stream = RobocopyLogStream::new(reader).follow(Duration::from_millis(5));
+
+        let (head, tail) = LOG.split_at(LOG.find("\t    New File").unwrap());
+        let write_head = async {
+            writer.write_all(head.as_bytes()).await?;
+            eyre::Ok(())
+        };
+        let (written, header) = tokio::join!(write_head, stream.next());
+        written?;
+        assert!(matches!(header, Some(Ok(RobocopyParseAdvance::Header(_)))));
+
+        // Nothing else is available yet; the stream must stay pending
+        let pending = tokio::time::timeout(Duration::from_millis(50), stream.next()).await;
+        assert!(pending.is_err(), "stream yielded without new data");
+
+        writer.write_all(tail.as_bytes()).await?;
+        let entry = stream.next().await.transpose()?;
+        assert!(matches!(
+            entry,
+            Some(RobocopyParseAdvance::LogEntry(
+                RobocopyLogEntry::NewFile { .. }
+            ))
+        ));
+        Ok(())
+    }
+}
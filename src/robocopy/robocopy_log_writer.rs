@@ -0,0 +1,339 @@
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_CODE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use arbitrary::Arbitrary;
+use chrono::DateTime;
+use chrono::Local;
+use clap::ValueEnum;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Width of the label and file count column of a directory line.
+const DIRECTORY_COLUMN_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Arbitrary)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    /// What robocopy itself writes on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Renders parsed robocopy output back into robocopy's text layout.
+///
+/// Entries can be written straight from the parser: when a file entry is
+/// written again with more percentages (the parser emits one entry per
+/// progress tick), only the new progress lines are appended.
+///
+/// Sizes go through [`format_robocopy_size`], which rounds anything from a
+/// mebibyte up to one decimal place as robocopy does. Parsed logs still render
+/// byte for byte, since their sizes were rounded by robocopy in the first
+/// place, but an entry or summary built from an exact count such as
+/// 52,430,000 bytes is written as `50.0 m` and reads back as 52,428,800.
+#[derive(Debug)]
+pub struct RobocopyLogWriter<W> {
+    inner: W,
+    line_ending: LineEnding,
+    // The last file entry written and how many of its percentages are already out
+    last_file: Option<(RobocopyFileClass, PathBuf, usize)>,
+}
+
+impl<W: Write> RobocopyLogWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line_ending: LineEnding::Lf,
+            last_file: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if flushing the underlying writer fails.
+    pub fn flush(&mut self) -> eyre::Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Write a complete job: header, entries and summary if present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_log(&mut self, log: &RobocopyLog) -> eyre::Result<()> {
+        self.write_header(&log.header)?;
+        for entry in &log.parts {
+            self.write_entry(entry)?;
+        }
+        if let Some(summary) = &log.summary {
+            self.write_summary(summary)?;
+        }
+        Ok(())
+    }
+
+    /// Write whatever the parser produced; `NeedMoreData` writes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_advance(&mut self, advance: &RobocopyParseAdvance) -> eyre::Result<()> {
+        match advance {
+            RobocopyParseAdvance::NeedMoreData => Ok(()),
+            RobocopyParseAdvance::Header(header) => self.write_header(header),
+            RobocopyParseAdvance::LogEntry(entry) => self.write_entry(entry),
+            RobocopyParseAdvance::Summary(summary) => self.write_summary(summary),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_header(&mut self, header: &RobocopyHeader) -> eyre::Result<()> {
+        self.last_file = None;
+        self.write_text(&header.to_string())?;
+        self.newline()?;
+        self.newline()
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_summary(&mut self, summary: &RobocopySummary) -> eyre::Result<()> {
+        self.last_file = None;
+        self.newline()?;
+        self.write_text(&summary.to_string())?;
+        self.newline()?;
+        self.newline()
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_entry(&mut self, entry: &RobocopyLogEntry) -> eyre::Result<()> {
+        if let (Some(class), Some(size), Some(path), Some(percentages)) = (
+            entry.file_class(),
+            entry.file_size(),
+            entry.path(),
+            entry.percentages(),
+        ) {
+            let already_written = match &self.last_file {
+                Some((last_class, last_path, written))
+                    if *last_class == class
+                        && last_path == path
+                        && *written < percentages.len() =>
+                {
+                    *written
+                }
+                _ => {
+                    self.line(&format!(
+                        "\t    {label:<10}\t\t{size:>8}\t{path}",
+                        label = class.label(),
+                        size = format_robocopy_size(size),
+                        path = path.display()
+                    ))?;
+                    0
+                }
+            };
+            for pct in &percentages[already_written..] {
+                self.line(&format!("{pct:>3}%"))?;
+            }
+            self.last_file = Some((class, path.to_path_buf(), percentages.len()));
+            return Ok(());
+        }
+
+        self.last_file = None;
+        match entry {
+            RobocopyLogEntry::AccessDeniedError { when, path } => self.write_error(
+                when,
+                ACCESS_DENIED_CODE,
+                ACCESS_DENIED_OPERATION,
+                path,
+                ACCESS_DENIED_MESSAGE,
+            ),
+            RobocopyLogEntry::Error {
+                when,
+                code,
+                operation,
+                path,
+                message,
+            } => self.write_error(when, *code, operation, path, message),
+            RobocopyLogEntry::Directory {
+                class,
+                file_count,
+                path,
+            } => {
+                let label = match class {
+                    RobocopyDirectoryClass::Existing => "",
+                    RobocopyDirectoryClass::New => "  New Dir",
+                    RobocopyDirectoryClass::Extra => "*EXTRA Dir",
+                };
+                let width = DIRECTORY_COLUMN_WIDTH - label.len();
+                self.line(&format!(
+                    "\t{label}{file_count:>width$}\t{path}",
+                    path = path.display()
+                ))
+            }
+            RobocopyLogEntry::Retry { wait } => self.line(&format!(
+                "Waiting {} seconds... Retrying...",
+                wait.as_secs()
+            )),
+            RobocopyLogEntry::RetryLimitExceeded => {
+                self.line("ERROR: RETRY LIMIT EXCEEDED.")?;
+                self.newline()
+            }
+            RobocopyLogEntry::NewFile { .. } | RobocopyLogEntry::File { .. } => {
+                unreachable!("file entries are written above")
+            }
+        }
+    }
+
+    fn write_error(
+        &mut self,
+        when: &DateTime<Local>,
+        code: u32,
+        operation: &str,
+        path: &Path,
+        message: &str,
+    ) -> eyre::Result<()> {
+        self.line(&format!(
+            "{when} ERROR {code} (0x{code:08X}) {operation} {path}",
+            when = when.format("%Y/%m/%d %H:%M:%S"),
+            path = path.display()
+        ))?;
+        if !message.is_empty() {
+            self.line(message)?;
+        }
+        self.newline()
+    }
+
+    fn line(&mut self, text: &str) -> eyre::Result<()> {
+        self.write_text(text)?;
+        self.newline()
+    }
+
+    fn newline(&mut self) -> eyre::Result<()> {
+        self.inner.write_all(self.line_ending.as_str().as_bytes())?;
+        Ok(())
+    }
+
+    /// Write text that may contain `\n`, translating to the configured line ending.
+    fn write_text(&mut self, text: &str) -> eyre::Result<()> {
+        match self.line_ending {
+            LineEnding::Lf => self.inner.write_all(text.as_bytes())?,
+            LineEnding::CrLf => self
+                .inner
+                .write_all(text.replace('\n', "\r\n").as_bytes())?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+
+    /// Hand-written logs in robocopy's layout, not captures of real runs, so
+    /// passing shows the writer agrees with the parser rather than with
+    /// robocopy. The `/MT` progress and retry lines in particular are modelled
+    /// on `sample.txt`, not recorded.
+    const CORPUS: &[(&str, &str)] = &[
+        (
+            "multithreaded",
+            include_str!("corpus/synthetic/multithreaded.log"),
+        ),
+        (
+            "directories",
+            include_str!("corpus/synthetic/directories.log"),
+        ),
+        (
+            "appended_jobs",
+            include_str!("corpus/synthetic/appended_jobs.log"),
+        ),
+        ("retries", include_str!("corpus/synthetic/retries.log")),
+    ];
+
+    /// Feed `text` through the parser in `chunk_size` pieces and write every event straight back.
+    fn stream_roundtrip(
+        text: &str,
+        chunk_size: usize,
+        line_ending: LineEnding,
+    ) -> eyre::Result<String> {
+        let mut parser = RobocopyLogParser::new();
+        let mut writer = RobocopyLogWriter::new(Vec::new()).with_line_ending(line_ending);
+        for chunk in text.as_bytes().chunks(chunk_size) {
+            parser.accept_bytes(chunk);
+            loop {
+                let advance = parser.advance()?;
+                if advance == RobocopyParseAdvance::NeedMoreData {
+                    break;
+                }
+                writer.write_advance(&advance)?;
+            }
+        }
+        Ok(String::from_utf8(writer.into_inner())?)
+    }
+
+    #[test]
+    fn streaming_parse_then_render_is_lossless() -> eyre::Result<()> {
+        for (name, text) in CORPUS {
+            for chunk_size in [1, 7, 64, text.len()] {
+                let rendered = stream_roundtrip(text, chunk_size, LineEnding::Lf)?;
+                assert_eq!(
+                    &rendered, text,
+                    "{name} changed with chunk size {chunk_size}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_parse_then_render_is_lossless() -> eyre::Result<()> {
+        for (name, text) in CORPUS {
+            let crlf = text.replace('\n', "\r\n");
+            let rendered = stream_roundtrip(&crlf, 13, LineEnding::CrLf)?;
+            assert_eq!(rendered, crlf, "{name} changed when using CRLF");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn collected_log_renders_identically() -> eyre::Result<()> {
+        for (name, text) in CORPUS {
+            let logs = RobocopyLog::parse_all(text)?;
+            let rendered: String = logs.iter().map(ToString::to_string).collect();
+            assert_eq!(&rendered, text, "{name} changed after collecting");
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
+use eyre::WrapErr;
 use std::fmt::Display;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct RobocopyOptions {
     pub(crate) inner: String,
 }
@@ -19,3 +21,373 @@ impl FromStr for RobocopyOptions {
         })
     }
 }
+
+impl RobocopyOptions {
+    /// The file patterns robocopy lists ahead of the switches, e.g. `*.*`.
+    #[must_use]
+    pub fn file_patterns(&self) -> Vec<&str> {
+        self.inner
+            .split_whitespace()
+            .take_while(|token| !token.starts_with('/'))
+            .collect()
+    }
+
+    /// The switches, in the order robocopy lists them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a switch that takes a number has something else.
+    pub fn switches(&self) -> eyre::Result<Vec<RobocopySwitch>> {
+        RobocopySwitch::parse_all(
+            self.inner
+                .split_whitespace()
+                .skip_while(|token| !token.starts_with('/')),
+        )
+    }
+}
+
+/// One robocopy switch, with its value if it takes one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RobocopySwitch {
+    /// `/S`: copy subdirectories, but not empty ones.
+    Subdirectories,
+    /// `/E`: copy subdirectories, including empty ones.
+    EmptySubdirectories,
+    /// `/LEV:n`: only copy the top `n` levels of the source tree.
+    Levels(u32),
+    /// `/Z`: copy files in restartable mode.
+    Restartable,
+    /// `/B`: copy files in backup mode.
+    Backup,
+    /// `/ZB`: restartable mode, falling back to backup mode on access denied.
+    RestartableBackup,
+    /// `/J`: copy using unbuffered I/O.
+    Unbuffered,
+    /// `/COPY:flags`: what to copy for files, e.g. `DAT`.
+    Copy(String),
+    /// `/DCOPY:flags`: what to copy for directories, e.g. `DA`.
+    DirectoryCopy(String),
+    /// `/SEC`: copy files with security, `/COPY:DATS`.
+    Security,
+    /// `/COPYALL`: copy all file info, `/COPY:DATSOU`.
+    CopyAll,
+    /// `/NOCOPY`: copy no file info, for use with `/PURGE`.
+    NoCopy,
+    /// `/SECFIX`: fix security on all files, even skipped ones.
+    SecurityFix,
+    /// `/TIMFIX`: fix times on all files, even skipped ones.
+    TimeFix,
+    /// `/PURGE`: delete destination files and directories that no longer exist in the source.
+    Purge,
+    /// `/MIR`: mirror a directory tree, `/E` plus `/PURGE`.
+    Mirror,
+    /// `/MOV`: move files, deleting them from the source after copying.
+    MoveFiles,
+    /// `/MOVE`: move files and directories.
+    Move,
+    /// `/CREATE`: create the directory tree and zero-length files only.
+    Create,
+    /// `/FAT`: create destination files using 8.3 FAT names.
+    FatNames,
+    /// `/FFT`: assume FAT file times, with two-second granularity.
+    FatFileTimes,
+    /// `/DST`: compensate for one-hour daylight saving time differences.
+    DaylightSaving,
+    /// `/MT:n`: copy with `n` threads.
+    Threads(u32),
+    /// `/A`: copy only files with the archive attribute set.
+    Archive,
+    /// `/M`: copy only files with the archive attribute set, and reset it.
+    ArchiveReset,
+    /// `/XF names`: exclude files matching the names or wildcards.
+    ExcludeFiles(Vec<String>),
+    /// `/XD names`: exclude directories matching the names or wildcards.
+    ExcludeDirs(Vec<String>),
+    /// `/XC`: exclude changed files.
+    ExcludeChanged,
+    /// `/XN`: exclude newer files.
+    ExcludeNewer,
+    /// `/XO`: exclude older files.
+    ExcludeOlder,
+    /// `/XX`: exclude extra files and directories.
+    ExcludeExtra,
+    /// `/XL`: exclude lonely files and directories.
+    ExcludeLonely,
+    /// `/IS`: include same files.
+    IncludeSame,
+    /// `/IT`: include tweaked files.
+    IncludeTweaked,
+    /// `/MAX:n`: exclude files bigger than `n` bytes.
+    MaxSize(u64),
+    /// `/MIN:n`: exclude files smaller than `n` bytes.
+    MinSize(u64),
+    /// `/MAXAGE:n`: exclude files older than `n` days, or than a `YYYYMMDD` date.
+    MaxAge(u32),
+    /// `/MINAGE:n`: exclude files newer than `n` days, or than a `YYYYMMDD` date.
+    MinAge(u32),
+    /// `/XJ`: exclude junction points.
+    ExcludeJunctions,
+    /// `/R:n`: retries on failed copies.
+    Retries(u32),
+    /// `/W:n`: seconds to wait between retries.
+    Wait(u32),
+    /// `/L`: list only, without copying, deleting or timestamping.
+    List,
+    /// `/X`: report all extra files, not just selected ones.
+    ReportExtra,
+    /// `/V`: verbose output, showing skipped files.
+    Verbose,
+    /// `/TS`: include source file timestamps in the output.
+    Timestamps,
+    /// `/FP`: include full path names of files in the output.
+    FullPaths,
+    /// `/BYTES`: print sizes as bytes.
+    Bytes,
+    /// `/NS`: don't log file sizes.
+    NoSize,
+    /// `/NC`: don't log file classes.
+    NoClass,
+    /// `/NFL`: don't log file names.
+    NoFileList,
+    /// `/NDL`: don't log directory names.
+    NoDirList,
+    /// `/NP`: don't show the percentage copied.
+    NoProgress,
+    /// `/ETA`: show the estimated time of arrival of copied files.
+    Eta,
+    /// `/LOG:file`: write the log to a file, overwriting it.
+    Log(PathBuf),
+    /// `/LOG+:file`: append the log to a file.
+    AppendLog(PathBuf),
+    /// `/UNILOG:file`: write the log to a file as Unicode, overwriting it.
+    UnicodeLog(PathBuf),
+    /// `/UNILOG+:file`: append the log to a file as Unicode.
+    AppendUnicodeLog(PathBuf),
+    /// `/TEE`: write to the console as well as the log file.
+    Tee,
+    /// `/NJH`: no job header.
+    NoJobHeader,
+    /// `/NJS`: no job summary.
+    NoJobSummary,
+    /// `/UNICODE`: write the output as Unicode.
+    Unicode,
+    /// Any other switch, kept as written.
+    Other(String),
+}
+
+/// Switches without a value, and how robocopy spells them.
+const FLAGS: &[(RobocopySwitch, &str)] = &[
+    (RobocopySwitch::Subdirectories, "/S"),
+    (RobocopySwitch::EmptySubdirectories, "/E"),
+    (RobocopySwitch::Restartable, "/Z"),
+    (RobocopySwitch::Backup, "/B"),
+    (RobocopySwitch::RestartableBackup, "/ZB"),
+    (RobocopySwitch::Unbuffered, "/J"),
+    (RobocopySwitch::Security, "/SEC"),
+    (RobocopySwitch::CopyAll, "/COPYALL"),
+    (RobocopySwitch::NoCopy, "/NOCOPY"),
+    (RobocopySwitch::SecurityFix, "/SECFIX"),
+    (RobocopySwitch::TimeFix, "/TIMFIX"),
+    (RobocopySwitch::Purge, "/PURGE"),
+    (RobocopySwitch::Mirror, "/MIR"),
+    (RobocopySwitch::MoveFiles, "/MOV"),
+    (RobocopySwitch::Move, "/MOVE"),
+    (RobocopySwitch::Create, "/CREATE"),
+    (RobocopySwitch::FatNames, "/FAT"),
+    (RobocopySwitch::FatFileTimes, "/FFT"),
+    (RobocopySwitch::DaylightSaving, "/DST"),
+    (RobocopySwitch::Archive, "/A"),
+    (RobocopySwitch::ArchiveReset, "/M"),
+    (RobocopySwitch::ExcludeChanged, "/XC"),
+    (RobocopySwitch::ExcludeNewer, "/XN"),
+    (RobocopySwitch::ExcludeOlder, "/XO"),
+    (RobocopySwitch::ExcludeExtra, "/XX"),
+    (RobocopySwitch::ExcludeLonely, "/XL"),
+    (RobocopySwitch::IncludeSame, "/IS"),
+    (RobocopySwitch::IncludeTweaked, "/IT"),
+    (RobocopySwitch::ExcludeJunctions, "/XJ"),
+    (RobocopySwitch::List, "/L"),
+    (RobocopySwitch::ReportExtra, "/X"),
+    (RobocopySwitch::Verbose, "/V"),
+    (RobocopySwitch::Timestamps, "/TS"),
+    (RobocopySwitch::FullPaths, "/FP"),
+    (RobocopySwitch::Bytes, "/BYTES"),
+    (RobocopySwitch::NoSize, "/NS"),
+    (RobocopySwitch::NoClass, "/NC"),
+    (RobocopySwitch::NoFileList, "/NFL"),
+    (RobocopySwitch::NoDirList, "/NDL"),
+    (RobocopySwitch::NoProgress, "/NP"),
+    (RobocopySwitch::Eta, "/ETA"),
+    (RobocopySwitch::Tee, "/TEE"),
+    (RobocopySwitch::NoJobHeader, "/NJH"),
+    (RobocopySwitch::NoJobSummary, "/NJS"),
+    (RobocopySwitch::Unicode, "/UNICODE"),
+];
+
+impl RobocopySwitch {
+    /// Whether the switch is followed by a list of names, like `/XF *.tmp *.bak`.
+    fn takes_list(token: &str) -> bool {
+        token.eq_ignore_ascii_case("/XF") || token.eq_ignore_ascii_case("/XD")
+    }
+
+    /// Parse a run of switches, gathering the names after `/XF` and `/XD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a switch that takes a number has something else.
+    pub fn parse_all<'a>(tokens: impl IntoIterator<Item = &'a str>) -> eyre::Result<Vec<Self>> {
+        let mut tokens = tokens.into_iter().peekable();
+        let mut switches = Vec::new();
+        while let Some(token) = tokens.next() {
+            let mut values = Vec::new();
+            if Self::takes_list(token) {
+                while let Some(value) = tokens.next_if(|token| !token.starts_with('/')) {
+                    values.push(value.to_string());
+                }
+            }
+            switches.push(Self::parse(token, values)?);
+        }
+        Ok(switches)
+    }
+
+    /// Parse a switch as written on robocopy's command line; robocopy ignores case.
+    ///
+    /// `values` holds the names following `/XF` or `/XD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a switch that takes a number has something else.
+    pub fn parse(token: &str, values: Vec<String>) -> eyre::Result<Self> {
+        let upper = token.to_ascii_uppercase();
+        if let Some((switch, _)) = FLAGS.iter().find(|(_, name)| *name == upper) {
+            return Ok(switch.clone());
+        }
+        match upper.as_str() {
+            "/XF" => return Ok(Self::ExcludeFiles(values)),
+            "/XD" => return Ok(Self::ExcludeDirs(values)),
+            _ => {}
+        }
+        let Some((name, value)) = token.split_once(':') else {
+            return Ok(Self::Other(token.to_string()));
+        };
+        Ok(match name.to_ascii_uppercase().as_str() {
+            "/LEV" => Self::Levels(parse_number(token, value)?),
+            "/COPY" => Self::Copy(value.to_string()),
+            "/DCOPY" => Self::DirectoryCopy(value.to_string()),
+            "/MT" => Self::Threads(parse_number(token, value)?),
+            "/MAX" => Self::MaxSize(parse_number(token, value)?),
+            "/MIN" => Self::MinSize(parse_number(token, value)?),
+            "/MAXAGE" => Self::MaxAge(parse_number(token, value)?),
+            "/MINAGE" => Self::MinAge(parse_number(token, value)?),
+            "/R" => Self::Retries(parse_number(token, value)?),
+            "/W" => Self::Wait(parse_number(token, value)?),
+            "/LOG" => Self::Log(value.into()),
+            "/LOG+" => Self::AppendLog(value.into()),
+            "/UNILOG" => Self::UnicodeLog(value.into()),
+            "/UNILOG+" => Self::AppendUnicodeLog(value.into()),
+            _ => Self::Other(token.to_string()),
+        })
+    }
+
+    /// The switch as separate command line arguments; `/XF` and `/XD` are
+    /// followed by their names.
+    #[must_use]
+    pub fn tokens(&self) -> Vec<String> {
+        if let Some((_, name)) = FLAGS.iter().find(|(switch, _)| switch == self) {
+            return vec![(*name).to_string()];
+        }
+        let single = match self {
+            Self::ExcludeFiles(names) | Self::ExcludeDirs(names) => {
+                let switch = if matches!(self, Self::ExcludeFiles(_)) {
+                    "/XF"
+                } else {
+                    "/XD"
+                };
+                return std::iter::once(switch.to_string())
+                    .chain(names.iter().cloned())
+                    .collect();
+            }
+            Self::Levels(n) => format!("/LEV:{n}"),
+            Self::Copy(flags) => format!("/COPY:{flags}"),
+            Self::DirectoryCopy(flags) => format!("/DCOPY:{flags}"),
+            Self::Threads(n) => format!("/MT:{n}"),
+            Self::MaxSize(n) => format!("/MAX:{n}"),
+            Self::MinSize(n) => format!("/MIN:{n}"),
+            Self::MaxAge(n) => format!("/MAXAGE:{n}"),
+            Self::MinAge(n) => format!("/MINAGE:{n}"),
+            Self::Retries(n) => format!("/R:{n}"),
+            Self::Wait(n) => format!("/W:{n}"),
+            Self::Log(path) => format!("/LOG:{}", path.display()),
+            Self::AppendLog(path) => format!("/LOG+:{}", path.display()),
+            Self::UnicodeLog(path) => format!("/UNILOG:{}", path.display()),
+            Self::AppendUnicodeLog(path) => format!("/UNILOG+:{}", path.display()),
+            Self::Other(token) => token.clone(),
+            _ => unreachable!("switches without a value are in FLAGS"),
+        };
+        vec![single]
+    }
+
+    /// The log file this switch writes, if it is one of the `/LOG` family.
+    #[must_use]
+    pub fn log_file(&self) -> Option<&PathBuf> {
+        match self {
+            Self::Log(path)
+            | Self::AppendLog(path)
+            | Self::UnicodeLog(path)
+            | Self::AppendUnicodeLog(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(token: &str, value: &str) -> eyre::Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .wrap_err_with(|| format!("Invalid value in {token}"))
+}
+
+impl Display for RobocopySwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.tokens().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_switches_from_an_options_line() -> eyre::Result<()> {
+        let options: RobocopyOptions =
+            "*.* /TEE /s /E /DCOPY:DA /COPY:DAT /MT:16 /R:1000000 /W:5 /XF *.tmp *.bak /XD cache /FOO"
+                .parse()?;
+        assert_eq!(options.file_patterns(), ["*.*"]);
+        let switches = options.switches()?;
+        assert_eq!(
+            switches,
+            [
+                RobocopySwitch::Tee,
+                RobocopySwitch::Subdirectories,
+                RobocopySwitch::EmptySubdirectories,
+                RobocopySwitch::DirectoryCopy("DA".to_string()),
+                RobocopySwitch::Copy("DAT".to_string()),
+                RobocopySwitch::Threads(16),
+                RobocopySwitch::Retries(1_000_000),
+                RobocopySwitch::Wait(5),
+                RobocopySwitch::ExcludeFiles(vec!["*.tmp".to_string(), "*.bak".to_string()]),
+                RobocopySwitch::ExcludeDirs(vec!["cache".to_string()]),
+                RobocopySwitch::Other("/FOO".to_string()),
+            ]
+        );
+        let rendered: Vec<_> = switches.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered.join(" "),
+            "/TEE /S /E /DCOPY:DA /COPY:DAT /MT:16 /R:1000000 /W:5 /XF *.tmp *.bak /XD cache /FOO"
+        );
+        assert!("/MT:lots".parse::<RobocopyOptions>()?.switches().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use std::path::PathBuf;
+
+/// Turns the paths in log entries into full source paths.
+///
+/// Without `/FP` robocopy writes bare file names under the directory line
+/// they belong to, so files can only be placed by remembering the last
+/// directory seen. Directory and error lines always carry full paths.
+#[derive(Debug, Clone, Default)]
+pub struct RobocopyPathResolver {
+    current_directory: Option<String>,
+}
+
+impl RobocopyPathResolver {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The full path of an entry, updating the directory context as needed.
+    pub fn resolve(&mut self, entry: &RobocopyLogEntry) -> Option<PathBuf> {
+        let path = entry.path()?.to_string_lossy();
+        match entry {
+            RobocopyLogEntry::Directory { .. } => {
+                self.current_directory = Some(path.to_string());
+                Some(PathBuf::from(path.as_ref()))
+            }
+            RobocopyLogEntry::NewFile { .. } | RobocopyLogEntry::File { .. } => {
+                match &self.current_directory {
+                    Some(directory)
+                        if !is_absolute_windows_path(&path) && !path.starts_with('/') =>
+                    {
+                        Some(PathBuf::from(windows_join(directory, &path)))
+                    }
+                    _ => Some(PathBuf::from(path.as_ref())),
+                }
+            }
+            _ => Some(PathBuf::from(path.as_ref())),
+        }
+    }
+
+    /// The directory the next bare file name belongs to.
+    #[must_use]
+    pub fn current_directory(&self) -> Option<&str> {
+        self.current_directory.as_deref()
+    }
+}
+
+/// Whether `path` starts with a drive letter or is a UNC path, regardless of
+/// the platform the log is read on.
+#[must_use]
+pub fn is_absolute_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with(r"\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Join with a backslash; `Path::join` would use `/` off Windows. Logs
+/// written on Linux end their directory lines with `/` instead.
+fn windows_join(directory: &str, name: &str) -> String {
+    if directory.ends_with(['\\', '/']) {
+        format!("{directory}{name}")
+    } else {
+        format!("{directory}\\{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+    use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+    use uom::si::information::byte;
+    use uom::si::usize::Information;
+
+    #[test]
+    fn bare_names_resolve_against_the_last_directory() {
+        let mut resolver = RobocopyPathResolver::new();
+        let file = |path: &str| {
+            RobocopyLogEntry::file(
+                RobocopyFileClass::NewFile,
+                Information::new::<byte>(1),
+                PathBuf::from(path),
+                vec![],
+            )
+        };
+        assert_eq!(resolver.resolve(&file("a.txt")), Some("a.txt".into()));
+        let directory = RobocopyLogEntry::Directory {
+            class: RobocopyDirectoryClass::New,
+            file_count: 2,
+            path: PathBuf::from(r"J:\pool\0\"),
+        };
+        assert_eq!(resolver.resolve(&directory), Some(r"J:\pool\0\".into()));
+        assert_eq!(
+            resolver.resolve(&file("b.txt")),
+            Some(r"J:\pool\0\b.txt".into())
+        );
+        // Logs written with /FP already carry full paths
+        assert_eq!(
+            resolver.resolve(&file(r"J:\other\c.txt")),
+            Some(r"J:\other\c.txt".into())
+        );
+        assert_eq!(
+            resolver.resolve(&RobocopyLogEntry::RetryLimitExceeded),
+            None
+        );
+        // Logs written on Linux use forward slashes
+        resolver.resolve(&RobocopyLogEntry::Directory {
+            class: RobocopyDirectoryClass::Existing,
+            file_count: 1,
+            path: PathBuf::from("/srv/pool/"),
+        });
+        assert_eq!(
+            resolver.resolve(&file("d.txt")),
+            Some("/srv/pool/d.txt".into())
+        );
+        assert_eq!(
+            resolver.resolve(&file("/srv/other/e.txt")),
+            Some("/srv/other/e.txt".into())
+        );
+    }
+}
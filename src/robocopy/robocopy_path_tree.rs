@@ -0,0 +1,390 @@
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use std::collections::BTreeMap;
+use std::path::Path;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+/// Index of a node in a [`RobocopyPathTree`].
+pub type RobocopyPathTreeNodeId = usize;
+
+/// Totals for a node and everything below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RobocopyPathStats {
+    /// Bytes of files robocopy copied, i.e. classified as new, newer, older,
+    /// changed or modified and not given up on.
+    pub bytes_copied: Information,
+    files: [u64; RobocopyFileClass::ALL.len()],
+    /// Files robocopy gave up on after running out of retries.
+    pub failed_files: u64,
+    /// Error lines, counting every retry of the same failure.
+    pub errors: u64,
+}
+
+impl Default for RobocopyPathStats {
+    fn default() -> Self {
+        Self {
+            bytes_copied: Information::new::<byte>(0),
+            files: [0; RobocopyFileClass::ALL.len()],
+            failed_files: 0,
+            errors: 0,
+        }
+    }
+}
+
+impl RobocopyPathStats {
+    /// Files with the given classification.
+    #[must_use]
+    pub fn files(&self, class: RobocopyFileClass) -> u64 {
+        self.files[class as usize]
+    }
+
+    /// Files of any classification.
+    #[must_use]
+    pub fn file_count(&self) -> u64 {
+        self.files.iter().sum()
+    }
+
+    /// Non-zero file counts by classification.
+    pub fn files_by_class(&self) -> impl Iterator<Item = (RobocopyFileClass, u64)> + '_ {
+        RobocopyFileClass::ALL
+            .into_iter()
+            .map(|class| (class, self.files(class)))
+            .filter(|(_, count)| *count > 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobocopyPathNodeKind {
+    Directory,
+    File,
+}
+
+#[derive(Debug, Clone)]
+pub struct RobocopyPathTreeNode {
+    /// One path component; drives keep their backslash, e.g. `J:\`.
+    pub name: String,
+    pub kind: RobocopyPathNodeKind,
+    pub parent: Option<RobocopyPathTreeNodeId>,
+    /// Children by name, so they list in a stable order.
+    pub children: BTreeMap<String, RobocopyPathTreeNodeId>,
+    pub stats: RobocopyPathStats,
+}
+
+/// Every path in a log arranged as a tree, with totals per directory.
+///
+/// Bare file names are placed under the directory line before them. A file
+/// reported again for a progress tick or a retry is counted once; a file that
+/// ran out of retries moves from copied to failed.
+#[derive(Debug, Clone)]
+pub struct RobocopyPathTree {
+    nodes: Vec<RobocopyPathTreeNode>,
+    resolver: RobocopyPathResolver,
+    // The file robocopy is working on, with the stats it contributed
+    current_file: Option<(RobocopyPathTreeNodeId, RobocopyFileClass, Information)>,
+    // Whether the last error was about a directory rather than the current file
+    directory_error: bool,
+}
+
+impl Default for RobocopyPathTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![RobocopyPathTreeNode {
+                name: String::new(),
+                kind: RobocopyPathNodeKind::Directory,
+                parent: None,
+                children: BTreeMap::new(),
+                stats: RobocopyPathStats::default(),
+            }],
+            resolver: RobocopyPathResolver::new(),
+            current_file: None,
+            directory_error: false,
+        }
+    }
+}
+
+impl RobocopyPathTree {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a RobocopyLogEntry>) -> Self {
+        let mut tree = Self::new();
+        for entry in entries {
+            tree.push(entry);
+        }
+        tree
+    }
+
+    /// The unnamed node above every drive and share.
+    #[must_use]
+    pub fn root(&self) -> RobocopyPathTreeNodeId {
+        0
+    }
+
+    #[must_use]
+    pub fn node(&self, id: RobocopyPathTreeNodeId) -> &RobocopyPathTreeNode {
+        &self.nodes[id]
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Child nodes, directories first and then by name.
+    #[must_use]
+    pub fn children(&self, id: RobocopyPathTreeNodeId) -> Vec<RobocopyPathTreeNodeId> {
+        let mut children: Vec<_> = self.nodes[id].children.values().copied().collect();
+        children.sort_by_key(|child| self.nodes[*child].kind == RobocopyPathNodeKind::File);
+        children
+    }
+
+    /// The full path of a node.
+    #[must_use]
+    pub fn path(&self, id: RobocopyPathTreeNodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(node) = current.filter(|node| *node != self.root()) {
+            names.push(self.nodes[node].name.as_str());
+            current = self.nodes[node].parent;
+        }
+        names.reverse();
+        let mut path = String::new();
+        for name in names {
+            if !path.is_empty() && !path.ends_with('\\') {
+                path.push('\\');
+            }
+            path.push_str(name);
+        }
+        path
+    }
+
+    /// The node for a full path, if the log mentioned it.
+    #[must_use]
+    pub fn find(&self, path: &str) -> Option<RobocopyPathTreeNodeId> {
+        let mut node = self.root();
+        for component in components(path) {
+            node = *self.nodes[node].children.get(&component)?;
+        }
+        Some(node)
+    }
+
+    pub fn push(&mut self, entry: &RobocopyLogEntry) {
+        let path = self.resolver.resolve(entry);
+        match entry {
+            RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                let class = entry.file_class().unwrap_or(RobocopyFileClass::NewFile);
+                let Some(path) = path else { return };
+                let node = self.insert(&path, RobocopyPathNodeKind::File);
+                if let Some((current, current_class, counted)) = self.current_file
+                    && current == node
+                    && current_class == class
+                {
+                    // Another progress tick or retry of the same file
+                    if copies(class) {
+                        self.add(node, |stats| {
+                            stats.bytes_copied = stats.bytes_copied - counted + *size;
+                        });
+                        self.current_file = Some((node, class, *size));
+                    }
+                    return;
+                }
+                let counted = if copies(class) {
+                    *size
+                } else {
+                    Information::new::<byte>(0)
+                };
+                self.add(node, |stats| {
+                    stats.files[class as usize] += 1;
+                    stats.bytes_copied += counted;
+                });
+                self.current_file = Some((node, class, counted));
+            }
+            RobocopyLogEntry::Directory { .. } => {
+                if let Some(path) = path {
+                    self.insert(&path, RobocopyPathNodeKind::Directory);
+                }
+                self.current_file = None;
+            }
+            RobocopyLogEntry::Error { .. } | RobocopyLogEntry::AccessDeniedError { .. } => {
+                if let RobocopyLogEntry::Error { operation, .. } = entry {
+                    self.directory_error = operation.to_ascii_lowercase().contains("directory");
+                }
+                if let Some(path) = path {
+                    let kind = if path.to_string_lossy().ends_with('\\') {
+                        RobocopyPathNodeKind::Directory
+                    } else {
+                        RobocopyPathNodeKind::File
+                    };
+                    let node = self.insert(&path, kind);
+                    self.add(node, |stats| stats.errors += 1);
+                }
+            }
+            RobocopyLogEntry::Retry { .. } => {}
+            RobocopyLogEntry::RetryLimitExceeded => {
+                if std::mem::take(&mut self.directory_error) {
+                    return;
+                }
+                if let Some((node, _, counted)) = self.current_file.take() {
+                    self.add(node, |stats| {
+                        stats.bytes_copied -= counted;
+                        stats.failed_files += 1;
+                    });
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, path: &Path, kind: RobocopyPathNodeKind) -> RobocopyPathTreeNodeId {
+        let mut node = self.root();
+        let components = components(&path.to_string_lossy());
+        let last = components.len().saturating_sub(1);
+        for (index, component) in components.into_iter().enumerate() {
+            node = if let Some(child) = self.nodes[node].children.get(&component) {
+                *child
+            } else {
+                let child = self.nodes.len();
+                self.nodes[node].children.insert(component.clone(), child);
+                self.nodes.push(RobocopyPathTreeNode {
+                    name: component,
+                    kind: if index == last {
+                        kind
+                    } else {
+                        RobocopyPathNodeKind::Directory
+                    },
+                    parent: Some(node),
+                    children: BTreeMap::new(),
+                    stats: RobocopyPathStats::default(),
+                });
+                child
+            };
+        }
+        node
+    }
+
+    /// Apply a change to a node and every ancestor.
+    fn add(&mut self, node: RobocopyPathTreeNodeId, change: impl Fn(&mut RobocopyPathStats)) {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            change(&mut self.nodes[id].stats);
+            current = self.nodes[id].parent;
+        }
+    }
+}
+
+fn copies(class: RobocopyFileClass) -> bool {
+    class.summary_column() == RobocopySummaryColumn::Copied
+}
+
+/// Split a Windows path, keeping `J:\` and `\\server` as the first component.
+fn components(path: &str) -> Vec<String> {
+    let (first, rest) = if let Some(unc) = path.strip_prefix(r"\\") {
+        let (server, rest) = unc.split_once('\\').unwrap_or((unc, ""));
+        (format!(r"\\{server}"), rest)
+    } else {
+        match path.split_once('\\') {
+            Some((drive, rest)) if drive.ends_with(':') => (format!("{drive}\\"), rest),
+            _ => (String::new(), path),
+        }
+    };
+    let mut components: Vec<String> = Vec::new();
+    if !first.is_empty() {
+        components.push(first);
+    }
+    components.extend(
+        rest.split('\\')
+            .filter(|component| !component.is_empty())
+            .map(str::to_string),
+    );
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+
+    #[test]
+    fn splits_windows_paths() {
+        assert_eq!(components(r"J:\pool\0\"), [r"J:\", "pool", "0"]);
+        assert_eq!(
+            components(r"\\nas\share\a.txt"),
+            [r"\\nas", "share", "a.txt"]
+        );
+        assert_eq!(components("a.txt"), ["a.txt"]);
+    }
+
+    #[test]
+    fn root_totals_match_the_summary_counts() -> eyre::Result<()> {
+        for seed in 0..10 {
+            let config = RobocopyLogGeneratorConfig {
+                seed,
+                files: 120,
+                error_percent: 20,
+                retries: u32::try_from(seed % 3)?,
+                ..Default::default()
+            };
+            let log = RobocopyLogGenerator::new(config).generate_log();
+            let tree = RobocopyPathTree::from_entries(&log.parts);
+            let counts = RobocopySummaryCounts::from_entries(&log.parts);
+            let root = tree.node(tree.root()).stats;
+            assert_eq!(root.bytes_copied, counts.bytes.copied, "seed {seed}");
+            assert_eq!(root.failed_files, counts.files.failed, "seed {seed}");
+            let copied: u64 = root
+                .files_by_class()
+                .filter(|(class, _)| copies(*class))
+                .map(|(_, count)| count)
+                .sum();
+            assert_eq!(
+                copied - root.failed_files,
+                counts.files.copied,
+                "seed {seed}"
+            );
+
+            // Every directory's totals are the sum of its children's
+            for id in 0..tree.len() {
+                let node = tree.node(id);
+                if node.children.is_empty() {
+                    continue;
+                }
+                let children = tree.children(id);
+                let bytes: usize = children
+                    .iter()
+                    .map(|child| tree.node(*child).stats.bytes_copied.get::<byte>())
+                    .sum();
+                assert_eq!(node.stats.bytes_copied.get::<byte>(), bytes);
+                let errors: u64 = children.iter().map(|c| tree.node(*c).stats.errors).sum();
+                assert!(node.stats.errors >= errors);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn places_sample_files_under_their_directory() -> eyre::Result<()> {
+        let logs = RobocopyLog::parse_all(include_str!("sample.txt"))?;
+        let tree = RobocopyPathTree::from_entries(&logs[0].parts);
+        let directory = tree
+            .find(r"J:\nas-ds418j_1.hbk\Pool\0\17\")
+            .expect("directory is in the tree");
+        let node = tree.node(directory);
+        assert_eq!(node.kind, RobocopyPathNodeKind::Directory);
+        assert!(node.stats.files(RobocopyFileClass::NewFile) > 0);
+        assert_eq!(tree.path(directory), r"J:\nas-ds418j_1.hbk\Pool\0\17");
+        let drive = tree.find(r"J:\").expect("drive is in the tree");
+        assert_eq!(tree.node(drive).stats.errors, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use humansize::BINARY;
+use humansize::format_size;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+/// Rate samples closer together than this are merged into the next one, since
+/// the parser hands over a whole chunk of entries at the same instant.
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Tracks how many bytes a running job has copied and how fast.
+///
+/// Feed it entries as they are parsed along with the time they were seen.
+/// Bytes are credited from the `NewFile`-style entries robocopy copies: files
+/// that reached 100% (or were logged with `/NP`) count in full and the file in
+/// flight counts by its latest percentage. The rate is an exponentially
+/// weighted average so a single slow file does not swing the ETA around.
+#[derive(Debug, Clone)]
+pub struct RobocopyProgress {
+    total_bytes: Option<Information>,
+    half_life: Duration,
+    completed_bytes: Information,
+    completed_files: u64,
+    current_file: Option<RobocopyInFlightFile>,
+    last_sample: Option<(Instant, Information)>,
+    bytes_per_second: Option<f64>,
+    finished: bool,
+}
+
+/// The file robocopy is copying right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyInFlightFile {
+    pub path: PathBuf,
+    pub size: Information,
+    /// The latest percentage, or `None` when the log was written with `/NP`.
+    pub percent: Option<u8>,
+}
+
+impl RobocopyInFlightFile {
+    /// The bytes of this file already at the destination.
+    #[must_use]
+    pub fn bytes_done(&self) -> Information {
+        let size = self.size.get::<byte>();
+        let percent = usize::from(self.percent.unwrap_or(0).min(100));
+        Information::new::<byte>(size / 100 * percent + size % 100 * percent / 100)
+    }
+}
+
+/// A point-in-time view of a [`RobocopyProgress`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobocopyProgressSnapshot {
+    pub bytes_done: Information,
+    pub total_bytes: Option<Information>,
+    pub files_done: u64,
+    pub current_file: Option<RobocopyInFlightFile>,
+    /// Smoothed copy rate; `None` until two samples far enough apart were seen.
+    pub bytes_per_second: Option<f64>,
+    /// Time left at the current rate; `None` without a total or a rate.
+    pub eta: Option<Duration>,
+    /// Whether the job summary has been seen.
+    pub finished: bool,
+}
+
+impl Default for RobocopyProgress {
+    fn default() -> Self {
+        Self {
+            total_bytes: None,
+            half_life: Duration::from_secs(10),
+            completed_bytes: Information::new::<byte>(0),
+            completed_files: 0,
+            current_file: None,
+            last_sample: None,
+            bytes_per_second: None,
+            finished: false,
+        }
+    }
+}
+
+impl RobocopyProgress {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measure the ETA against the total size of the source, e.g. from a
+    /// previous `/L` listing or the size of the source drive.
+    #[must_use]
+    pub fn with_total_bytes(mut self, total_bytes: Option<Information>) -> Self {
+        self.total_bytes = total_bytes;
+        self
+    }
+
+    /// How quickly old rate samples stop mattering; ten seconds by default.
+    #[must_use]
+    pub fn with_half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life.max(Duration::from_millis(1));
+        self
+    }
+
+    pub fn set_total_bytes(&mut self, total_bytes: Option<Information>) {
+        self.total_bytes = total_bytes;
+    }
+
+    /// Forget everything counted so far, keeping the total and half-life.
+    pub fn reset(&mut self) {
+        *self = Self::new()
+            .with_total_bytes(self.total_bytes)
+            .with_half_life(self.half_life);
+    }
+
+    /// Handle anything the parser produced.
+    ///
+    /// A header starts a new job, so counters start over; a summary marks the
+    /// job finished.
+    pub fn push_advance(&mut self, advance: &RobocopyParseAdvance, now: Instant) {
+        match advance {
+            RobocopyParseAdvance::NeedMoreData => self.tick(now),
+            RobocopyParseAdvance::Header(_) => {
+                *self = Self::new()
+                    .with_total_bytes(self.total_bytes)
+                    .with_half_life(self.half_life);
+                self.tick(now);
+            }
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry, now),
+            RobocopyParseAdvance::Summary(_) => {
+                self.complete_current_file();
+                self.finished = true;
+                self.tick(now);
+            }
+        }
+    }
+
+    pub fn push(&mut self, entry: &RobocopyLogEntry, now: Instant) {
+        match entry {
+            RobocopyLogEntry::NewFile { size, path, .. }
+            | RobocopyLogEntry::File { size, path, .. } => {
+                let copied = entry
+                    .file_class()
+                    .is_some_and(|class| class.summary_column() == RobocopySummaryColumn::Copied);
+                let percent = entry.percentages().and_then(|p| p.last().copied());
+                if let Some(current) = &mut self.current_file
+                    && current.path == *path
+                    && copied
+                {
+                    current.size = *size;
+                    current.percent = percent.or(current.percent);
+                } else {
+                    self.complete_current_file();
+                    if copied {
+                        self.current_file = Some(RobocopyInFlightFile {
+                            path: path.clone(),
+                            size: *size,
+                            percent,
+                        });
+                    }
+                }
+            }
+            RobocopyLogEntry::Directory { .. } | RobocopyLogEntry::AccessDeniedError { .. } => {
+                self.complete_current_file();
+            }
+            RobocopyLogEntry::Error { .. } | RobocopyLogEntry::RetryLimitExceeded => {
+                // The partial copy is thrown away; a retry starts the file over
+                self.current_file = None;
+            }
+            RobocopyLogEntry::Retry { .. } => {}
+        }
+        self.tick(now);
+    }
+
+    /// Take a rate sample without new entries, so a stalled job slows down.
+    pub fn tick(&mut self, now: Instant) {
+        let done = self.bytes_done();
+        let Some((then, then_done)) = self.last_sample else {
+            self.last_sample = Some((now, done));
+            return;
+        };
+        let elapsed = now.saturating_duration_since(then);
+        if elapsed < MIN_SAMPLE_INTERVAL {
+            return;
+        }
+        #[allow(clippy::cast_precision_loss, reason = "rates are approximate")]
+        let instant_rate = done.get::<byte>().saturating_sub(then_done.get::<byte>()) as f64
+            / elapsed.as_secs_f64();
+        let weight = 1.0 - 0.5_f64.powf(elapsed.as_secs_f64() / self.half_life.as_secs_f64());
+        self.bytes_per_second = Some(match self.bytes_per_second {
+            Some(rate) => rate + weight * (instant_rate - rate),
+            None => instant_rate,
+        });
+        self.last_sample = Some((now, done));
+    }
+
+    /// Forget the rate measured so far, e.g. after catching up on a log that
+    /// was written long before it was opened.
+    pub fn restart_clock(&mut self, now: Instant) {
+        self.bytes_per_second = None;
+        self.last_sample = Some((now, self.bytes_done()));
+    }
+
+    /// Bytes copied, including the file in flight.
+    #[must_use]
+    pub fn bytes_done(&self) -> Information {
+        self.completed_bytes
+            + self.current_file.as_ref().map_or(
+                Information::new::<byte>(0),
+                RobocopyInFlightFile::bytes_done,
+            )
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> RobocopyProgressSnapshot {
+        let bytes_done = self.bytes_done();
+        let eta = self.total_bytes.and_then(|total| {
+            let remaining = total.get::<byte>().saturating_sub(bytes_done.get::<byte>());
+            if remaining == 0 || self.finished {
+                return Some(Duration::ZERO);
+            }
+            let rate = self.bytes_per_second.filter(|rate| *rate > 0.0)?;
+            #[allow(clippy::cast_precision_loss, reason = "rates are approximate")]
+            Duration::try_from_secs_f64(remaining as f64 / rate).ok()
+        });
+        RobocopyProgressSnapshot {
+            bytes_done,
+            total_bytes: self.total_bytes,
+            files_done: self.completed_files,
+            current_file: self.current_file.clone(),
+            bytes_per_second: self.bytes_per_second,
+            eta,
+            finished: self.finished,
+        }
+    }
+
+    fn complete_current_file(&mut self) {
+        if let Some(file) = self.current_file.take()
+            && file.percent.is_none_or(|percent| percent >= 100)
+        {
+            self.completed_bytes += file.size;
+            self.completed_files += 1;
+        }
+    }
+}
+
+impl RobocopyProgressSnapshot {
+    /// Share of the total copied, between 0 and 1.
+    #[must_use]
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total_bytes?.get::<byte>();
+        if total == 0 {
+            return Some(1.0);
+        }
+        #[allow(clippy::cast_precision_loss, reason = "only used for display")]
+        Some((self.bytes_done.get::<byte>() as f64 / total as f64).min(1.0))
+    }
+}
+
+impl Display for RobocopyProgressSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_size(self.bytes_done.get::<byte>(), BINARY))?;
+        if let Some(total) = self.total_bytes {
+            write!(f, " of {}", format_size(total.get::<byte>(), BINARY))?;
+        }
+        if let Some(fraction) = self.fraction() {
+            write!(f, " ({:.1}%)", fraction * 100.0)?;
+        }
+        write!(f, ", {} files", self.files_done)?;
+        if let Some(rate) = self.bytes_per_second {
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                reason = "rates are non-negative and approximate"
+            )]
+            let rate = rate as u64;
+            write!(f, ", {}/s", format_size(rate, BINARY))?;
+        }
+        if self.finished {
+            write!(f, ", finished")?;
+        } else if let Some(eta) = self.eta {
+            let eta = Duration::from_secs(eta.as_secs());
+            write!(f, ", ETA {}", humantime::format_duration(eta))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+
+    fn mib(n: usize) -> Information {
+        Information::new::<byte>(n << 20)
+    }
+
+    fn new_file(path: &str, size: Information, percent: u8) -> RobocopyLogEntry {
+        RobocopyLogEntry::file(
+            RobocopyFileClass::NewFile,
+            size,
+            PathBuf::from(path),
+            vec![percent],
+        )
+    }
+
+    #[test]
+    fn credits_completed_and_in_flight_bytes() {
+        let start = Instant::now();
+        let mut progress = RobocopyProgress::new().with_total_bytes(Some(mib(400)));
+        progress.push(&new_file("a", mib(100), 50), start);
+        assert_eq!(progress.bytes_done(), mib(50));
+        progress.push(&new_file("a", mib(100), 100), start);
+        progress.push(&new_file("b", mib(200), 25), start);
+        assert_eq!(progress.bytes_done(), mib(150));
+        let snapshot = progress.snapshot();
+        assert_eq!(snapshot.files_done, 1);
+        assert_eq!(snapshot.fraction(), Some(0.375));
+
+        // Skipped files are not copied and a failed copy is thrown away
+        let same = RobocopyLogEntry::file(RobocopyFileClass::Same, mib(300), "c".into(), vec![]);
+        progress.push(&same, start);
+        assert_eq!(progress.bytes_done(), mib(100));
+        progress.push(&new_file("d", mib(10), 90), start);
+        progress.push(&RobocopyLogEntry::RetryLimitExceeded, start);
+        assert_eq!(progress.bytes_done(), mib(100));
+    }
+
+    #[test]
+    fn smooths_rate_and_estimates_time_left() {
+        let start = Instant::now();
+        let mut progress = RobocopyProgress::new()
+            .with_total_bytes(Some(mib(1000)))
+            .with_half_life(Duration::from_secs(1));
+        progress.push(&new_file("a", mib(1000), 0), start);
+        for second in 1..=10 {
+            progress.push(
+                &new_file("a", mib(1000), second * 5),
+                start + Duration::from_secs(u64::from(second)),
+            );
+        }
+        let snapshot = progress.snapshot();
+        let rate = snapshot.bytes_per_second.expect("rate is measured");
+        assert!((rate - 50.0 * 1024.0 * 1024.0).abs() < 1.0, "{rate}");
+        assert_eq!(snapshot.eta.map(|eta| eta.as_secs()), Some(10));
+
+        // Nothing copied for a while brings the rate down and the ETA up
+        progress.tick(start + Duration::from_secs(13));
+        let stalled = progress.snapshot();
+        assert!(stalled.bytes_per_second.expect("rate is measured") < rate / 4.0);
+        assert!(stalled.eta > snapshot.eta);
+
+        // Without a total there is nothing to measure against
+        progress.set_total_bytes(None);
+        assert_eq!(progress.snapshot().eta, None);
+    }
+
+    #[test]
+    fn restarting_the_clock_ignores_history() {
+        let start = Instant::now();
+        let mut progress = RobocopyProgress::new();
+        progress.push(&new_file("a", mib(1000), 100), start);
+        progress.push(
+            &new_file("b", mib(1000), 100),
+            start + Duration::from_secs(1),
+        );
+        progress.restart_clock(start + Duration::from_secs(1));
+        assert_eq!(progress.snapshot().bytes_per_second, None);
+        progress.tick(start + Duration::from_secs(2));
+        assert_eq!(progress.snapshot().bytes_per_second, Some(0.0));
+    }
+}
@@ -0,0 +1,968 @@
+use crate::glob_match::glob_match;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_MESSAGE;
+use crate::robocopy::robocopy_log_entry::ACCESS_DENIED_OPERATION;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_size::parse_robocopy_size;
+use bytesize::ByteSize;
+use clap::ValueEnum;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
+use uom::si::information::byte;
+
+/// A filter over log entries, such as `kind == "error" and code in (5, 32)`
+/// or `size > 1GiB and path ~ "*.bucket"`.
+///
+/// Comparisons are `field op value`, combined with `and`, `or`, `not` and
+/// parentheses. Operators are `==`, `!=`, `<`, `<=`, `>`, `>=`, `~` (glob
+/// match with `*` and `?`), `!~`, `in (...)` and `not in (...)`. Text
+/// comparisons ignore case. Strings take single or double quotes and have no
+/// escapes, so Windows paths can be written as they appear in the log.
+/// Numbers may carry a size suffix like `500MB` or `1.5GiB`, or one of
+/// robocopy's own `k`, `m`, `g` and `t`, so `1.5 m` copied from a log means
+/// what robocopy meant by it.
+///
+/// `kind` and `class` are checked against the kinds and classes that exist
+/// when compared with `==`, `!=` or `in`, so a typo is an error rather than
+/// an empty result.
+///
+/// A comparison on a field the entry does not have, such as `size` on an
+/// error, is false; `not` turns it true.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobocopyQuery {
+    expr: Expr,
+}
+
+/// A field of an entry that queries can compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobocopyQueryField {
+    /// `file`, `directory`, `error`, `retry` or `retry-limit`.
+    Kind,
+    /// The label robocopy writes, like `New File` or `*EXTRA Dir`, or the
+    /// short name used by `--class`, like `new-file` or `extra`.
+    Class,
+    /// The full source path, with bare file names joined to their directory.
+    Path,
+    /// File size in bytes.
+    Size,
+    /// Last progress percentage of a file.
+    Percent,
+    /// Win32 error code.
+    Code,
+    /// What robocopy was doing when an error happened, like `Copying File`.
+    Operation,
+    /// The error message robocopy printed.
+    Message,
+    /// Files robocopy found in a directory.
+    Files,
+    /// Seconds robocopy waits before a retry.
+    Wait,
+}
+
+impl RobocopyQueryField {
+    pub const ALL: [RobocopyQueryField; 10] = [
+        RobocopyQueryField::Kind,
+        RobocopyQueryField::Class,
+        RobocopyQueryField::Path,
+        RobocopyQueryField::Size,
+        RobocopyQueryField::Percent,
+        RobocopyQueryField::Code,
+        RobocopyQueryField::Operation,
+        RobocopyQueryField::Message,
+        RobocopyQueryField::Files,
+        RobocopyQueryField::Wait,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            RobocopyQueryField::Kind => "kind",
+            RobocopyQueryField::Class => "class",
+            RobocopyQueryField::Path => "path",
+            RobocopyQueryField::Size => "size",
+            RobocopyQueryField::Percent => "percent",
+            RobocopyQueryField::Code => "code",
+            RobocopyQueryField::Operation => "operation",
+            RobocopyQueryField::Message => "message",
+            RobocopyQueryField::Files => "files",
+            RobocopyQueryField::Wait => "wait",
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            RobocopyQueryField::Size
+                | RobocopyQueryField::Percent
+                | RobocopyQueryField::Code
+                | RobocopyQueryField::Files
+                | RobocopyQueryField::Wait
+        )
+    }
+
+    fn number(self, entry: &RobocopyLogEntry) -> Option<i128> {
+        match (self, entry) {
+            (RobocopyQueryField::Size, _) => {
+                entry.file_size().map(|size| size.get::<byte>() as i128)
+            }
+            (RobocopyQueryField::Percent, _) => entry
+                .percentages()
+                .and_then(|percentages| percentages.last())
+                .map(|percent| i128::from(*percent)),
+            (RobocopyQueryField::Code, _) => entry.error_code().map(i128::from),
+            (RobocopyQueryField::Files, RobocopyLogEntry::Directory { file_count, .. }) => {
+                // Extra directories have no count
+                (*file_count >= 0).then_some(i128::from(*file_count))
+            }
+            (RobocopyQueryField::Wait, RobocopyLogEntry::Retry { wait }) => {
+                Some(i128::from(wait.as_secs()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The text values of this field; classes have a label and a short name.
+    fn text<'a>(self, entry: &'a RobocopyLogEntry, path: Option<&'a str>) -> Vec<Cow<'a, str>> {
+        match self {
+            RobocopyQueryField::Kind => vec![Cow::Borrowed(entry_kind(entry))],
+            RobocopyQueryField::Class => class_names(entry),
+            RobocopyQueryField::Path => path
+                .map(Cow::Borrowed)
+                .or_else(|| entry.path().map(|path| path.to_string_lossy()))
+                .into_iter()
+                .collect(),
+            RobocopyQueryField::Operation => match entry {
+                RobocopyLogEntry::Error { operation, .. } => vec![Cow::Borrowed(operation)],
+                RobocopyLogEntry::AccessDeniedError { .. } => {
+                    vec![Cow::Borrowed(ACCESS_DENIED_OPERATION)]
+                }
+                _ => Vec::new(),
+            },
+            RobocopyQueryField::Message => match entry {
+                RobocopyLogEntry::Error { message, .. } => vec![Cow::Borrowed(message)],
+                RobocopyLogEntry::AccessDeniedError { .. } => {
+                    vec![Cow::Borrowed(ACCESS_DENIED_MESSAGE)]
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Display for RobocopyQueryField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Every value of the `kind` field.
+pub const ENTRY_KINDS: [&str; 5] = ["file", "directory", "error", "retry", "retry-limit"];
+
+const DIRECTORY_CLASSES: [RobocopyDirectoryClass; 3] = [
+    RobocopyDirectoryClass::Existing,
+    RobocopyDirectoryClass::New,
+    RobocopyDirectoryClass::Extra,
+];
+
+/// The value of the `kind` field.
+#[must_use]
+pub fn entry_kind(entry: &RobocopyLogEntry) -> &'static str {
+    match entry {
+        RobocopyLogEntry::NewFile { .. } | RobocopyLogEntry::File { .. } => "file",
+        RobocopyLogEntry::Directory { .. } => "directory",
+        RobocopyLogEntry::AccessDeniedError { .. } | RobocopyLogEntry::Error { .. } => "error",
+        RobocopyLogEntry::Retry { .. } => "retry",
+        RobocopyLogEntry::RetryLimitExceeded => "retry-limit",
+    }
+}
+
+fn class_names(entry: &RobocopyLogEntry) -> Vec<Cow<'static, str>> {
+    if let Some(class) = entry.file_class() {
+        let mut names = vec![Cow::Borrowed(class.label())];
+        if let Some(value) = class.to_possible_value() {
+            names.push(Cow::Owned(value.get_name().to_string()));
+        }
+        return names;
+    }
+    match entry {
+        RobocopyLogEntry::Directory { class, .. } => [class.label(), directory_class_name(*class)]
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .map(Cow::Borrowed)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The short name of a directory class, like `--class` uses for files.
+fn directory_class_name(class: RobocopyDirectoryClass) -> &'static str {
+    match class {
+        RobocopyDirectoryClass::Existing => "existing",
+        RobocopyDirectoryClass::New => "new",
+        RobocopyDirectoryClass::Extra => "extra",
+    }
+}
+
+/// Fail when a `kind` or `class` a query compares with does not exist.
+fn check_known_value(field: RobocopyQueryField, text: &str, column: usize) -> eyre::Result<()> {
+    match field {
+        RobocopyQueryField::Kind
+            if !ENTRY_KINDS
+                .iter()
+                .any(|kind| kind.eq_ignore_ascii_case(text)) =>
+        {
+            eyre::bail!(
+                "Unknown kind `{text}` at column {}; kinds are {}",
+                column + 1,
+                ENTRY_KINDS.join(", ")
+            )
+        }
+        RobocopyQueryField::Class if !is_known_class(text) => eyre::bail!(
+            "Unknown class `{text}` at column {}; classes are {}, or the labels robocopy writes like `New File`",
+            column + 1,
+            class_short_names().join(", ")
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// The short names of every file and directory class.
+fn class_short_names() -> Vec<String> {
+    RobocopyFileClass::value_variants()
+        .iter()
+        .filter_map(ValueEnum::to_possible_value)
+        .map(|value| value.get_name().to_string())
+        .chain(
+            DIRECTORY_CLASSES
+                .into_iter()
+                .map(|class| directory_class_name(class).to_string()),
+        )
+        .collect()
+}
+
+/// Whether `text` names a class, by its short name or its label.
+fn is_known_class(text: &str) -> bool {
+    let expected = normalize_class(text);
+    let labels = RobocopyFileClass::value_variants()
+        .iter()
+        .map(|class| class.label())
+        .chain(
+            DIRECTORY_CLASSES
+                .into_iter()
+                .map(RobocopyDirectoryClass::label),
+        )
+        .filter(|label| !label.is_empty())
+        .map(str::to_string);
+    class_short_names()
+        .into_iter()
+        .chain(labels)
+        .any(|name| normalize_class(&name) == expected)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Glob,
+    NotGlob,
+}
+
+impl CompareOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Glob => "~",
+            CompareOp::NotGlob => "!~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Text(String),
+    Number(i128),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(RobocopyQueryField, CompareOp, Value),
+    In(RobocopyQueryField, Vec<Value>),
+}
+
+impl Expr {
+    fn matches(&self, entry: &RobocopyLogEntry, path: Option<&str>) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(entry, path) && right.matches(entry, path),
+            Expr::Or(left, right) => left.matches(entry, path) || right.matches(entry, path),
+            Expr::Not(inner) => !inner.matches(entry, path),
+            Expr::Compare(field, op, value) => compare(*field, *op, value, entry, path),
+            Expr::In(field, values) => values
+                .iter()
+                .any(|value| compare(*field, CompareOp::Eq, value, entry, path)),
+        }
+    }
+}
+
+fn compare(
+    field: RobocopyQueryField,
+    op: CompareOp,
+    value: &Value,
+    entry: &RobocopyLogEntry,
+    path: Option<&str>,
+) -> bool {
+    match value {
+        Value::Number(expected) => {
+            let Some(actual) = field.number(entry) else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => actual == *expected,
+                CompareOp::Ne => actual != *expected,
+                CompareOp::Lt => actual < *expected,
+                CompareOp::Le => actual <= *expected,
+                CompareOp::Gt => actual > *expected,
+                CompareOp::Ge => actual >= *expected,
+                // Rejected by the parser
+                CompareOp::Glob | CompareOp::NotGlob => false,
+            }
+        }
+        Value::Text(expected) => {
+            let actual = field.text(entry, path);
+            if actual.is_empty() {
+                return false;
+            }
+            let equal = |candidate: &Cow<'_, str>| {
+                if field == RobocopyQueryField::Class {
+                    normalize_class(candidate) == normalize_class(expected)
+                } else {
+                    candidate.eq_ignore_ascii_case(expected)
+                }
+            };
+            match op {
+                CompareOp::Eq => actual.iter().any(equal),
+                CompareOp::Ne => !actual.iter().any(equal),
+                CompareOp::Glob => actual
+                    .iter()
+                    .any(|candidate| glob_match(expected, candidate)),
+                CompareOp::NotGlob => !actual
+                    .iter()
+                    .any(|candidate| glob_match(expected, candidate)),
+                // Rejected by the parser
+                CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge => false,
+            }
+        }
+    }
+}
+
+/// `New File`, `new-file` and `newfile` all name the same class.
+fn normalize_class(class: &str) -> String {
+    class
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl RobocopyQuery {
+    /// Whether an entry passes the filter; `path` is its resolved full path.
+    #[must_use]
+    pub fn matches(&self, entry: &RobocopyLogEntry, path: Option<&str>) -> bool {
+        self.expr.matches(entry, path)
+    }
+}
+
+impl FromStr for RobocopyQuery {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = QueryParser {
+            tokens: &tokens,
+            position: 0,
+            end: s.chars().count(),
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            eyre::bail!("Unexpected {} at column {}", token.kind, token.column + 1);
+        }
+        Ok(Self { expr })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Text(String),
+    Number(i128),
+    Op(CompareOp),
+    Open,
+    Close,
+    Comma,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "`{word}`"),
+            TokenKind::Text(text) => write!(f, "\"{text}\""),
+            TokenKind::Number(number) => write!(f, "{number}"),
+            TokenKind::Op(op) => write!(f, "`{}`", op.symbol()),
+            TokenKind::Open => write!(f, "`(`"),
+            TokenKind::Close => write!(f, "`)`"),
+            TokenKind::Comma => write!(f, "`,`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    /// Character offset in the query, for error messages.
+    column: usize,
+}
+
+fn tokenize(query: &str) -> eyre::Result<Vec<Token>> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' => TokenKind::Comma,
+            '"' | '\'' => {
+                let Some(length) = chars[i + 1..].iter().position(|next| *next == c) else {
+                    eyre::bail!("Unterminated string starting at column {}", column + 1);
+                };
+                let text: String = chars[i + 1..i + 1 + length].iter().collect();
+                i += length + 2;
+                tokens.push(Token {
+                    kind: TokenKind::Text(text),
+                    column,
+                });
+                continue;
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                let next = chars.get(i + 1).copied();
+                let (op, width) = match (c, next) {
+                    ('=', Some('=')) => (CompareOp::Eq, 2),
+                    ('!', Some('=')) => (CompareOp::Ne, 2),
+                    ('!', Some('~')) => (CompareOp::NotGlob, 2),
+                    ('<', Some('=')) => (CompareOp::Le, 2),
+                    ('>', Some('=')) => (CompareOp::Ge, 2),
+                    ('<', _) => (CompareOp::Lt, 1),
+                    ('>', _) => (CompareOp::Gt, 1),
+                    ('~', _) => (CompareOp::Glob, 1),
+                    _ => eyre::bail!("Unknown operator at column {}", column + 1),
+                };
+                i += width;
+                tokens.push(Token {
+                    kind: TokenKind::Op(op),
+                    column,
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                let (number, length) = tokenize_number(&chars[i..], column)?;
+                i += length;
+                tokens.push(Token {
+                    kind: TokenKind::Number(number),
+                    column,
+                });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|next| !(next.is_alphanumeric() || matches!(next, '_' | '-')))
+                    .unwrap_or(chars.len() - i);
+                let word: String = chars[i..i + length].iter().collect();
+                i += length;
+                tokens.push(Token {
+                    kind: TokenKind::Word(word),
+                    column,
+                });
+                continue;
+            }
+            _ => eyre::bail!("Unexpected `{c}` at column {}", column + 1),
+        };
+        i += 1;
+        tokens.push(Token { kind, column });
+    }
+    Ok(tokens)
+}
+
+/// Read the number at the start of `chars`, with its unit if it has one,
+/// returning it and how many characters it took.
+fn tokenize_number(chars: &[char], column: usize) -> eyre::Result<(i128, usize)> {
+    let run = |from: usize, accept: fn(&char) -> bool| {
+        chars[from..]
+            .iter()
+            .position(|next| !accept(next))
+            .unwrap_or(chars.len() - from)
+    };
+    let mantissa_length = run(0, |next| next.is_ascii_digit() || *next == '.');
+    let spaces = run(mantissa_length, |next| *next == ' ');
+    let unit_start = mantissa_length + spaces;
+    let unit_length = run(unit_start, char::is_ascii_alphanumeric);
+    let mantissa: String = chars[..mantissa_length].iter().collect();
+    let unit: String = chars[unit_start..unit_start + unit_length].iter().collect();
+    // A unit written apart from the number, as robocopy does, is only taken
+    // when it is one; otherwise it is the next word
+    let with_unit = parse_number(&mantissa, &unit);
+    let (number, length) = if spaces > 0 && with_unit.is_none() {
+        (parse_number(&mantissa, ""), mantissa_length)
+    } else {
+        (with_unit, unit_start + unit_length)
+    };
+    let Some(number) = number else {
+        let text: String = chars[..length].iter().collect();
+        eyre::bail!(
+            "Expected a number or a size like 1GiB or 1.5 m at column {}, got `{text}`",
+            column + 1
+        );
+    };
+    Ok((number, length))
+}
+
+/// A number with an optional size unit: robocopy's `k`, `m`, `g` and `t`
+/// as robocopy writes them, and anything else as `bytesize` reads it, like
+/// `500MB` or `1.5GiB`. Fractions need a unit.
+fn parse_number(mantissa: &str, unit: &str) -> Option<i128> {
+    if unit.is_empty() {
+        return mantissa.parse().ok();
+    }
+    if unit.len() == 1
+        && let Some(size) = parse_robocopy_size(&format!("{mantissa} {unit}"))
+    {
+        return Some(i128::from(u64::try_from(size.get::<byte>()).ok()?));
+    }
+    let size = format!("{mantissa}{unit}").parse::<ByteSize>().ok()?;
+    Some(i128::from(size.as_u64()))
+}
+
+struct QueryParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    /// Length of the query, for errors at its end.
+    end: usize,
+}
+
+impl QueryParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self, expected: &str) -> eyre::Result<&Token> {
+        let Some(token) = self.tokens.get(self.position) else {
+            eyre::bail!("Expected {expected} at column {}", self.end + 1);
+        };
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword)
+        );
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> eyre::Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> eyre::Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> eyre::Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if matches!(
+            self.peek(),
+            Some(Token {
+                kind: TokenKind::Open,
+                ..
+            })
+        ) {
+            self.position += 1;
+            let expr = self.or()?;
+            self.close()?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn close(&mut self) -> eyre::Result<()> {
+        let token = self.next("`)`")?;
+        if token.kind != TokenKind::Close {
+            eyre::bail!(
+                "Expected `)` at column {}, got {}",
+                token.column + 1,
+                token.kind
+            );
+        }
+        Ok(())
+    }
+
+    fn comparison(&mut self) -> eyre::Result<Expr> {
+        let token = self.next("a field")?;
+        let field = match &token.kind {
+            TokenKind::Word(word) => RobocopyQueryField::ALL
+                .into_iter()
+                .find(|field| field.name().eq_ignore_ascii_case(word))
+                .ok_or_else(|| {
+                    let fields: Vec<&str> = RobocopyQueryField::ALL
+                        .iter()
+                        .map(|field| field.name())
+                        .collect();
+                    eyre::eyre!(
+                        "Unknown field `{word}` at column {}; fields are {}",
+                        token.column + 1,
+                        fields.join(", ")
+                    )
+                })?,
+            other => eyre::bail!(
+                "Expected a field at column {}, got {other}",
+                token.column + 1
+            ),
+        };
+        let negated = self.keyword("not");
+        if self.keyword("in") {
+            let open = self.next("`(`")?;
+            if open.kind != TokenKind::Open {
+                eyre::bail!(
+                    "Expected `(` at column {}, got {}",
+                    open.column + 1,
+                    open.kind
+                );
+            }
+            let mut values = vec![self.value(field, true)?];
+            loop {
+                let token = self.next("`,` or `)`")?;
+                match token.kind {
+                    TokenKind::Comma => values.push(self.value(field, true)?),
+                    TokenKind::Close => break,
+                    _ => eyre::bail!(
+                        "Expected `,` or `)` at column {}, got {}",
+                        token.column + 1,
+                        token.kind
+                    ),
+                }
+            }
+            let expr = Expr::In(field, values);
+            return Ok(if negated {
+                Expr::Not(Box::new(expr))
+            } else {
+                expr
+            });
+        }
+        if negated {
+            let column = self.peek().map_or(self.end, |token| token.column);
+            eyre::bail!("Expected `in` at column {}", column + 1);
+        }
+        let token = self.next("an operator")?;
+        let TokenKind::Op(op) = token.kind else {
+            eyre::bail!(
+                "Expected an operator after `{field}` at column {}, got {}",
+                token.column + 1,
+                token.kind
+            );
+        };
+        let column = token.column;
+        let value = self.value(field, matches!(op, CompareOp::Eq | CompareOp::Ne))?;
+        match (&value, op) {
+            (Value::Number(_), CompareOp::Glob | CompareOp::NotGlob) => {
+                eyre::bail!(
+                    "`{}` at column {} needs text, `{field}` is a number",
+                    op.symbol(),
+                    column + 1
+                )
+            }
+            (Value::Text(_), CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge) => {
+                eyre::bail!(
+                    "`{}` at column {} needs a number, `{field}` is text",
+                    op.symbol(),
+                    column + 1
+                )
+            }
+            _ => Ok(Expr::Compare(field, op, value)),
+        }
+    }
+
+    /// The value of a comparison; `exact` when it is compared for equality
+    /// rather than as a glob.
+    fn value(&mut self, field: RobocopyQueryField, exact: bool) -> eyre::Result<Value> {
+        let token = self.next("a value")?;
+        match (&token.kind, field.is_numeric()) {
+            (TokenKind::Number(number), true) => Ok(Value::Number(*number)),
+            (TokenKind::Text(text), false) => {
+                if exact {
+                    check_known_value(field, text, token.column)?;
+                }
+                Ok(Value::Text(text.clone()))
+            }
+            (TokenKind::Number(_), false) => eyre::bail!(
+                "`{field}` is text; quote the value at column {}",
+                token.column + 1
+            ),
+            (TokenKind::Text(_), true) => eyre::bail!(
+                "`{field}` is a number; expected a number at column {}",
+                token.column + 1
+            ),
+            (other, _) => eyre::bail!(
+                "Expected a value at column {}, got {other}",
+                token.column + 1
+            ),
+        }
+    }
+}
+
+/// An entry that passed a query, with its resolved full path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyQueryMatch {
+    pub entry: RobocopyLogEntry,
+    pub path: Option<String>,
+}
+
+/// Runs a query over a stream of entries, finished or live.
+///
+/// The parser reports a file again for every progress tick, and robocopy
+/// writes it again for every retry; each file is tested once, when it
+/// reaches 100% or robocopy moves on to something else, so sizes and
+/// percentages are final.
+#[derive(Debug)]
+pub struct RobocopyQueryRunner {
+    query: RobocopyQuery,
+    resolver: RobocopyPathResolver,
+    current_file: Option<CurrentFile>,
+}
+
+#[derive(Debug)]
+struct CurrentFile {
+    entry: RobocopyLogEntry,
+    path: Option<String>,
+    tested: bool,
+}
+
+impl RobocopyQueryRunner {
+    #[must_use]
+    pub fn new(query: RobocopyQuery) -> Self {
+        Self {
+            query,
+            resolver: RobocopyPathResolver::new(),
+            current_file: None,
+        }
+    }
+
+    /// Feed a parser advance; a new header starts a new job.
+    pub fn push_advance(&mut self, advance: &RobocopyParseAdvance) -> Vec<RobocopyQueryMatch> {
+        match advance {
+            RobocopyParseAdvance::Header(_) => self.finish().into_iter().collect(),
+            RobocopyParseAdvance::LogEntry(entry) => self.push(entry),
+            RobocopyParseAdvance::Summary(_) | RobocopyParseAdvance::NeedMoreData => Vec::new(),
+        }
+    }
+
+    /// Feed an entry and get back the entries that are now final and match.
+    pub fn push(&mut self, entry: &RobocopyLogEntry) -> Vec<RobocopyQueryMatch> {
+        let path = self
+            .resolver
+            .resolve(entry)
+            .map(|path| path.to_string_lossy().into_owned());
+        let mut matches = Vec::new();
+        if entry.file_class().is_some() {
+            if let Some(current) = &mut self.current_file
+                && current.entry.file_class() == entry.file_class()
+                && current.path == path
+            {
+                if !current.tested {
+                    current.entry = entry.clone();
+                }
+            } else {
+                matches.extend(self.test_current_file());
+                self.current_file = Some(CurrentFile {
+                    entry: entry.clone(),
+                    path,
+                    tested: false,
+                });
+            }
+            if entry.percentages().and_then(<[u8]>::last) == Some(&100) {
+                matches.extend(self.test_current_file());
+            }
+            return matches;
+        }
+        matches.extend(self.test_current_file());
+        if matches!(entry, RobocopyLogEntry::Directory { .. }) {
+            self.current_file = None;
+        }
+        if self.query.matches(entry, path.as_deref()) {
+            matches.push(RobocopyQueryMatch {
+                entry: entry.clone(),
+                path,
+            });
+        }
+        matches
+    }
+
+    /// Test the file in progress, if any, and forget the directory context.
+    pub fn finish(&mut self) -> Option<RobocopyQueryMatch> {
+        let found = self.test_current_file();
+        self.current_file = None;
+        self.resolver = RobocopyPathResolver::new();
+        found
+    }
+
+    fn test_current_file(&mut self) -> Option<RobocopyQueryMatch> {
+        let current = self.current_file.as_mut()?;
+        if std::mem::replace(&mut current.tested, true) {
+            return None;
+        }
+        self.query
+            .matches(&current.entry, current.path.as_deref())
+            .then(|| RobocopyQueryMatch {
+                entry: current.entry.clone(),
+                path: current.path.clone(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+
+    fn run(query: &str, text: &str) -> eyre::Result<Vec<String>> {
+        let mut runner = RobocopyQueryRunner::new(query.parse()?);
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(text);
+        let mut found = Vec::new();
+        loop {
+            let advance = parser.advance()?;
+            found.extend(runner.push_advance(&advance));
+            if matches!(advance, RobocopyParseAdvance::NeedMoreData) {
+                break;
+            }
+        }
+        found.extend(runner.finish());
+        Ok(found
+            .into_iter()
+            .map(|found| found.path.unwrap_or_default())
+            .collect())
+    }
+
+    #[test]
+    fn filters_sample_entries() -> eyre::Result<()> {
+        let sample = include_str!("sample.txt");
+        assert_eq!(
+            run(r#"kind == "error" and code in (5, 32)"#, sample)?,
+            [r"J:\$RECYCLE.BIN\", r"J:\System Volume Information\"]
+        );
+        // Each bucket once, however many progress ticks it had
+        let buckets = run(r#"size > 1MiB and path ~ "*.bucket""#, sample)?;
+        assert!(!buckets.is_empty());
+        assert!(buckets.iter().all(|path| path.ends_with(".bucket")));
+        let unique: std::collections::HashSet<_> = buckets.iter().collect();
+        assert_eq!(unique.len(), buckets.len());
+
+        let logs = RobocopyLog::parse_all(sample)?;
+        let files = logs[0]
+            .parts
+            .iter()
+            .filter(|entry| entry.file_class().is_some())
+            .count();
+        assert_eq!(run("kind == 'file'", sample)?.len(), files);
+        assert_eq!(
+            run(
+                "class == 'new-file' and not (size < 100 or path ~ '*.index')",
+                sample
+            )?
+            .len(),
+            run(
+                "class == 'New File' and size >= 100 and path !~ '*.INDEX'",
+                sample
+            )?
+            .len()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in [
+            "",
+            "size >",
+            "colour == 'red'",
+            "size ~ 5",
+            "path > 'a'",
+            "code == '5'",
+            "kind == 'error' and",
+            "code in (5, 32",
+            "(kind == 'file'",
+            "path == 'unterminated",
+            "size > 12 parsecs",
+            "size > 1.5",
+            "size > 1.5 parsecs",
+            "kind == 'dir'",
+            "kind in ('file', 'eror')",
+            "class != 'new-fiel'",
+        ] {
+            assert!(query.parse::<RobocopyQuery>().is_err(), "{query}");
+        }
+    }
+
+    #[test]
+    fn parses_fractional_sizes_and_checks_kinds() -> eyre::Result<()> {
+        let parse =
+            |query: &str| -> eyre::Result<Expr> { Ok(query.parse::<RobocopyQuery>()?.expr) };
+        assert_eq!(parse("size >= 1.5 m")?, parse("size >= 1572864")?);
+        assert_eq!(parse("size >= 1.5m")?, parse("size >= 1572864")?);
+        assert_eq!(parse("size > 1.5GiB")?, parse("size > 1610612736")?);
+        assert_eq!(parse("size > 1.5 GB")?, parse("size > 1500000000")?);
+        assert!(parse("size > 1 and kind == 'file'").is_ok());
+        // Labels and short names are both classes; globs are not checked
+        assert!(parse("class in ('New File', 'new', '*EXTRA Dir', 'extra')").is_ok());
+        assert!(parse("kind ~ 'dir*' and class ~ 'whatever'").is_ok());
+        let error = parse("kind == 'dir'").unwrap_err().to_string();
+        assert!(error.contains("directory, error"), "{error}");
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_summary_tally::COLUMN_NAMES;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+/// What one run did with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RobocopyRunFile {
+    pub class: RobocopyFileClass,
+    pub size: Information,
+}
+
+impl RobocopyRunFile {
+    /// Whether robocopy copies files of this class.
+    #[must_use]
+    pub fn copies(&self) -> bool {
+        self.class.summary_column() == RobocopySummaryColumn::Copied
+    }
+}
+
+/// Every file a run logged and every path it gave up on, by full source path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobocopyRunPaths {
+    pub files: BTreeMap<String, RobocopyRunFile>,
+    /// Files and directories robocopy gave up on after its last retry, and
+    /// directories it could not read at all.
+    pub failed: BTreeSet<String>,
+}
+
+impl RobocopyRunPaths {
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a RobocopyLogEntry>) -> Self {
+        let mut paths = Self::default();
+        let mut resolver = RobocopyPathResolver::new();
+        let mut current_file: Option<String> = None;
+        // The directory the last error was about, if it was about one
+        let mut directory_error: Option<String> = None;
+        for entry in entries {
+            let path = resolver
+                .resolve(entry)
+                .map(|path| path.to_string_lossy().into_owned());
+            match entry {
+                RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                    let Some(path) = path else { continue };
+                    let class = entry.file_class().unwrap_or(RobocopyFileClass::NewFile);
+                    // Progress ticks and retries rewrite the same file
+                    paths
+                        .files
+                        .insert(path.clone(), RobocopyRunFile { class, size: *size });
+                    current_file = Some(path);
+                    directory_error = None;
+                }
+                RobocopyLogEntry::Directory { .. } => {
+                    current_file = None;
+                    directory_error = None;
+                }
+                RobocopyLogEntry::AccessDeniedError { .. } => {
+                    paths.failed.extend(path);
+                    directory_error = None;
+                }
+                RobocopyLogEntry::Error { operation, .. } => {
+                    directory_error =
+                        path.filter(|_| operation.to_ascii_lowercase().contains("directory"));
+                }
+                RobocopyLogEntry::Retry { .. } => {}
+                RobocopyLogEntry::RetryLimitExceeded => {
+                    if let Some(directory) = directory_error.take() {
+                        paths.failed.insert(directory);
+                    } else if let Some(file) = current_file.take() {
+                        paths.failed.insert(file);
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    /// Whether the run copied `path` without giving up on it.
+    #[must_use]
+    pub fn copied(&self, path: &str) -> bool {
+        !self.failed.contains(path) && self.files.get(path).is_some_and(RobocopyRunFile::copies)
+    }
+}
+
+/// A header field that differs between the runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyHeaderChange {
+    /// `Source`, `Dest`, `Files` or `Options`.
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+/// A file the second run copied that the first did not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyCopiedFile {
+    pub path: String,
+    pub class: RobocopyFileClass,
+    pub size: Information,
+}
+
+/// A file both runs logged with different classifications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyReclassifiedFile {
+    pub path: String,
+    pub before: RobocopyFileClass,
+    pub after: RobocopyFileClass,
+}
+
+/// A summary cell that differs between the runs; bytes are counted in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyTotalsChange {
+    /// `Dirs`, `Files` or `Bytes`.
+    pub row: &'static str,
+    /// `Total`, `Copied`, `Skipped`, `Mismatch`, `FAILED` or `Extras`.
+    pub column: &'static str,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl RobocopyTotalsChange {
+    #[must_use]
+    pub fn delta(&self) -> i128 {
+        i128::from(self.after) - i128::from(self.before)
+    }
+}
+
+/// What changed between two runs of a job, usually the same nightly job on
+/// consecutive nights.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobocopyRunDiff {
+    pub header: Vec<RobocopyHeaderChange>,
+    /// Options only the second run used, in the order it lists them.
+    pub options_added: Vec<String>,
+    /// Options only the first run used.
+    pub options_removed: Vec<String>,
+    pub copied: Vec<RobocopyCopiedFile>,
+    /// Paths the second run gave up on that the first did not.
+    pub newly_failed: Vec<String>,
+    /// Paths the first run gave up on that the second did not.
+    pub recovered: Vec<String>,
+    pub reclassified: Vec<RobocopyReclassifiedFile>,
+    /// Summary cells, rebuilt from the entries, that differ.
+    pub totals: Vec<RobocopyTotalsChange>,
+}
+
+impl RobocopyRunDiff {
+    #[must_use]
+    pub fn new(before: &RobocopyLog, after: &RobocopyLog) -> Self {
+        let mut diff = Self::default();
+
+        for (field, old, new) in [
+            (
+                "Source",
+                before.header.source.display().to_string(),
+                after.header.source.display().to_string(),
+            ),
+            (
+                "Dest",
+                before.header.dest.display().to_string(),
+                after.header.dest.display().to_string(),
+            ),
+            (
+                "Files",
+                before.header.files.to_string(),
+                after.header.files.to_string(),
+            ),
+            (
+                "Options",
+                before.header.options.to_string(),
+                after.header.options.to_string(),
+            ),
+        ] {
+            if old.trim() != new.trim() {
+                diff.header.push(RobocopyHeaderChange {
+                    field,
+                    before: old,
+                    after: new,
+                });
+            }
+        }
+        let old_options = option_tokens(&before.header.options.to_string());
+        let new_options = option_tokens(&after.header.options.to_string());
+        diff.options_added = difference(&new_options, &old_options);
+        diff.options_removed = difference(&old_options, &new_options);
+
+        let old_paths = RobocopyRunPaths::from_entries(&before.parts);
+        let new_paths = RobocopyRunPaths::from_entries(&after.parts);
+        for (path, file) in &new_paths.files {
+            if new_paths.copied(path) && !old_paths.copied(path) {
+                diff.copied.push(RobocopyCopiedFile {
+                    path: path.clone(),
+                    class: file.class,
+                    size: file.size,
+                });
+            }
+            if let Some(old) = old_paths.files.get(path)
+                && old.class != file.class
+            {
+                diff.reclassified.push(RobocopyReclassifiedFile {
+                    path: path.clone(),
+                    before: old.class,
+                    after: file.class,
+                });
+            }
+        }
+        diff.newly_failed = new_paths
+            .failed
+            .difference(&old_paths.failed)
+            .cloned()
+            .collect();
+        diff.recovered = old_paths
+            .failed
+            .difference(&new_paths.failed)
+            .cloned()
+            .collect();
+
+        let old_counts = RobocopySummaryCounts::from_entries(&before.parts);
+        let new_counts = RobocopySummaryCounts::from_entries(&after.parts);
+        let bytes =
+            |counts: &RobocopySummaryCounts| counts.bytes.values().map(|v| v.get::<byte>() as u64);
+        for (row, old, new) in [
+            ("Dirs", old_counts.dirs.values(), new_counts.dirs.values()),
+            (
+                "Files",
+                old_counts.files.values(),
+                new_counts.files.values(),
+            ),
+            ("Bytes", bytes(&old_counts), bytes(&new_counts)),
+        ] {
+            for ((column, before), after) in COLUMN_NAMES.into_iter().zip(old).zip(new) {
+                if before != after {
+                    diff.totals.push(RobocopyTotalsChange {
+                        row,
+                        column,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        diff
+    }
+
+    /// Whether the runs did the same thing with the same options.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The switches in an `Options :` line, upper-cased since robocopy ignores case.
+fn option_tokens(options: &str) -> Vec<String> {
+    options
+        .split_whitespace()
+        .filter(|token| token.starts_with('/'))
+        .map(str::to_ascii_uppercase)
+        .collect()
+}
+
+fn difference(left: &[String], right: &[String]) -> Vec<String> {
+    left.iter()
+        .filter(|token| !right.contains(token))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn reports_what_changed_between_runs() -> eyre::Result<()> {
+        let before = RobocopyLog::parse_all(include_str!("sample.txt"))?.remove(0);
+        assert!(RobocopyRunDiff::new(&before, &before).is_empty());
+
+        let mut after = before.clone();
+        after.header.options =
+            "*.* /TEE /MIR /DCOPY:DA /COPY:DAT /MT:16 /R:1000000 /W:5".parse()?;
+        // The recycle bin became readable
+        after.parts.remove(0);
+        // A file was unchanged this time
+        let index = after
+            .parts
+            .iter()
+            .position(|entry| {
+                entry
+                    .path()
+                    .is_some_and(|path| path.to_string_lossy().ends_with("0.index"))
+            })
+            .expect("sample has 0.index");
+        let size = after.parts[index].file_size().expect("is a file");
+        after.parts[index] = RobocopyLogEntry::file(
+            RobocopyFileClass::Same,
+            size,
+            PathBuf::from(r"J:\nas-ds418j_1.hbk\Pool\0\17\0.index"),
+            vec![],
+        );
+        // One new file copied, another given up on
+        let copied = r"J:\nas-ds418j_1.hbk\Pool\0\17\2.index";
+        let failed = r"J:\nas-ds418j_1.hbk\Pool\0\17\2.bucket";
+        let one_kib = Information::new::<byte>(1024);
+        after.parts.extend([
+            RobocopyLogEntry::file(
+                RobocopyFileClass::NewFile,
+                one_kib,
+                copied.into(),
+                vec![100],
+            ),
+            RobocopyLogEntry::file(RobocopyFileClass::NewFile, one_kib, failed.into(), vec![]),
+            RobocopyLogEntry::Error {
+                when: *before.header.started.as_datetime(),
+                code: 32,
+                operation: "Copying File".to_string(),
+                path: failed.into(),
+                message: "The process cannot access the file.".to_string(),
+            },
+            RobocopyLogEntry::RetryLimitExceeded,
+        ]);
+
+        let diff = RobocopyRunDiff::new(&before, &after);
+        assert_eq!(diff.header.len(), 1);
+        assert_eq!(diff.header[0].field, "Options");
+        assert_eq!(diff.options_added, ["/MIR"]);
+        assert_eq!(diff.options_removed, ["/S", "/E"]);
+        assert_eq!(
+            diff.copied,
+            [RobocopyCopiedFile {
+                path: copied.to_string(),
+                class: RobocopyFileClass::NewFile,
+                size: one_kib,
+            }]
+        );
+        assert_eq!(diff.newly_failed, [failed]);
+        assert_eq!(diff.recovered, [r"J:\$RECYCLE.BIN\"]);
+        assert_eq!(diff.reclassified.len(), 1);
+        assert_eq!(diff.reclassified[0].after, RobocopyFileClass::Same);
+        let failed_files = diff
+            .totals
+            .iter()
+            .find(|change| change.row == "Files" && change.column == "FAILED")
+            .expect("failed files changed");
+        assert_eq!(failed_files.delta(), 1);
+        assert!(diff.totals.iter().any(|change| change.row == "Dirs"
+            && change.column == "FAILED"
+            && change.delta() == -1));
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+use crate::robocopy::robocopy_command::RobocopyCommand;
+use crate::robocopy::robocopy_exit_code::RobocopyExitCode;
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_options::RobocopySwitch;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::robocopy::robocopy_progress::RobocopyProgressSnapshot;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryCounts;
+use crate::robocopy::robocopy_summary_tally::RobocopySummaryTally;
+use crossbeam_channel::RecvTimeoutError;
+use eyre::OptionExt;
+use eyre::WrapErr;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// One job robocopy ran, as parsed from its output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobocopyRunJob {
+    pub header: RobocopyHeader,
+    /// The summary robocopy printed; missing when it was killed or ran with `/NJS`.
+    pub summary: Option<RobocopySummary>,
+    /// Totals rebuilt from the entries.
+    pub counts: RobocopySummaryCounts,
+}
+
+impl RobocopyRunJob {
+    /// The exit code this job implies, from its summary if it printed one.
+    #[must_use]
+    pub fn expected_exit_code(&self) -> RobocopyExitCode {
+        self.summary.as_ref().map_or_else(
+            || RobocopyExitCode::from_counts(&self.counts),
+            RobocopyExitCode::from_summary,
+        )
+    }
+}
+
+/// What a supervised robocopy run did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobocopyRunResult {
+    pub exit_code: RobocopyExitCode,
+    pub jobs: Vec<RobocopyRunJob>,
+    /// Progress of the last job when the process exited.
+    pub progress: RobocopyProgressSnapshot,
+    pub elapsed: Duration,
+}
+
+impl RobocopyRunResult {
+    /// The exit code the last job's output implies, to check against the real one.
+    #[must_use]
+    pub fn expected_exit_code(&self) -> Option<RobocopyExitCode> {
+        self.jobs.last().map(RobocopyRunJob::expected_exit_code)
+    }
+}
+
+/// Runs a [`RobocopyCommand`] and parses its output as it is written.
+///
+/// The output is read from the `/LOG` file when the command writes one and
+/// from standard output otherwise, so progress is reported the same way in
+/// both cases.
+#[derive(Debug, Clone)]
+pub struct RobocopyRunner {
+    command: RobocopyCommand,
+    report_interval: Duration,
+    stdout_file: Option<PathBuf>,
+}
+
+impl RobocopyRunner {
+    #[must_use]
+    pub fn new(command: RobocopyCommand) -> Self {
+        Self {
+            command,
+            report_interval: Duration::from_secs(1),
+            stdout_file: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_report_interval(mut self, interval: Duration) -> Self {
+        self.report_interval = interval;
+        self
+    }
+
+    /// Also copy everything the process writes to standard output into a file.
+    #[must_use]
+    pub fn with_stdout_file(mut self, path: Option<PathBuf>) -> Self {
+        self.stdout_file = path;
+        self
+    }
+
+    /// Spawn the process and wait for it, handing a progress snapshot to
+    /// `report` every report interval.
+    ///
+    /// An existing `/LOG` file is truncated first, as robocopy would, so old
+    /// jobs are not mistaken for this one; with `/LOG+` only what the run
+    /// appends is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process cannot be spawned, its output cannot
+    /// be read or parsed, or it was killed without an exit code.
+    pub fn run(
+        &self,
+        mut report: impl FnMut(&RobocopyProgressSnapshot),
+    ) -> eyre::Result<RobocopyRunResult> {
+        self.command.validate()?;
+        let mut log = self.open_log()?;
+        let mut stdout_file = self
+            .stdout_file
+            .as_ref()
+            .map(|path| {
+                File::create(path).wrap_err_with(|| format!("Failed to create {}", path.display()))
+            })
+            .transpose()?;
+        let started = Instant::now();
+        let mut child = self
+            .command
+            .command()
+            .stdout(Stdio::piped())
+            .spawn()
+            .wrap_err_with(|| format!("Failed to start {}", self.command.executable().display()))?;
+        let mut stdout = child.stdout.take().ok_or_eyre("Child has no stdout")?;
+        let (tx, rx) = crossbeam_channel::unbounded::<Vec<u8>>();
+        let reader = std::thread::spawn(move || {
+            let mut buf = vec![0u8; READ_CHUNK_SIZE];
+            loop {
+                match stdout.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => {
+                        if tx.send(buf[..read].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let mut output = RobocopyRunOutput::new();
+        let mut last_report = Instant::now();
+        loop {
+            match rx.recv_timeout(self.report_interval.saturating_sub(last_report.elapsed())) {
+                Ok(chunk) => {
+                    if let Some(file) = &mut stdout_file {
+                        file.write_all(&chunk)?;
+                    }
+                    if log.is_none() {
+                        output.accept(&chunk)?;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if let Some(log) = &mut log {
+                output.accept(&log.read_new()?)?;
+            }
+            if last_report.elapsed() >= self.report_interval {
+                output.progress.tick(Instant::now());
+                report(&output.progress.snapshot());
+                last_report = Instant::now();
+            }
+        }
+        let status = child.wait()?;
+        let _ = reader.join();
+        if let Some(log) = &mut log {
+            output.accept(&log.read_new()?)?;
+        }
+        let code = status.code().ok_or_eyre(format!(
+            "{} was terminated without an exit code",
+            self.command.executable().display()
+        ))?;
+        let progress = output.progress.snapshot();
+        report(&progress);
+        Ok(RobocopyRunResult {
+            exit_code: RobocopyExitCode(u32::try_from(code).unwrap_or(u32::MAX)),
+            jobs: output.finish(),
+            progress,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    fn open_log(&self) -> eyre::Result<Option<LogFollower>> {
+        let Some(switch) = self
+            .command
+            .switches()
+            .iter()
+            .find(|switch| switch.log_file().is_some())
+        else {
+            return Ok(None);
+        };
+        let path = switch.log_file().cloned().unwrap_or_default();
+        let append = matches!(
+            switch,
+            RobocopySwitch::AppendLog(_) | RobocopySwitch::AppendUnicodeLog(_)
+        );
+        let offset = if append {
+            std::fs::metadata(&path).map_or(0, |metadata| metadata.len())
+        } else {
+            File::create(&path).wrap_err_with(|| format!("Failed to create {}", path.display()))?;
+            0
+        };
+        Ok(Some(LogFollower {
+            path,
+            file: None,
+            offset,
+        }))
+    }
+}
+
+/// Reads what robocopy appends to its `/LOG` file, opening it once it exists.
+struct LogFollower {
+    path: PathBuf,
+    file: Option<File>,
+    offset: u64,
+}
+
+impl LogFollower {
+    fn read_new(&mut self) -> eyre::Result<Vec<u8>> {
+        if self.file.is_none() {
+            match File::open(&self.path) {
+                Ok(file) => self.file = Some(file),
+                Err(_) => return Ok(Vec::new()),
+            }
+        }
+        let Some(file) = &mut self.file else {
+            return Ok(Vec::new());
+        };
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .wrap_err_with(|| format!("Failed to read {}", self.path.display()))?;
+        self.offset += bytes.len() as u64;
+        Ok(bytes)
+    }
+}
+
+/// Parser state for the output of one run.
+struct RobocopyRunOutput {
+    parser: RobocopyLogParser,
+    progress: RobocopyProgress,
+    tally: RobocopySummaryTally,
+    jobs: Vec<RobocopyRunJob>,
+}
+
+impl RobocopyRunOutput {
+    fn new() -> Self {
+        Self {
+            parser: RobocopyLogParser::new(),
+            progress: RobocopyProgress::new(),
+            tally: RobocopySummaryTally::new(),
+            jobs: Vec::new(),
+        }
+    }
+
+    fn accept(&mut self, chunk: &[u8]) -> eyre::Result<()> {
+        self.parser.accept_bytes(chunk);
+        loop {
+            let advance = self.parser.advance()?;
+            self.progress.push_advance(&advance, Instant::now());
+            match advance {
+                RobocopyParseAdvance::NeedMoreData => return Ok(()),
+                RobocopyParseAdvance::Header(header) => {
+                    self.finish_job();
+                    self.jobs.push(RobocopyRunJob {
+                        header,
+                        summary: None,
+                        counts: RobocopySummaryCounts::default(),
+                    });
+                }
+                RobocopyParseAdvance::LogEntry(entry) => self.tally.push(&entry),
+                RobocopyParseAdvance::Summary(summary) => {
+                    self.finish_job();
+                    if let Some(job) = self.jobs.last_mut() {
+                        job.summary = Some(summary);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Store the tally of the entries so far on the current job.
+    fn finish_job(&mut self) {
+        let counts = std::mem::take(&mut self.tally).finish();
+        if let Some(job) = self.jobs.last_mut()
+            && job.summary.is_none()
+        {
+            job.counts = counts;
+        }
+    }
+
+    fn finish(mut self) -> Vec<RobocopyRunJob> {
+        self.finish_job();
+        self.jobs
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn runs_a_fake_robocopy_and_parses_its_output() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = RobocopyLogGeneratorConfig {
+            files: 40,
+            error_percent: 10,
+            ..Default::default()
+        };
+        let text = RobocopyLogGenerator::new(config).generate_log().to_string();
+        let sample = dir.path().join("sample.log");
+        std::fs::write(&sample, &text)?;
+        // Writes the log to stdout, or to the file after `/LOG:`, and exits 3
+        let script = dir.path().join("robocopy.sh");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nout=/dev/stdout\nfor arg in \"$@\"; do case \"$arg\" in /LOG:*) out=\"${{arg#/LOG:}}\";; esac; done\ncat '{}' > \"$out\"\nexit 3\n",
+                sample.display()
+            ),
+        )?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        let expected = RobocopyExitCode::from_summary(
+            crate::robocopy::robocopy_log::RobocopyLog::parse_all(&text)?[0]
+                .summary
+                .as_ref()
+                .expect("generated logs have a summary"),
+        );
+
+        let log = dir.path().join("run.log");
+        for command in [
+            RobocopyCommand::new("src", "dst"),
+            RobocopyCommand::new("src", "dst").with_switch(RobocopySwitch::Log(log.clone())),
+        ] {
+            let mut reports = 0;
+            let result = RobocopyRunner::new(command.with_executable(&script))
+                .with_report_interval(Duration::from_millis(10))
+                .run(|_| reports += 1)?;
+            assert_eq!(result.exit_code, RobocopyExitCode(3));
+            assert_eq!(result.jobs.len(), 1);
+            assert_eq!(result.expected_exit_code(), Some(expected));
+            assert!(result.progress.finished);
+            assert!(reports >= 1);
+        }
+        assert_eq!(std::fs::read_to_string(&log)?, text);
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use uom::si::information::byte;
+use uom::si::usize::Information;
+
+const KIB: f64 = 1024.0;
+const UNITS: [(char, f64); 4] = [
+    ('t', KIB * KIB * KIB * KIB),
+    ('g', KIB * KIB * KIB),
+    ('m', KIB * KIB),
+    ('k', KIB),
+];
+
+/// Parse a size as robocopy prints it, e.g. `204576`, `50.0 m` or `1.5 g`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "robocopy output contains human-readable floats that we round to usize"
+)]
+#[must_use]
+pub fn parse_robocopy_size(s: &str) -> Option<Information> {
+    let t = s.trim().to_lowercase();
+    let unit_char = t.chars().next_back()?;
+    let (num_str, unit) = if unit_char.is_ascii_alphabetic() {
+        (&t[..t.len() - 1], Some(unit_char))
+    } else {
+        ((&*t), None)
+    };
+    let number: f64 = num_str.trim().parse().ok()?;
+    if !number.is_finite() || number < 0.0 {
+        return None;
+    }
+    let factor = match unit {
+        None => 1.0,
+        Some(unit) => UNITS.iter().find(|(u, _)| *u == unit)?.1,
+    };
+    Some(Information::new::<byte>((number * factor) as usize))
+}
+
+/// Format a size the way robocopy prints it in file lines and summaries.
+///
+/// Sizes below one mebibyte are written as plain byte counts, larger ones are
+/// scaled to one decimal place with a `m`, `g` or `t` suffix.
+#[allow(
+    clippy::cast_precision_loss,
+    reason = "robocopy only shows one decimal place"
+)]
+#[must_use]
+pub fn format_robocopy_size(size: Information) -> String {
+    let bytes = size.get::<byte>();
+    for (unit, factor) in UNITS {
+        if unit != 'k' && bytes as f64 >= factor {
+            return format!("{:.1} {unit}", bytes as f64 / factor);
+        }
+    }
+    bytes.to_string()
+}
+
+/// Whether a size robocopy printed could be `bytes`, given that it rounds
+/// anything from a mebibyte up to one decimal place.
+#[must_use]
+pub fn robocopy_size_matches(printed: Information, bytes: u64) -> bool {
+    let bytes = Information::new::<byte>(usize::try_from(bytes).unwrap_or(usize::MAX));
+    format_robocopy_size(printed) == format_robocopy_size(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::information::mebibyte;
+
+    #[test]
+    fn format_then_parse_is_stable() {
+        for text in [
+            "0", "204576", "1048575", "50.0 m", "1.1 m", "150.2 m", "3.7 g", "1.0 t",
+        ] {
+            let size = parse_robocopy_size(text).unwrap();
+            assert_eq!(format_robocopy_size(size), text);
+        }
+        assert_eq!(
+            parse_robocopy_size("50.0 m"),
+            Some(Information::new::<mebibyte>(50))
+        );
+        assert_eq!(parse_robocopy_size("12 q"), None);
+        assert_eq!(parse_robocopy_size(""), None);
+
+        let printed = parse_robocopy_size("1.5 m").unwrap();
+        assert!(robocopy_size_matches(printed, 1_550_000));
+        assert!(!robocopy_size_matches(printed, 1_700_000));
+        assert!(!robocopy_size_matches(
+            parse_robocopy_size("204576").unwrap(),
+            204_577
+        ));
+    }
+}
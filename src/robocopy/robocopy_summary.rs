@@ -0,0 +1,356 @@
+use super::robocopy_size::format_robocopy_size;
+use super::robocopy_size::parse_robocopy_size;
+use super::robocopy_start_datetime::RobocopyStartDateTime;
+use eyre::OptionExt;
+use eyre::WrapErr;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+use uom::si::usize::Information;
+
+pub(crate) const SUMMARY_SEPARATOR: &str =
+    "------------------------------------------------------------------------------";
+const COLUMNS_LINE: &str = "               Total    Copied   Skipped  Mismatch    FAILED    Extras";
+
+/*
+------------------------------------------------------------------------------
+
+               Total    Copied   Skipped  Mismatch    FAILED    Extras
+    Dirs :         3         2         1         0         0         0
+   Files :        10         8         2         0         0         0
+   Bytes :   150.2 m   150.0 m    204576         0         0         0
+   Times :   0:00:05   0:00:04                       0:00:00   0:00:00
+
+   Speed :            36900000 Bytes/sec.
+   Speed :            2111.434 MegaBytes/min.
+   Ended : August 27, 2025 10:22:11 PM
+*/
+/// The job summary robocopy writes at the end of a run (omitted with `/NJS`).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RobocopySummary {
+    pub dirs: RobocopySummaryRow<u64>,
+    pub files: RobocopySummaryRow<u64>,
+    pub bytes: RobocopySummaryRow<Information>,
+    pub times: RobocopySummaryTimes,
+    pub speed: Option<RobocopySpeed>,
+    /// Printed in the same format as the header's `Started` field.
+    pub ended: RobocopyStartDateTime,
+}
+
+/// One row of the summary table.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct RobocopySummaryRow<T> {
+    pub total: T,
+    pub copied: T,
+    pub skipped: T,
+    pub mismatch: T,
+    pub failed: T,
+    pub extras: T,
+}
+
+impl<T: Copy> RobocopySummaryRow<T> {
+    /// The cells in column order.
+    #[must_use]
+    pub fn values(&self) -> [T; 6] {
+        [
+            self.total,
+            self.copied,
+            self.skipped,
+            self.mismatch,
+            self.failed,
+            self.extras,
+        ]
+    }
+
+    #[must_use]
+    pub fn from_values([total, copied, skipped, mismatch, failed, extras]: [T; 6]) -> Self {
+        Self {
+            total,
+            copied,
+            skipped,
+            mismatch,
+            failed,
+            extras,
+        }
+    }
+}
+
+/// A column of the summary table that an item is counted in (besides `Total`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum RobocopySummaryColumn {
+    Copied,
+    Skipped,
+    Mismatch,
+    Failed,
+    Extras,
+}
+
+impl<T: std::ops::AddAssign + Copy> RobocopySummaryRow<T> {
+    /// Count `value` in `column`, and in `total` unless it is an extra.
+    pub fn add(&mut self, column: RobocopySummaryColumn, value: T) {
+        match column {
+            RobocopySummaryColumn::Copied => self.copied += value,
+            RobocopySummaryColumn::Skipped => self.skipped += value,
+            RobocopySummaryColumn::Mismatch => self.mismatch += value,
+            RobocopySummaryColumn::Failed => self.failed += value,
+            RobocopySummaryColumn::Extras => {
+                self.extras += value;
+                return;
+            }
+        }
+        self.total += value;
+    }
+}
+
+/// The `Times` row; robocopy leaves the skipped and mismatch columns blank.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct RobocopySummaryTimes {
+    pub total: Duration,
+    pub copied: Duration,
+    pub failed: Duration,
+    pub extras: Duration,
+}
+
+/// The two `Speed` lines.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RobocopySpeed {
+    pub bytes_per_second: u64,
+    /// Megabytes per minute in thousandths, as robocopy prints three decimals.
+    pub megabytes_per_minute_milli: u64,
+}
+
+impl RobocopySpeed {
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "display value with three decimals"
+    )]
+    #[must_use]
+    pub fn megabytes_per_minute(&self) -> f64 {
+        self.megabytes_per_minute_milli as f64 / 1000.0
+    }
+}
+
+impl Display for RobocopySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{SUMMARY_SEPARATOR}")?;
+        writeln!(f)?;
+        writeln!(f, "{COLUMNS_LINE}")?;
+        write_row(f, "Dirs", self.dirs.values().map(|v| v.to_string()))?;
+        write_row(f, "Files", self.files.values().map(|v| v.to_string()))?;
+        write_row(f, "Bytes", self.bytes.values().map(format_robocopy_size))?;
+        writeln!(
+            f,
+            "{:>8} :{:>10}{:>10}{:>20}{:>10}{:>10}",
+            "Times",
+            format_duration(self.times.total),
+            format_duration(self.times.copied),
+            "",
+            format_duration(self.times.failed),
+            format_duration(self.times.extras),
+        )?;
+        writeln!(f)?;
+        if let Some(speed) = &self.speed {
+            writeln!(f, "   Speed :{:>20} Bytes/sec.", speed.bytes_per_second)?;
+            writeln!(
+                f,
+                "   Speed :{:>20} MegaBytes/min.",
+                format!(
+                    "{}.{:03}",
+                    speed.megabytes_per_minute_milli / 1000,
+                    speed.megabytes_per_minute_milli % 1000
+                )
+            )?;
+        }
+        write!(f, "   Ended : {}", self.ended)
+    }
+}
+
+fn write_row(f: &mut std::fmt::Formatter<'_>, label: &str, cells: [String; 6]) -> std::fmt::Result {
+    write!(f, "{label:>8} :")?;
+    for cell in cells {
+        write!(f, "{cell:>10}")?;
+    }
+    writeln!(f)
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn parse_duration(s: &str) -> eyre::Result<Duration> {
+    let mut parts = s.split(':');
+    let mut next = || -> eyre::Result<u64> {
+        Ok(parts
+            .next()
+            .ok_or_eyre("Expected h:mm:ss duration")?
+            .parse()?)
+    };
+    let (h, m, sec) = (next()?, next()?, next()?);
+    Ok(Duration::from_secs(h * 3600 + m * 60 + sec))
+}
+
+/// Join `150.2` `m` style token pairs back into single size values.
+fn size_tokens(value: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for token in value.split_whitespace() {
+        let is_unit = token.len() == 1 && "kmgtKMGT".contains(token);
+        match out.last_mut() {
+            Some(last) if is_unit => {
+                last.push(' ');
+                last.push_str(token);
+            }
+            _ => out.push(token.to_string()),
+        }
+    }
+    out
+}
+
+fn parse_row<T: Copy>(
+    value: &str,
+    tokens: fn(&str) -> Vec<String>,
+    parse: impl Fn(&str) -> Option<T>,
+) -> eyre::Result<RobocopySummaryRow<T>> {
+    let cells = tokens(value)
+        .iter()
+        .map(|t| parse(t).ok_or_else(|| eyre::eyre!("Invalid summary value: '{t}'")))
+        .collect::<eyre::Result<Vec<T>>>()?;
+    let cells: [T; 6] = cells
+        .try_into()
+        .map_err(|cells: Vec<T>| eyre::eyre!("Expected 6 summary columns, got {}", cells.len()))?;
+    Ok(RobocopySummaryRow::from_values(cells))
+}
+
+fn parse_milli(s: &str) -> Option<u64> {
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    if frac.len() > 3 || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let frac = format!("{frac:0<3}");
+    Some(whole.parse::<u64>().ok()? * 1000 + frac.parse::<u64>().ok()?)
+}
+
+impl FromStr for RobocopySummary {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut dirs = None;
+        let mut files = None;
+        let mut bytes = None;
+        let mut times = None;
+        let mut bytes_per_second = None;
+        let mut megabytes_per_minute_milli = None;
+        let mut ended = None;
+
+        for raw_line in s.lines() {
+            let Some((key, value)) = raw_line.split_once(':') else {
+                continue;
+            };
+            match key.trim().to_ascii_lowercase().as_str() {
+                "dirs" => {
+                    dirs = Some(
+                        parse_row(value, whitespace_tokens, |t| t.parse().ok())
+                            .wrap_err("Invalid Dirs row")?,
+                    );
+                }
+                "files" => {
+                    files = Some(
+                        parse_row(value, whitespace_tokens, |t| t.parse().ok())
+                            .wrap_err("Invalid Files row")?,
+                    );
+                }
+                "bytes" => {
+                    bytes = Some(
+                        parse_row(value, size_tokens, parse_robocopy_size)
+                            .wrap_err("Invalid Bytes row")?,
+                    );
+                }
+                "times" => {
+                    let cells = value
+                        .split_whitespace()
+                        .map(parse_duration)
+                        .collect::<eyre::Result<Vec<_>>>()
+                        .wrap_err("Invalid Times row")?;
+                    let [total, copied, failed, extras] = cells[..] else {
+                        eyre::bail!("Expected 4 Times columns, got {}", cells.len());
+                    };
+                    times = Some(RobocopySummaryTimes {
+                        total,
+                        copied,
+                        failed,
+                        extras,
+                    });
+                }
+                "speed" => {
+                    let mut tokens = value.split_whitespace();
+                    let (Some(number), Some(unit)) = (tokens.next(), tokens.next()) else {
+                        eyre::bail!("Invalid Speed line: '{raw_line}'");
+                    };
+                    if unit.eq_ignore_ascii_case("Bytes/sec.") {
+                        bytes_per_second = Some(number.parse().wrap_err("Invalid Bytes/sec")?);
+                    } else if unit.eq_ignore_ascii_case("MegaBytes/min.") {
+                        megabytes_per_minute_milli =
+                            Some(parse_milli(number).ok_or_eyre("Invalid MegaBytes/min")?);
+                    }
+                }
+                "ended" => {
+                    ended = Some(value.parse().wrap_err("Invalid Ended field")?);
+                }
+                _ => {}
+            }
+        }
+
+        let speed = match (bytes_per_second, megabytes_per_minute_milli) {
+            (Some(bytes_per_second), Some(megabytes_per_minute_milli)) => Some(RobocopySpeed {
+                bytes_per_second,
+                megabytes_per_minute_milli,
+            }),
+            _ => None,
+        };
+        Ok(RobocopySummary {
+            dirs: dirs.ok_or_eyre("Missing Dirs row")?,
+            files: files.ok_or_eyre("Missing Files row")?,
+            bytes: bytes.ok_or_eyre("Missing Bytes row")?,
+            times: times.ok_or_eyre("Missing Times row")?,
+            speed,
+            ended: ended.ok_or_eyre("Missing Ended field")?,
+        })
+    }
+}
+
+fn whitespace_tokens(value: &str) -> Vec<String> {
+    value.split_whitespace().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uom::si::information::byte;
+
+    const SUMMARY: &str = "\
+------------------------------------------------------------------------------
+
+               Total    Copied   Skipped  Mismatch    FAILED    Extras
+    Dirs :         3         2         1         0         0         0
+   Files :        10         8         2         0         0         0
+   Bytes :   150.2 m   150.0 m    204576         0         0         0
+   Times :   0:00:05   0:00:04                       0:00:00   0:00:01
+
+   Speed :            36900000 Bytes/sec.
+   Speed :            2111.434 MegaBytes/min.
+   Ended : August 27, 2025 10:22:11 PM";
+
+    #[test]
+    fn parse_and_display_roundtrip() -> eyre::Result<()> {
+        let summary: RobocopySummary = SUMMARY.parse()?;
+        assert_eq!(summary.files.copied, 8);
+        assert_eq!(summary.bytes.skipped, Information::new::<byte>(204_576));
+        assert_eq!(summary.times.extras, Duration::from_secs(1));
+        assert_eq!(
+            summary.speed.map(|s| s.megabytes_per_minute_milli),
+            Some(2_111_434)
+        );
+        assert_eq!(summary.to_string(), SUMMARY);
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_summary::RobocopySummaryRow;
+use std::path::PathBuf;
+use uom::si::usize::Information;
+
+/// The Dirs/Files/Bytes rows of a job summary, rebuilt from log entries.
+///
+/// Useful when the footer is missing because the log was truncated or written
+/// with `/NJS`, and for checking a footer against the entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RobocopySummaryCounts {
+    pub dirs: RobocopySummaryRow<u64>,
+    pub files: RobocopySummaryRow<u64>,
+    pub bytes: RobocopySummaryRow<Information>,
+}
+
+/// A cell where the footer robocopy wrote disagrees with the entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopySummaryDiscrepancy {
+    /// `Dirs`, `Files` or `Bytes`.
+    pub row: &'static str,
+    /// `Total`, `Copied`, `Skipped`, `Mismatch`, `FAILED` or `Extras`.
+    pub column: &'static str,
+    pub computed: String,
+    pub reported: String,
+}
+
+pub(crate) const COLUMN_NAMES: [&str; 6] =
+    ["Total", "Copied", "Skipped", "Mismatch", "FAILED", "Extras"];
+
+impl RobocopySummaryCounts {
+    /// Tally entries, either as collected in a `RobocopyLog` or straight from
+    /// the parser with one entry per progress tick.
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a RobocopyLogEntry>) -> Self {
+        let mut tally = RobocopySummaryTally::new();
+        for entry in entries {
+            tally.push(entry);
+        }
+        tally.finish()
+    }
+
+    /// Compare against a footer.
+    ///
+    /// Robocopy prints sizes rounded to one decimal, so byte counts match
+    /// when they print the same.
+    #[must_use]
+    pub fn discrepancies(&self, footer: &RobocopySummary) -> Vec<RobocopySummaryDiscrepancy> {
+        let mut discrepancies = Vec::new();
+        let mut compare = |row: &'static str, computed: [String; 6], reported: [String; 6]| {
+            for ((column, computed), reported) in COLUMN_NAMES.iter().zip(computed).zip(reported) {
+                if computed != reported {
+                    discrepancies.push(RobocopySummaryDiscrepancy {
+                        row,
+                        column,
+                        computed,
+                        reported,
+                    });
+                }
+            }
+        };
+        compare(
+            "Dirs",
+            self.dirs.values().map(|v| v.to_string()),
+            footer.dirs.values().map(|v| v.to_string()),
+        );
+        compare(
+            "Files",
+            self.files.values().map(|v| v.to_string()),
+            footer.files.values().map(|v| v.to_string()),
+        );
+        compare(
+            "Bytes",
+            self.bytes.values().map(format_robocopy_size),
+            footer.bytes.values().map(format_robocopy_size),
+        );
+        discrepancies
+    }
+}
+
+/// Incrementally builds [`RobocopySummaryCounts`] from a stream of entries.
+///
+/// A file is counted once even though the parser reports it again for every
+/// progress tick and robocopy writes it again for every retry.
+#[derive(Debug, Default)]
+pub struct RobocopySummaryTally {
+    counts: RobocopySummaryCounts,
+    // The file robocopy is working on, counted once it moves on
+    current_file: Option<(RobocopyFileClass, PathBuf, Information)>,
+    // Whether the last error was about a directory rather than the current file
+    directory_error: bool,
+}
+
+impl RobocopySummaryTally {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, entry: &RobocopyLogEntry) {
+        match entry {
+            RobocopyLogEntry::NewFile { size, path, .. }
+            | RobocopyLogEntry::File { size, path, .. } => {
+                let class = entry.file_class().unwrap_or(RobocopyFileClass::NewFile);
+                if let Some((current_class, current_path, current_size)) = &mut self.current_file
+                    && *current_class == class
+                    && current_path == path
+                {
+                    *current_size = *size;
+                } else {
+                    self.count_current_file(None);
+                    self.current_file = Some((class, path.clone(), *size));
+                }
+                self.directory_error = false;
+            }
+            RobocopyLogEntry::Directory { class, .. } => {
+                self.count_current_file(None);
+                let column = match class {
+                    RobocopyDirectoryClass::Existing => RobocopySummaryColumn::Skipped,
+                    RobocopyDirectoryClass::New => RobocopySummaryColumn::Copied,
+                    RobocopyDirectoryClass::Extra => RobocopySummaryColumn::Extras,
+                };
+                self.counts.dirs.add(column, 1);
+                self.directory_error = false;
+            }
+            RobocopyLogEntry::AccessDeniedError { .. } => {
+                // Robocopy gives up on unreadable directories straight away
+                self.count_current_file(None);
+                self.counts.dirs.add(RobocopySummaryColumn::Failed, 1);
+                self.directory_error = false;
+            }
+            RobocopyLogEntry::Error { operation, .. } => {
+                self.directory_error = operation.to_ascii_lowercase().contains("directory");
+            }
+            RobocopyLogEntry::Retry { .. } => {}
+            RobocopyLogEntry::RetryLimitExceeded => {
+                if self.directory_error {
+                    self.counts.dirs.add(RobocopySummaryColumn::Failed, 1);
+                } else {
+                    self.count_current_file(Some(RobocopySummaryColumn::Failed));
+                }
+                self.directory_error = false;
+            }
+        }
+    }
+
+    /// The counts so far, including the file currently being copied.
+    #[must_use]
+    pub fn counts(&self) -> RobocopySummaryCounts {
+        let mut counts = self.counts;
+        if let Some((class, _, size)) = &self.current_file {
+            counts.files.add(class.summary_column(), 1);
+            counts.bytes.add(class.summary_column(), *size);
+        }
+        counts
+    }
+
+    #[must_use]
+    pub fn finish(mut self) -> RobocopySummaryCounts {
+        self.count_current_file(None);
+        self.counts
+    }
+
+    fn count_current_file(&mut self, column: Option<RobocopySummaryColumn>) {
+        if let Some((class, _, size)) = self.current_file.take() {
+            let column = column.unwrap_or(class.summary_column());
+            self.counts.files.add(column, 1);
+            self.counts.bytes.add(column, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+    use crate::robocopy::robocopy_log_generator::RobocopyGeneratorOptionSet;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+    use uom::si::information::byte;
+    use uom::si::information::mebibyte;
+
+    #[test]
+    fn counts_match_generated_footers() -> eyre::Result<()> {
+        for seed in 0..20 {
+            let config = RobocopyLogGeneratorConfig {
+                seed,
+                files: 80,
+                error_percent: 25,
+                retries: u32::try_from(seed % 3)?,
+                threads: (seed % 2 == 0).then_some(4),
+                option_set: RobocopyGeneratorOptionSet::Mirror,
+                ..Default::default()
+            };
+            let log = RobocopyLogGenerator::new(config).generate_log();
+            let footer = log.summary.as_ref().expect("summary is generated");
+            let counts = RobocopySummaryCounts::from_entries(&log.parts);
+            assert_eq!(counts.dirs, footer.dirs, "seed {seed}");
+            assert_eq!(counts.files, footer.files, "seed {seed}");
+            assert_eq!(counts.bytes, footer.bytes, "seed {seed}");
+
+            // Streaming entries, one per progress tick, give the same answer
+            let text = log.to_string();
+            let mut parser = RobocopyLogParser::new();
+            parser.accept(&text);
+            let mut tally = RobocopySummaryTally::new();
+            let mut parsed_footer = None;
+            loop {
+                match parser.advance()? {
+                    RobocopyParseAdvance::NeedMoreData => break,
+                    RobocopyParseAdvance::Header(_) => {}
+                    RobocopyParseAdvance::LogEntry(entry) => tally.push(&entry),
+                    RobocopyParseAdvance::Summary(summary) => parsed_footer = Some(summary),
+                }
+            }
+            let streamed = tally.finish();
+            assert_eq!(streamed.files, counts.files, "seed {seed}");
+            let parsed_footer = parsed_footer.expect("footer is parsed");
+            assert_eq!(streamed.discrepancies(&parsed_footer), Vec::new());
+        }
+        Ok(())
+    }
+
+    /// `sample.txt` stops before robocopy's footer, so the expected rows are
+    /// counted off its lines by hand.
+    #[test]
+    fn counts_sample_log_exactly() -> eyre::Result<()> {
+        let logs = RobocopyLog::parse_all(include_str!("sample.txt"))?;
+        let counts = RobocopySummaryCounts::from_entries(&logs[0].parts);
+        // Two directories that could not be read and seven new files
+        assert_eq!(
+            counts.dirs,
+            RobocopySummaryRow {
+                total: 2,
+                failed: 2,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            counts.files,
+            RobocopySummaryRow {
+                total: 7,
+                copied: 7,
+                ..Default::default()
+            }
+        );
+        // Three 50.0 m buckets, two indexes and two empty locks
+        let bytes = Information::new::<mebibyte>(150)
+            + Information::new::<byte>(204_576)
+            + Information::new::<byte>(204_224);
+        assert_eq!(
+            counts.bytes,
+            RobocopySummaryRow {
+                total: bytes,
+                copied: bytes,
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_footer_discrepancies() -> eyre::Result<()> {
+        let logs = RobocopyLog::parse_all(include_str!("corpus/synthetic/multithreaded.log"))?;
+        let log = &logs[0];
+        let counts = RobocopySummaryCounts::from_entries(&log.parts);
+        let footer = log.summary.as_ref().expect("corpus has a footer");
+        let cells: Vec<(&str, &str)> = counts
+            .discrepancies(footer)
+            .iter()
+            .map(|d| (d.row, d.column))
+            .collect();
+        // The footer counts directories and a skipped file the log has no lines for
+        assert_eq!(
+            cells,
+            [
+                ("Dirs", "Total"),
+                ("Dirs", "Copied"),
+                ("Dirs", "Skipped"),
+                ("Dirs", "FAILED"),
+                ("Files", "Total"),
+                ("Files", "Skipped"),
+                ("Bytes", "Total"),
+            ]
+        );
+        assert_eq!(counts.files.failed, 1);
+        assert_eq!(counts.files.extras, 1);
+        assert_eq!(counts.files.copied, 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+use crate::robocopy::robocopy_log::RobocopyLog;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_size::robocopy_size_matches;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use rayon::prelude::*;
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use uom::si::usize::Information;
+
+/// A file the log says was copied, relative to the job's source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyCopiedFile {
+    /// The full path as resolved from the log.
+    pub log_path: PathBuf,
+    /// Where the file sits below the source, and so below the destination;
+    /// `None` when the log places it outside the job's source.
+    pub relative: Option<PathBuf>,
+    /// The size robocopy printed, which may be rounded.
+    pub size: Information,
+}
+
+/// The files a job copied: those logged in a copied class that did not run
+/// out of retries.
+#[must_use]
+pub fn copied_files(log: &RobocopyLog) -> Vec<RobocopyCopiedFile> {
+    let root = log.header.source.to_string_lossy().into_owned();
+    let mut resolver = RobocopyPathResolver::new();
+    let mut files = Vec::new();
+    let mut pending: Option<RobocopyCopiedFile> = None;
+    for entry in &log.parts {
+        let path = resolver.resolve(entry);
+        match entry {
+            RobocopyLogEntry::NewFile { size, .. } | RobocopyLogEntry::File { size, .. } => {
+                let path = path.unwrap_or_default();
+                // Retries repeat the file line
+                if pending.as_ref().is_some_and(|file| file.log_path == path) {
+                    continue;
+                }
+                files.extend(pending.take());
+                if entry
+                    .file_class()
+                    .is_some_and(|class| class.summary_column() == RobocopySummaryColumn::Copied)
+                {
+                    pending = Some(RobocopyCopiedFile {
+                        relative: relative_path(&root, &path.to_string_lossy()),
+                        log_path: path,
+                        size: *size,
+                    });
+                }
+            }
+            RobocopyLogEntry::Directory { .. } | RobocopyLogEntry::AccessDeniedError { .. } => {
+                files.extend(pending.take());
+            }
+            RobocopyLogEntry::RetryLimitExceeded => pending = None,
+            RobocopyLogEntry::Error { .. } | RobocopyLogEntry::Retry { .. } => {}
+        }
+    }
+    files.extend(pending);
+    files
+}
+
+/// `path` below `root`, comparing case-insensitively as Windows does.
+fn relative_path(root: &str, path: &str) -> Option<PathBuf> {
+    let rest = path
+        .get(..root.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(root))
+        .map(|_| &path[root.len()..])?;
+    // A root without a trailing separator must end at a component boundary
+    if !root.ends_with(['\\', '/']) && !rest.starts_with(['\\', '/']) {
+        return None;
+    }
+    let relative: PathBuf = rest
+        .split(['\\', '/'])
+        .filter(|part| !part.is_empty())
+        .collect();
+    Some(relative)
+}
+
+/// Why a copied file failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RobocopyVerifyProblemKind {
+    /// The file is not in the destination.
+    Missing,
+    /// The destination's size is not the one robocopy logged.
+    LoggedSizeMismatch {
+        logged: Information,
+        dest: u64,
+    },
+    /// The log places the file outside the job's source.
+    OutsideSource,
+    SizeMismatch {
+        source: u64,
+        dest: u64,
+    },
+    TimeMismatch {
+        source: SystemTime,
+        dest: SystemTime,
+    },
+    HashMismatch,
+    /// The file could not be read.
+    Unreadable {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl RobocopyVerifyProblemKind {
+    /// A short name for reports.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            RobocopyVerifyProblemKind::Missing => "missing",
+            RobocopyVerifyProblemKind::LoggedSizeMismatch { .. } => "logged-size-mismatch",
+            RobocopyVerifyProblemKind::OutsideSource => "outside-source",
+            RobocopyVerifyProblemKind::SizeMismatch { .. } => "size-mismatch",
+            RobocopyVerifyProblemKind::TimeMismatch { .. } => "time-mismatch",
+            RobocopyVerifyProblemKind::HashMismatch => "hash-mismatch",
+            RobocopyVerifyProblemKind::Unreadable { .. } => "unreadable",
+        }
+    }
+}
+
+impl Display for RobocopyVerifyProblemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RobocopyVerifyProblemKind::Missing => write!(f, "missing from the destination"),
+            RobocopyVerifyProblemKind::LoggedSizeMismatch { logged, dest } => {
+                write!(
+                    f,
+                    "size {dest} bytes, the log says {}",
+                    format_robocopy_size(*logged)
+                )
+            }
+            RobocopyVerifyProblemKind::OutsideSource => {
+                write!(f, "logged outside the job's source")
+            }
+            RobocopyVerifyProblemKind::SizeMismatch { source, dest } => {
+                write!(f, "size {dest} bytes, source has {source}")
+            }
+            RobocopyVerifyProblemKind::TimeMismatch { source, dest } => {
+                let difference = dest
+                    .duration_since(*source)
+                    .or_else(|_| source.duration_since(*dest))
+                    .unwrap_or_default();
+                let direction = if dest > source { "newer" } else { "older" };
+                write!(
+                    f,
+                    "modified {} {direction} than the source",
+                    humantime::format_duration(difference)
+                )
+            }
+            RobocopyVerifyProblemKind::HashMismatch => write!(f, "contents differ"),
+            RobocopyVerifyProblemKind::Unreadable { path, message } => {
+                write!(f, "cannot read {}: {message}", path.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobocopyVerifyProblem {
+    pub file: RobocopyCopiedFile,
+    pub kind: RobocopyVerifyProblemKind,
+}
+
+/// The outcome of checking every copied file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobocopyVerifyReport {
+    /// Copied files checked.
+    pub checked: usize,
+    /// Bytes in the destination files that matched.
+    pub verified_bytes: u64,
+    /// Bytes hashed, across source and destination.
+    pub hashed_bytes: u64,
+    /// Files no longer in the source, checked against the logged size alone.
+    pub log_only: usize,
+    /// In log order.
+    pub problems: Vec<RobocopyVerifyProblem>,
+}
+
+/// Checks that the files a log says were copied arrived intact.
+///
+/// Each file is found below the source and destination roots by its path
+/// relative to the job's source, so logs written on Windows can be checked
+/// against the same trees mounted elsewhere.
+#[derive(Debug, Clone)]
+pub struct RobocopyVerifier {
+    source: PathBuf,
+    dest: PathBuf,
+    hash: bool,
+    time_tolerance: Duration,
+}
+
+impl RobocopyVerifier {
+    #[must_use]
+    pub fn new(source: impl Into<PathBuf>, dest: impl Into<PathBuf>) -> Self {
+        Self {
+            source: source.into(),
+            dest: dest.into(),
+            hash: false,
+            time_tolerance: Duration::ZERO,
+        }
+    }
+
+    /// Also compare contents, hashing both copies of every file.
+    #[must_use]
+    pub fn with_hash(mut self, hash: bool) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// How far apart modification times may be, for filesystems that store
+    /// them coarsely.
+    #[must_use]
+    pub fn with_time_tolerance(mut self, tolerance: Duration) -> Self {
+        self.time_tolerance = tolerance;
+        self
+    }
+
+    /// Check every file in parallel; a file reports at most one problem.
+    #[must_use]
+    pub fn verify(&self, files: &[RobocopyCopiedFile]) -> RobocopyVerifyReport {
+        let checks: Vec<FileCheck> = files
+            .par_iter()
+            .map(|file| self.verify_file(file))
+            .collect();
+        let mut report = RobocopyVerifyReport {
+            checked: files.len(),
+            ..RobocopyVerifyReport::default()
+        };
+        for (file, check) in files.iter().zip(checks) {
+            report.hashed_bytes += check.hashed;
+            report.log_only += usize::from(check.log_only);
+            match check.result {
+                Ok(size) => report.verified_bytes += size,
+                Err(kind) => report.problems.push(RobocopyVerifyProblem {
+                    file: file.clone(),
+                    kind,
+                }),
+            }
+        }
+        report
+    }
+
+    /// Check one file against its logged size, then against the source
+    /// when it is still there.
+    fn verify_file(&self, file: &RobocopyCopiedFile) -> FileCheck {
+        let Some(relative) = &file.relative else {
+            return FileCheck::failed(RobocopyVerifyProblemKind::OutsideSource);
+        };
+        let source = self.source.join(relative);
+        let dest = self.dest.join(relative);
+        let dest_metadata = match std::fs::metadata(&dest) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return FileCheck::failed(RobocopyVerifyProblemKind::Missing);
+            }
+            Err(error) => return FileCheck::failed(unreadable(&dest, &error)),
+        };
+        let size = dest_metadata.len();
+        if !robocopy_size_matches(file.size, size) {
+            return FileCheck::failed(RobocopyVerifyProblemKind::LoggedSizeMismatch {
+                logged: file.size,
+                dest: size,
+            });
+        }
+        let source_metadata = match std::fs::metadata(&source) {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return FileCheck {
+                    log_only: true,
+                    ..FileCheck::passed(size)
+                };
+            }
+            Err(error) => return FileCheck::failed(unreadable(&source, &error)),
+        };
+        if size != source_metadata.len() {
+            return FileCheck::failed(RobocopyVerifyProblemKind::SizeMismatch {
+                source: source_metadata.len(),
+                dest: size,
+            });
+        }
+        let times = source_metadata
+            .modified()
+            .and_then(|source| dest_metadata.modified().map(|dest| (source, dest)));
+        match times {
+            Ok((source, dest)) => {
+                let difference = dest
+                    .duration_since(source)
+                    .or_else(|_| source.duration_since(dest))
+                    .unwrap_or_default();
+                if difference > self.time_tolerance {
+                    return FileCheck::failed(RobocopyVerifyProblemKind::TimeMismatch {
+                        source,
+                        dest,
+                    });
+                }
+            }
+            Err(error) => return FileCheck::failed(unreadable(&dest, &error)),
+        }
+        if !self.hash {
+            return FileCheck::passed(size);
+        }
+        let hashed = 2 * size;
+        let (source_hash, dest_hash) = rayon::join(|| hash_file(&source), || hash_file(&dest));
+        let result = match (source_hash, dest_hash) {
+            (Ok(source_hash), Ok(dest_hash)) if source_hash == dest_hash => Ok(size),
+            (Ok(_), Ok(_)) => Err(RobocopyVerifyProblemKind::HashMismatch),
+            (Err(error), _) => Err(unreadable(&source, &error)),
+            (_, Err(error)) => Err(unreadable(&dest, &error)),
+        };
+        FileCheck {
+            result,
+            hashed,
+            log_only: false,
+        }
+    }
+}
+
+/// How checking one file went.
+struct FileCheck {
+    /// The destination size when the file checks out.
+    result: Result<u64, RobocopyVerifyProblemKind>,
+    hashed: u64,
+    log_only: bool,
+}
+
+impl FileCheck {
+    fn passed(size: u64) -> Self {
+        Self {
+            result: Ok(size),
+            hashed: 0,
+            log_only: false,
+        }
+    }
+
+    fn failed(kind: RobocopyVerifyProblemKind) -> Self {
+        Self {
+            result: Err(kind),
+            hashed: 0,
+            log_only: false,
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok(hasher.finalize())
+}
+
+fn unreadable(path: &Path, error: &io::Error) -> RobocopyVerifyProblemKind {
+    RobocopyVerifyProblemKind::Unreadable {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copy::copy_engine::CopyEngine;
+    use crate::robocopy::robocopy_command::RobocopyCommand;
+    use crate::robocopy::robocopy_log_writer::RobocopyLogWriter;
+
+    #[test]
+    fn finds_missing_and_changed_copies() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let source = dir.path().join("src");
+        let dest = dir.path().join("dst");
+        std::fs::create_dir_all(source.join("sub"))?;
+        for name in ["a.txt", "b.txt", "c.txt", "sub/d.txt"] {
+            std::fs::write(source.join(name), name)?;
+        }
+        let command =
+            RobocopyCommand::from_args(&[&source, &dest].map(|path| path.display().to_string()))?
+                .with_switch(crate::robocopy::robocopy_options::RobocopySwitch::Subdirectories);
+        let mut writer = RobocopyLogWriter::new(Vec::new());
+        CopyEngine::new(command)?.run(&mut writer)?;
+        let log = RobocopyLog::parse_all(&String::from_utf8(writer.into_inner())?)?.remove(0);
+        let files = copied_files(&log);
+        let relative: Vec<_> = files
+            .iter()
+            .filter_map(|file| file.relative.clone())
+            .collect();
+        assert_eq!(
+            relative,
+            ["a.txt", "b.txt", "c.txt", "sub/d.txt"].map(PathBuf::from)
+        );
+
+        let verifier = RobocopyVerifier::new(&source, &dest).with_hash(true);
+        assert!(verifier.verify(&files).problems.is_empty());
+
+        std::fs::remove_file(dest.join("a.txt"))?;
+        // Not the size the log claims
+        std::fs::write(dest.join("b.txt"), "longer than before")?;
+        // Same size and time, different contents
+        let modified = std::fs::metadata(dest.join("c.txt"))?.modified()?;
+        std::fs::write(dest.join("c.txt"), "C.TXT")?;
+        File::options()
+            .write(true)
+            .open(dest.join("c.txt"))?
+            .set_modified(modified)?;
+        // Changed in the source since the copy
+        std::fs::write(source.join("sub/d.txt"), "rewritten since")?;
+        let kinds = |report: &RobocopyVerifyReport| -> Vec<&'static str> {
+            report
+                .problems
+                .iter()
+                .map(|problem| problem.kind.name())
+                .collect()
+        };
+        let report = verifier.verify(&files);
+        assert_eq!(
+            kinds(&report),
+            [
+                "missing",
+                "logged-size-mismatch",
+                "hash-mismatch",
+                "size-mismatch"
+            ]
+        );
+        assert_eq!((report.checked, report.log_only), (4, 0));
+
+        // Without a source only the logged size can be checked
+        std::fs::remove_file(source.join("b.txt"))?;
+        std::fs::remove_file(source.join("c.txt"))?;
+        let report = verifier.verify(&files);
+        assert_eq!(
+            kinds(&report),
+            ["missing", "logged-size-mismatch", "size-mismatch"]
+        );
+        assert_eq!(report.log_only, 1);
+
+        assert_eq!(
+            relative_path(r"J:\Pool\", r"j:\pool\x\y.bin"),
+            Some(PathBuf::from("x").join("y.bin"))
+        );
+        assert_eq!(relative_path(r"J:\pool", r"J:\pool2\y.bin"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+/// A Win32 error code robocopy commonly reports, with the message it prints
+/// on the line after the `ERROR` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win32ErrorInfo {
+    pub code: u32,
+    pub name: &'static str,
+    pub message: &'static str,
+    /// What usually causes the error during a copy.
+    pub explanation: &'static str,
+    /// What to try before rerunning.
+    pub fix: &'static str,
+}
+
+pub const KNOWN_WIN32_ERRORS: &[Win32ErrorInfo] = &[
+    Win32ErrorInfo {
+        code: 2,
+        name: "ERROR_FILE_NOT_FOUND",
+        message: "The system cannot find the file specified.",
+        explanation: "The file was listed in the source but was gone by the time robocopy opened it.",
+        fix: "Usually harmless churn from files being deleted during the copy; rerun to pick up the final state.",
+    },
+    Win32ErrorInfo {
+        code: 3,
+        name: "ERROR_PATH_NOT_FOUND",
+        message: "The system cannot find the path specified.",
+        explanation: "A directory in the path does not exist, often because it was removed or renamed mid-copy.",
+        fix: "Check the source still exists and the path is not longer than 260 characters without long path support.",
+    },
+    Win32ErrorInfo {
+        code: 5,
+        name: "ERROR_ACCESS_DENIED",
+        message: "Access is denied.",
+        explanation: "The account running robocopy lacks permission on the source or destination.",
+        fix: "Run elevated with /B or /ZB (backup mode), or grant the account read access to the source and write access to the destination.",
+    },
+    Win32ErrorInfo {
+        code: 32,
+        name: "ERROR_SHARING_VIOLATION",
+        message: "The process cannot access the file because it is being used by another process.",
+        explanation: "Another process has the file open without sharing it.",
+        fix: "Close the program holding the file, copy from a VSS snapshot, or exclude it with /XF.",
+    },
+    Win32ErrorInfo {
+        code: 53,
+        name: "ERROR_BAD_NETPATH",
+        message: "The network path was not found.",
+        explanation: "The network share could not be reached.",
+        fix: "Check the server name, DNS and VPN, and that the share is online; raise /R and /W to ride out outages.",
+    },
+    Win32ErrorInfo {
+        code: 59,
+        name: "ERROR_UNEXP_NET_ERR",
+        message: "An unexpected network error occurred.",
+        explanation: "The network connection failed in an unexpected way.",
+        fix: "Check the network link and the file server's event log; raise /R and /W to retry through blips.",
+    },
+    Win32ErrorInfo {
+        code: 64,
+        name: "ERROR_NETNAME_DELETED",
+        message: "The specified network name is no longer available.",
+        explanation: "The network connection dropped while the file was open.",
+        fix: "Check cabling, Wi-Fi and the server's power settings; use /Z so interrupted files resume instead of restarting.",
+    },
+    Win32ErrorInfo {
+        code: 112,
+        name: "ERROR_DISK_FULL",
+        message: "There is not enough space on the disk.",
+        explanation: "The destination volume filled up.",
+        fix: "Free space or pick a larger destination; /MIR with /PURGE can clear extras first.",
+    },
+    Win32ErrorInfo {
+        code: 121,
+        name: "ERROR_SEM_TIMEOUT",
+        message: "The semaphore timeout period has expired.",
+        explanation: "A device or network request took too long, often a failing disk or a saturated link.",
+        fix: "Check the disk's SMART status and cabling, lower /MT, and use /Z to resume.",
+    },
+    Win32ErrorInfo {
+        code: 1392,
+        name: "ERROR_FILE_CORRUPT",
+        message: "The file or directory is corrupted and unreadable.",
+        explanation: "The file system reports the file or directory as corrupt.",
+        fix: "Run chkdsk on the affected volume and restore the file from another copy.",
+    },
+    Win32ErrorInfo {
+        code: 1450,
+        name: "ERROR_NO_SYSTEM_RESOURCES",
+        message: "Insufficient system resources exist to complete the requested service.",
+        explanation: "Windows ran out of kernel memory, usually from copying very large files over the network.",
+        fix: "Lower /MT, use /J for unbuffered I/O on large files, or increase the server's paged pool.",
+    },
+];
+
+/// Look up a known Win32 error code.
+#[must_use]
+pub fn win32_error_info(code: u32) -> Option<&'static Win32ErrorInfo> {
+    KNOWN_WIN32_ERRORS.iter().find(|info| info.code == code)
+}
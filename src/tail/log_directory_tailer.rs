@@ -0,0 +1,337 @@
+use crate::glob_match::glob_match;
+use crate::tail::log_tailer::LogTailer;
+use crate::tail::polling_log_tailer::PollingLogTailer;
+use crate::tail::tail_event::TailEvent;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use eyre::WrapErr;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+/// The set of log files a [`LogDirectoryTailer`] follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogWatchTarget {
+    /// Every file directly inside a directory.
+    Directory(PathBuf),
+    /// Files in `directory` whose name matches `pattern`, where `*` is any
+    /// run of characters and `?` is one, ignoring case.
+    Glob { directory: PathBuf, pattern: String },
+}
+
+impl LogWatchTarget {
+    /// Recognize a directory, or a path whose file name has wildcards such as
+    /// `logs\*.log`; `None` for anything else, which is a single log.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(LogWatchTarget::Directory(path.to_path_buf()));
+        }
+        let pattern = path.file_name()?.to_str()?;
+        if !pattern.contains(['*', '?']) {
+            return None;
+        }
+        Some(LogWatchTarget::Glob {
+            directory: parent_directory(path),
+            pattern: pattern.to_string(),
+        })
+    }
+
+    /// Just the file at `path`, for watching a single log the same way.
+    #[must_use]
+    pub fn file(path: &Path) -> Self {
+        LogWatchTarget::Glob {
+            directory: parent_directory(path),
+            pattern: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        match self {
+            LogWatchTarget::Directory(directory) | LogWatchTarget::Glob { directory, .. } => {
+                directory
+            }
+        }
+    }
+
+    /// The files that currently match, in name order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be listed.
+    pub fn matching_files(&self) -> eyre::Result<Vec<PathBuf>> {
+        let directory = self.directory();
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(directory)
+            .wrap_err_with(|| format!("Failed to list {}", directory.display()))?
+        {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            if let LogWatchTarget::Glob { pattern, .. } = self
+                && !glob_match(pattern, &entry.file_name().to_string_lossy())
+            {
+                continue;
+            }
+            files.push(entry.path());
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn parent_directory(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Something observed while watching a set of logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogDirectoryEvent {
+    /// A matching file was found; `log` numbers files in the order they were found.
+    Added { log: usize, path: PathBuf },
+    /// Everything the file held when watching started has been delivered, or
+    /// the file was truncated or replaced since. Sent right after `Added` for a
+    /// file that was empty then or created later, so all of its data is new.
+    CaughtUp { log: usize },
+    /// Something the file's tailer observed.
+    Tail { log: usize, event: TailEvent },
+}
+
+/// Follows every log matching a [`LogWatchTarget`], including files created
+/// after watching started.
+#[derive(Debug, Clone)]
+pub struct LogDirectoryTailer {
+    /// How often the directory is listed for new files.
+    pub scan_interval: Duration,
+    pub tailer: PollingLogTailer,
+}
+
+impl Default for LogDirectoryTailer {
+    fn default() -> Self {
+        Self {
+            scan_interval: Duration::from_secs(2),
+            tailer: PollingLogTailer::default(),
+        }
+    }
+}
+
+impl LogDirectoryTailer {
+    /// Start watching `target`, delivering the events of every file on one channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be listed or the scanner fails to start.
+    pub fn watch(&self, target: LogWatchTarget) -> eyre::Result<Receiver<LogDirectoryEvent>> {
+        // Fail early on a missing directory rather than from the scanner thread
+        target.matching_files()?;
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let scanner = self.clone();
+        thread::Builder::new()
+            .name("log-directory-tailer".into())
+            .spawn(move || {
+                if let Err(error) = scanner.scan(&target, &tx) {
+                    warn!(
+                        "Watching {} stopped: {error:?}",
+                        target.directory().display()
+                    );
+                }
+            })
+            .wrap_err("Failed to spawn log-directory-tailer thread")?;
+        Ok(rx)
+    }
+
+    fn scan(&self, target: &LogWatchTarget, tx: &Sender<LogDirectoryEvent>) -> eyre::Result<()> {
+        let mut seen = BTreeSet::new();
+        let mut listing_failed = false;
+        let mut first_listing = true;
+        // Like the file tailers, this stops once a send finds the receiver gone
+        loop {
+            // A share that drops out for a while should not end the watch
+            let files = match target.matching_files() {
+                Ok(files) => {
+                    if listing_failed {
+                        info!("Listing {} again", target.directory().display());
+                        listing_failed = false;
+                    }
+                    files
+                }
+                Err(error) => {
+                    if !listing_failed {
+                        warn!("Retrying until it can be listed: {error:?}");
+                        listing_failed = true;
+                    }
+                    Vec::new()
+                }
+            };
+            for path in files {
+                if seen.contains(&path) {
+                    continue;
+                }
+                let log = seen.len();
+                seen.insert(path.clone());
+                // A file can vanish between listing and opening it
+                let rx = match self.tailer.tail(&path) {
+                    Ok(rx) => rx,
+                    Err(error) => {
+                        debug!("Not following {}: {error:?}", path.display());
+                        continue;
+                    }
+                };
+                // Only files that were there when watching started hold old output
+                let existing = if first_listing {
+                    std::fs::metadata(&path).map_or(0, |metadata| metadata.len())
+                } else {
+                    0
+                };
+                info!("Following {}", path.display());
+                if tx.send(LogDirectoryEvent::Added { log, path }).is_err() {
+                    return Ok(());
+                }
+                let tx = tx.clone();
+                thread::Builder::new()
+                    .name("log-directory-forwarder".into())
+                    .spawn(move || forward(log, existing, &rx, &tx))
+                    .wrap_err("Failed to spawn log-directory-forwarder thread")?;
+            }
+            if !listing_failed {
+                first_listing = false;
+            }
+            thread::sleep(self.scan_interval);
+        }
+    }
+}
+
+/// Pass on a file's events, announcing `CaughtUp` once the `existing` bytes
+/// it held when found have been delivered.
+fn forward(log: usize, existing: u64, rx: &Receiver<TailEvent>, tx: &Sender<LogDirectoryEvent>) {
+    let mut caught_up = existing == 0;
+    if caught_up && tx.send(LogDirectoryEvent::CaughtUp { log }).is_err() {
+        return;
+    }
+    let mut delivered = 0;
+    for event in rx {
+        // After a reset the old output is gone and whatever follows is new
+        let reset = event.is_reset();
+        if let TailEvent::Data(chunk) = &event {
+            delivered += chunk.len() as u64;
+        }
+        if tx.send(LogDirectoryEvent::Tail { log, event }).is_err() {
+            return;
+        }
+        if !caught_up && (reset || delivered >= existing) {
+            caught_up = true;
+            if tx.send(LogDirectoryEvent::CaughtUp { log }).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn follows_matching_files_including_new_ones() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("share-a.log"), b"a\n")?;
+        std::fs::write(dir.path().join("notes.txt"), b"not a log\n")?;
+        let target = LogWatchTarget::from_path(&dir.path().join("*.LOG")).expect("has a wildcard");
+        assert_eq!(target.directory(), dir.path());
+        assert!(LogWatchTarget::from_path(&dir.path().join("share-a.log")).is_none());
+        assert_eq!(
+            LogWatchTarget::from_path(dir.path()),
+            Some(LogWatchTarget::Directory(dir.path().to_path_buf()))
+        );
+
+        let watcher = LogDirectoryTailer {
+            scan_interval: Duration::from_millis(10),
+            tailer: PollingLogTailer {
+                poll_interval: Duration::from_millis(10),
+                ..PollingLogTailer::default()
+            },
+        };
+        let rx = watcher.watch(target)?;
+        let recv = || {
+            rx.recv_timeout(Duration::from_secs(5))
+                .expect("watcher produced no event")
+        };
+        assert_eq!(
+            recv(),
+            LogDirectoryEvent::Added {
+                log: 0,
+                path: dir.path().join("share-a.log")
+            }
+        );
+        assert_eq!(
+            recv(),
+            LogDirectoryEvent::Tail {
+                log: 0,
+                event: TailEvent::Data(b"a\n".to_vec())
+            }
+        );
+        assert_eq!(recv(), LogDirectoryEvent::CaughtUp { log: 0 });
+
+        let mut file = std::fs::File::create(dir.path().join("share-b.log"))?;
+        file.write_all(b"b\n")?;
+        assert_eq!(
+            recv(),
+            LogDirectoryEvent::Added {
+                log: 1,
+                path: dir.path().join("share-b.log")
+            }
+        );
+        // A file created while watching holds nothing old
+        assert_eq!(recv(), LogDirectoryEvent::CaughtUp { log: 1 });
+        assert_eq!(
+            recv(),
+            LogDirectoryEvent::Tail {
+                log: 1,
+                event: TailEvent::Data(b"b\n".to_vec())
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keeps_watching_a_directory_that_disappears_for_a_while() -> eyre::Result<()> {
+        let parent = tempfile::tempdir()?;
+        let dir = parent.path().join("logs");
+        std::fs::create_dir(&dir)?;
+        let watcher = LogDirectoryTailer {
+            scan_interval: Duration::from_millis(10),
+            tailer: PollingLogTailer {
+                poll_interval: Duration::from_millis(10),
+                ..PollingLogTailer::default()
+            },
+        };
+        let rx = watcher.watch(LogWatchTarget::Directory(dir.clone()))?;
+
+        std::fs::remove_dir(&dir)?;
+        thread::sleep(Duration::from_millis(50));
+        std::fs::create_dir(&dir)?;
+        std::fs::write(dir.join("share-a.log"), b"a\n")?;
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5))?,
+            LogDirectoryEvent::Added {
+                log: 0,
+                path: dir.join("share-a.log")
+            }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use crate::tail::tail_event::TailEvent;
+use crossbeam_channel::Receiver;
+use std::path::Path;
+
+/// A backend that follows a growing log file.
+///
+/// Implementations spawn their own reader and deliver [`TailEvent`]s on the
+/// returned channel. The channel closes when the backend stops, either because
+/// the receiver was dropped or because an unrecoverable error occurred.
+pub trait LogTailer {
+    /// Start tailing `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` is not a readable file or the backend fails to start.
+    fn tail(&self, path: &Path) -> eyre::Result<Receiver<TailEvent>>;
+}
+
+/// The tailer used by the CLI on the current platform.
+#[must_use]
+pub fn default_log_tailer() -> Box<dyn LogTailer> {
+    Box::new(crate::tail::polling_log_tailer::PollingLogTailer::default())
+}
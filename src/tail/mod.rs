@@ -0,0 +1,9 @@
+pub mod log_directory_tailer;
+pub mod log_tailer;
+pub mod polling_log_tailer;
+pub mod tail_event;
+
+pub use log_directory_tailer::LogDirectoryTailer;
+pub use log_tailer::LogTailer;
+pub use polling_log_tailer::PollingLogTailer;
+pub use tail_event::TailEvent;
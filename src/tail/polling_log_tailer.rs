@@ -0,0 +1,272 @@
+use crate::tail::log_tailer::LogTailer;
+use crate::tail::tail_event::TailEvent;
+use crossbeam_channel::Receiver;
+use crossbeam_channel::Sender;
+use eyre::WrapErr;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::debug;
+use tracing::warn;
+
+/// Where a tail begins reading when it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TailStart {
+    /// Read the existing content first. Required when the consumer needs the header.
+    #[default]
+    FromStart,
+    /// Only report data appended after the tail started.
+    FromEnd,
+}
+
+/// How many leading bytes identify the content of a log.
+///
+/// Enough to cover a robocopy header up to its `Started :` timestamp, so a log
+/// rewritten in place by a later job differs from the one already read.
+const FINGERPRINT_LEN: u64 = 1024;
+
+/// Portable tailer that polls the file for growth.
+///
+/// Works anywhere `std::fs` does. Before each read it compares the open handle
+/// with what the path currently refers to, so it notices rotation (the path
+/// was replaced by a new file), and compares the first bytes of the file with
+/// those already read, so it notices truncation even when the file has grown
+/// back past the old position, as robocopy's `/LOG` does when it starts over.
+#[derive(Debug, Clone)]
+pub struct PollingLogTailer {
+    pub poll_interval: Duration,
+    pub read_chunk_size: usize,
+    pub start: TailStart,
+}
+
+impl Default for PollingLogTailer {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(150),
+            read_chunk_size: 64 * 1024,
+            start: TailStart::FromStart,
+        }
+    }
+}
+
+impl LogTailer for PollingLogTailer {
+    fn tail(&self, path: &Path) -> eyre::Result<Receiver<TailEvent>> {
+        if !path.is_file() {
+            eyre::bail!("Path is not a file: {}", path.display());
+        }
+        let mut file = File::open(path)
+            .wrap_err_with(|| format!("Failed to open file for tailing: {}", path.display()))?;
+        if self.start == TailStart::FromEnd {
+            file.seek(SeekFrom::End(0))?;
+        }
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut worker = PollWorker {
+            path: path.to_path_buf(),
+            position: file.stream_position()?,
+            file,
+            fingerprint: Vec::new(),
+            buf: vec![0u8; self.read_chunk_size.max(1)],
+            poll_interval: self.poll_interval,
+        };
+        worker.fingerprint = worker.read_prefix()?;
+        thread::Builder::new()
+            .name("polling-log-tailer".into())
+            .spawn(move || {
+                if let Err(error) = worker.run(&tx) {
+                    warn!("Tailing stopped: {error:?}");
+                }
+            })
+            .wrap_err("Failed to spawn polling-log-tailer thread")?;
+        Ok(rx)
+    }
+}
+
+struct PollWorker {
+    path: PathBuf,
+    file: File,
+    position: u64,
+    /// The first bytes already read, up to [`FINGERPRINT_LEN`].
+    fingerprint: Vec<u8>,
+    buf: Vec<u8>,
+    poll_interval: Duration,
+}
+
+impl PollWorker {
+    fn run(mut self, tx: &Sender<TailEvent>) -> eyre::Result<()> {
+        loop {
+            // Check before reading, so data from a file that was rewritten
+            // since the last poll is never read from the old position
+            if let Some(event) = self.check_replaced_or_truncated()? {
+                debug!(?event, path = %self.path.display(), "Restarting tail");
+                if tx.send(event).is_err() {
+                    return Ok(());
+                }
+            }
+            loop {
+                let read = self.file.read(&mut self.buf)?;
+                if read == 0 {
+                    break;
+                }
+                let chunk = &self.buf[..read];
+                if self.fingerprint.len() as u64 == self.position {
+                    let wanted = FINGERPRINT_LEN.saturating_sub(self.position);
+                    let take = usize::try_from(wanted).unwrap_or(usize::MAX).min(read);
+                    self.fingerprint.extend_from_slice(&chunk[..take]);
+                }
+                self.position += read as u64;
+                if tx.send(TailEvent::Data(chunk.to_vec())).is_err() {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    fn check_replaced_or_truncated(&mut self) -> eyre::Result<Option<TailEvent>> {
+        let Ok(path_file) = File::open(&self.path) else {
+            // Mid-rotation the path may briefly not exist; keep the old handle
+            return Ok(None);
+        };
+        if let (Some(path_id), Some(handle_id)) = (file_id(&path_file)?, file_id(&self.file)?)
+            && path_id != handle_id
+        {
+            self.file = path_file;
+            self.position = 0;
+            self.fingerprint.clear();
+            return Ok(Some(TailEvent::Rotated));
+        }
+        if self.file.metadata()?.len() < self.position || self.read_prefix()? != self.fingerprint {
+            self.file.seek(SeekFrom::Start(0))?;
+            self.position = 0;
+            self.fingerprint.clear();
+            return Ok(Some(TailEvent::Truncated));
+        }
+        Ok(None)
+    }
+
+    /// Read the start of the file, up to what has already been read, leaving
+    /// the handle at the current position.
+    fn read_prefix(&mut self) -> eyre::Result<Vec<u8>> {
+        let mut prefix = Vec::new();
+        self.file.seek(SeekFrom::Start(0))?;
+        (&mut self.file)
+            .take(self.position.min(FINGERPRINT_LEN))
+            .read_to_end(&mut prefix)?;
+        self.file.seek(SeekFrom::Start(self.position))?;
+        Ok(prefix)
+    }
+}
+
+/// Identifies the file behind a handle, so a path replaced by a new file is
+/// told apart from the one already open.
+#[cfg(unix)]
+fn file_id(file: &File) -> eyre::Result<Option<(u64, u64)>> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = file.metadata()?;
+    Ok(Some((metadata.dev(), metadata.ino())))
+}
+
+/// Identifies the file behind a handle by its volume serial number and file
+/// index. Creation times cannot be used: NTFS tunneling gives a file recreated
+/// under the same name the creation time of the one it replaced.
+#[cfg(windows)]
+fn file_id(file: &File) -> eyre::Result<Option<(u64, u64)>> {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION;
+    use windows::Win32::Storage::FileSystem::GetFileInformationByHandle;
+
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    // SAFETY: the handle is owned by `file`, which outlives the call
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &raw mut info) }?;
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Ok(Some((u64::from(info.dwVolumeSerialNumber), index)))
+}
+
+/// Without file ids, only truncation is detected.
+#[cfg(not(any(unix, windows)))]
+fn file_id(_file: &File) -> eyre::Result<Option<(u64, u64)>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn tailer() -> PollingLogTailer {
+        PollingLogTailer {
+            poll_interval: Duration::from_millis(10),
+            ..PollingLogTailer::default()
+        }
+    }
+
+    fn recv(rx: &Receiver<TailEvent>) -> TailEvent {
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("tailer produced no event")
+    }
+
+    #[test]
+    fn reports_appends_and_truncation() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("robocopy.log");
+        std::fs::write(&path, b"first\n")?;
+
+        let rx = tailer().tail(&path)?;
+        assert_eq!(recv(&rx), TailEvent::Data(b"first\n".to_vec()));
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path)?;
+        file.write_all(b"second\n")?;
+        assert_eq!(recv(&rx), TailEvent::Data(b"second\n".to_vec()));
+
+        std::fs::write(&path, b"new\n")?;
+        assert_eq!(recv(&rx), TailEvent::Truncated);
+        assert_eq!(recv(&rx), TailEvent::Data(b"new\n".to_vec()));
+        Ok(())
+    }
+
+    #[test]
+    fn reports_truncation_that_grows_past_the_old_position() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("robocopy.log");
+        std::fs::write(&path, b"old job\n")?;
+
+        let rx = tailer().tail(&path)?;
+        assert_eq!(recv(&rx), TailEvent::Data(b"old job\n".to_vec()));
+
+        let mut file = std::fs::OpenOptions::new().write(true).open(&path)?;
+        file.set_len(0)?;
+        file.write_all(b"new job, longer than the old one\n")?;
+        assert_eq!(recv(&rx), TailEvent::Truncated);
+        assert_eq!(
+            recv(&rx),
+            TailEvent::Data(b"new job, longer than the old one\n".to_vec())
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_rotation() -> eyre::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("robocopy.log");
+        std::fs::write(&path, b"old job\n")?;
+
+        let rx = tailer().tail(&path)?;
+        assert_eq!(recv(&rx), TailEvent::Data(b"old job\n".to_vec()));
+
+        std::fs::rename(&path, dir.path().join("robocopy.log.1"))?;
+        std::fs::write(&path, b"new job, longer than the old one\n")?;
+        assert_eq!(recv(&rx), TailEvent::Rotated);
+        assert_eq!(
+            recv(&rx),
+            TailEvent::Data(b"new job, longer than the old one\n".to_vec())
+        );
+        Ok(())
+    }
+}
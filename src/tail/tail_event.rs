@@ -0,0 +1,32 @@
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use tracing::info;
+
+/// Something observed while tailing a log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TailEvent {
+    /// Newly appended bytes, in file order.
+    Data(Vec<u8>),
+    /// The file shrank below the position already read; reading restarts at offset 0.
+    Truncated,
+    /// The path now refers to a different file; reading restarts at its beginning.
+    Rotated,
+}
+
+impl TailEvent {
+    /// Whether consumers should discard parser state before handling further data.
+    #[must_use]
+    pub fn is_reset(&self) -> bool {
+        matches!(self, TailEvent::Truncated | TailEvent::Rotated)
+    }
+
+    /// Hand this event to a parser, starting over when the file was replaced.
+    pub fn feed(self, parser: &mut RobocopyLogParser) {
+        match self {
+            TailEvent::Data(chunk) => parser.accept_bytes(&chunk),
+            TailEvent::Truncated | TailEvent::Rotated => {
+                info!("Log file was {self:?}, restarting parser");
+                parser.reset();
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+pub mod path_finder;
+pub mod path_tree_pane;
+pub mod robocopy_logs_app;
+pub mod robocopy_logs_dashboard;
+pub mod robocopy_logs_dashboard_view;
+pub mod robocopy_logs_tui;
+pub mod robocopy_logs_view;
+
+pub use robocopy_logs_app::RobocopyLogsApp;
+pub use robocopy_logs_dashboard::RobocopyLogsDashboard;
+pub use robocopy_logs_tui::run_robocopy_logs_dashboard;
+pub use robocopy_logs_tui::run_robocopy_logs_tui;
@@ -0,0 +1,195 @@
+use nucleo::Config;
+use nucleo::Injector;
+use nucleo::Matcher;
+use nucleo::Nucleo;
+use nucleo::Utf32String;
+use nucleo::pattern::CaseMatching;
+use nucleo::pattern::Normalization;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// How long a frame may wait for the matcher before drawing stale results.
+const TICK_TIMEOUT_MS: u64 = 10;
+
+/// A path seen in the log and the entry it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathFinderItem {
+    pub path: String,
+    /// Index of the entry in the TUI's entry list.
+    pub entry: usize,
+}
+
+/// One match, ready to draw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathFinderMatch {
+    pub item: PathFinderItem,
+    /// Character positions in `item.path` that matched the query, ascending.
+    pub highlights: Vec<u32>,
+}
+
+/// Incremental fuzzy search over every path in the log.
+///
+/// Matching runs on nucleo's background thread pool, so pushing millions of
+/// paths and retyping the query never blocks drawing; [`Self::tick`] picks up
+/// whatever the workers finished since the last frame.
+pub struct PathFinder {
+    nucleo: Nucleo<PathFinderItem>,
+    injector: Injector<PathFinderItem>,
+    query: String,
+    matcher: Matcher,
+}
+
+impl Default for PathFinder {
+    fn default() -> Self {
+        let nucleo = Nucleo::new(Config::DEFAULT.match_paths(), Arc::new(|| {}), None, 1);
+        let injector = nucleo.injector();
+        Self {
+            nucleo,
+            injector,
+            query: String::new(),
+            matcher: Matcher::new(Config::DEFAULT.match_paths()),
+        }
+    }
+}
+
+impl Debug for PathFinder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PathFinder")
+            .field("query", &self.query)
+            .field("items", &self.injector.injected_items())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PathFinder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, item: PathFinderItem) {
+        self.injector.push(item, |item, columns| {
+            // The matcher only treats `/` as a separator; the swap keeps
+            // character positions so highlights still line up
+            columns[0] = Utf32String::from(item.path.replace('\\', "/"));
+        });
+    }
+
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        // Typing more characters only narrows the previous matches
+        let append = query.starts_with(self.query.as_str());
+        self.query = query.to_string();
+        self.nucleo.pattern.reparse(
+            0,
+            &query.replace('\\', "/"),
+            CaseMatching::Smart,
+            Normalization::Smart,
+            append,
+        );
+    }
+
+    /// Let the matcher publish new results; returns whether it is still busy.
+    pub fn tick(&mut self) -> bool {
+        self.nucleo.tick(TICK_TIMEOUT_MS).running
+    }
+
+    #[must_use]
+    pub fn item_count(&self) -> u32 {
+        self.nucleo.snapshot().item_count()
+    }
+
+    #[must_use]
+    pub fn match_count(&self) -> u32 {
+        self.nucleo.snapshot().matched_item_count()
+    }
+
+    /// Matches `start..start + count`, best first, with the matched characters.
+    pub fn matches(&mut self, start: u32, count: u32) -> Vec<PathFinderMatch> {
+        let snapshot = self.nucleo.snapshot();
+        let end = start
+            .saturating_add(count)
+            .min(snapshot.matched_item_count());
+        let pattern = snapshot.pattern().column_pattern(0);
+        let mut highlights = Vec::new();
+        snapshot
+            .matched_items(start.min(end)..end)
+            .map(|item| {
+                highlights.clear();
+                pattern.indices(
+                    item.matcher_columns[0].slice(..),
+                    &mut self.matcher,
+                    &mut highlights,
+                );
+                highlights.sort_unstable();
+                highlights.dedup();
+                PathFinderMatch {
+                    item: item.data.clone(),
+                    highlights: highlights.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(finder: &mut PathFinder) {
+        while finder.tick() {}
+    }
+
+    #[test]
+    fn finds_and_highlights_paths() {
+        let mut finder = PathFinder::new();
+        for (entry, path) in [
+            r"J:\pool\0\17\000001.bucket",
+            r"J:\pool\0\17\",
+            r"J:\photos\2024\IMG_0001.jpg",
+            r"J:\photos\2024\IMG_0002.jpg",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            finder.push(PathFinderItem {
+                path: path.to_string(),
+                entry,
+            });
+        }
+        settle(&mut finder);
+        assert_eq!(finder.item_count(), 4);
+        assert_eq!(finder.match_count(), 4);
+
+        finder.set_query("img2");
+        settle(&mut finder);
+        let matches = finder.matches(0, 10);
+        assert_eq!(matches.len(), 1);
+        let best = &matches[0];
+        assert_eq!(best.item.entry, 3);
+        let highlighted: String = best
+            .highlights
+            .iter()
+            .map(|index| best.item.path.chars().nth(*index as usize).unwrap_or('?'))
+            .collect();
+        assert_eq!(highlighted.to_lowercase(), "img2");
+
+        finder.set_query("bucket");
+        settle(&mut finder);
+        let matches = finder.matches(0, 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].item.entry, 0);
+
+        // Paths pushed while a query is active are matched too
+        finder.push(PathFinderItem {
+            path: r"J:\pool\0\18\000002.bucket".to_string(),
+            entry: 4,
+        });
+        settle(&mut finder);
+        assert_eq!(finder.match_count(), 2);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_path_tree::RobocopyPathNodeKind;
+use crate::robocopy::robocopy_path_tree::RobocopyPathTree;
+use crate::robocopy::robocopy_path_tree::RobocopyPathTreeNodeId;
+use ratatui::crossterm::event::KeyCode;
+use rustc_hash::FxHashSet;
+
+/// Rows moved by page up and page down.
+const PAGE: usize = 20;
+
+/// A row of the tree pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathTreeRow {
+    pub node: RobocopyPathTreeNodeId,
+    /// 0 for drives and shares.
+    pub depth: usize,
+    pub expanded: bool,
+}
+
+/// The collapsible directory tree shown in place of the entry list.
+#[derive(Debug, Default)]
+pub struct PathTreePane {
+    tree: RobocopyPathTree,
+    // Nodes whose expansion differs from the default: drives start expanded,
+    // everything else starts collapsed
+    toggled: FxHashSet<RobocopyPathTreeNodeId>,
+    selected: usize,
+    offset: usize,
+}
+
+impl PathTreePane {
+    pub fn push(&mut self, entry: &RobocopyLogEntry) {
+        self.tree.push(entry);
+    }
+
+    #[must_use]
+    pub fn tree(&self) -> &RobocopyPathTree {
+        &self.tree
+    }
+
+    fn is_expanded(&self, node: RobocopyPathTreeNodeId, depth: usize) -> bool {
+        self.toggled.contains(&node) != (depth == 0)
+    }
+
+    /// Every row currently shown, top to bottom.
+    #[must_use]
+    pub fn rows(&self) -> Vec<PathTreeRow> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(RobocopyPathTreeNodeId, usize)> = self
+            .tree
+            .children(self.tree.root())
+            .into_iter()
+            .rev()
+            .map(|node| (node, 0))
+            .collect();
+        while let Some((node, depth)) = stack.pop() {
+            let expanded = self.tree.node(node).kind == RobocopyPathNodeKind::Directory
+                && self.is_expanded(node, depth);
+            rows.push(PathTreeRow {
+                node,
+                depth,
+                expanded,
+            });
+            if expanded {
+                stack.extend(
+                    self.tree
+                        .children(node)
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, depth + 1)),
+                );
+            }
+        }
+        rows
+    }
+
+    /// Handle a navigation key; returns whether the key was used.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        let rows = self.rows();
+        let last = rows.len().saturating_sub(1);
+        let selected = rows.get(self.selected.min(last)).copied();
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(PAGE),
+            KeyCode::PageDown => self.selected = (self.selected + PAGE).min(last),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = last,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
+                if let Some(row) = selected
+                    && !row.expanded
+                    && !self.tree.node(row.node).children.is_empty()
+                {
+                    self.toggle(row.node);
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(row) = selected {
+                    if row.expanded {
+                        self.toggle(row.node);
+                    } else if let Some(parent) = rows[..self.selected.min(last)]
+                        .iter()
+                        .rposition(|candidate| candidate.depth + 1 == row.depth)
+                    {
+                        // Jump to the parent so another Left collapses it
+                        self.selected = parent;
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn toggle(&mut self, node: RobocopyPathTreeNodeId) {
+        if !self.toggled.remove(&node) {
+            self.toggled.insert(node);
+        }
+    }
+
+    /// The rows to draw in a pane `height` rows tall, and the position of the
+    /// selected row among them.
+    pub fn window(&mut self, height: usize) -> (Vec<PathTreeRow>, usize) {
+        let rows = self.rows();
+        self.selected = self.selected.min(rows.len().saturating_sub(1));
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if height > 0 && self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        let end = (self.offset + height).min(rows.len());
+        let start = self.offset.min(end);
+        (rows[start..end].to_vec(), self.selected - start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log::RobocopyLog;
+
+    #[test]
+    fn expands_and_collapses_directories() -> eyre::Result<()> {
+        let logs = RobocopyLog::parse_all(include_str!("../robocopy/sample.txt"))?;
+        let mut pane = PathTreePane::default();
+        for entry in &logs[0].parts {
+            pane.push(entry);
+        }
+        // The drive starts expanded to show its top-level directories
+        let rows = pane.rows();
+        assert_eq!(pane.tree().node(rows[0].node).name, r"J:\");
+        assert!(rows[0].expanded);
+        assert!(rows[1..].iter().all(|row| row.depth == 1 && !row.expanded));
+        let collapsed = rows.len();
+
+        // Expand the first directory with children, then walk back out
+        let target = rows
+            .iter()
+            .position(|row| !pane.tree().node(row.node).children.is_empty() && row.depth == 1)
+            .expect("sample has nested directories");
+        for _ in 0..target {
+            pane.handle_key(KeyCode::Down);
+        }
+        pane.handle_key(KeyCode::Right);
+        assert!(pane.rows().len() > collapsed);
+        pane.handle_key(KeyCode::Down);
+        pane.handle_key(KeyCode::Left);
+        assert_eq!(pane.window(100).1, target);
+        pane.handle_key(KeyCode::Left);
+        assert_eq!(pane.rows().len(), collapsed);
+        Ok(())
+    }
+}
@@ -0,0 +1,735 @@
+use crate::robocopy::robocopy_header::RobocopyHeader;
+use crate::robocopy::robocopy_job_status::RobocopyJobState;
+use crate::robocopy::robocopy_job_status::RobocopyJobStatus;
+use crate::robocopy::robocopy_job_status::RobocopyJobThresholds;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_path_resolver::RobocopyPathResolver;
+use crate::robocopy::robocopy_progress::RobocopyInFlightFile;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::robocopy::robocopy_progress::RobocopyProgressSnapshot;
+use crate::robocopy::robocopy_summary::RobocopySummary;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::tui::path_finder::PathFinder;
+use crate::tui::path_finder::PathFinderItem;
+use crate::tui::path_finder::PathFinderMatch;
+use crate::tui::path_tree_pane::PathTreePane;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::KeyModifiers;
+use std::collections::VecDeque;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// Throughput samples kept for the sparkline, one per [`RobocopyLogsApp::sample_rate`].
+const RATE_HISTORY_LEN: usize = 240;
+/// In-flight files shown at once; with `/MT` robocopy may report many.
+const MAX_IN_FLIGHT: usize = 4;
+/// Rows moved by page up and page down.
+const PAGE: usize = 20;
+
+/// Which entries the entry list shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryKindFilter {
+    #[default]
+    All,
+    Files,
+    Directories,
+    Errors,
+}
+
+impl EntryKindFilter {
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            EntryKindFilter::All => EntryKindFilter::Files,
+            EntryKindFilter::Files => EntryKindFilter::Directories,
+            EntryKindFilter::Directories => EntryKindFilter::Errors,
+            EntryKindFilter::Errors => EntryKindFilter::All,
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryKindFilter::All => "all",
+            EntryKindFilter::Files => "files",
+            EntryKindFilter::Directories => "dirs",
+            EntryKindFilter::Errors => "errors",
+        }
+    }
+
+    fn matches(self, entry: &RobocopyLogEntry) -> bool {
+        match self {
+            EntryKindFilter::All => true,
+            EntryKindFilter::Files => entry.file_class().is_some(),
+            EntryKindFilter::Directories => matches!(entry, RobocopyLogEntry::Directory { .. }),
+            EntryKindFilter::Errors => is_error(entry),
+        }
+    }
+}
+
+/// What the main pane shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pane {
+    #[default]
+    Entries,
+    Tree,
+}
+
+/// Whether keys go to the list or to the filter being typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    #[default]
+    Normal,
+    EditingFilter,
+    /// The fuzzy path finder is open.
+    Finding,
+}
+
+/// State behind the `robocopy-logs-tui` screen.
+///
+/// Knows nothing about terminals: feed it parser output and key presses, then
+/// hand it to [`crate::tui::robocopy_logs_view::render`].
+#[derive(Debug)]
+pub struct RobocopyLogsApp {
+    header: Option<RobocopyHeader>,
+    summary: Option<RobocopySummary>,
+    // One entry per file, updated in place by later progress ticks
+    entries: Vec<RobocopyLogEntry>,
+    errors: Vec<usize>,
+    // Indices into `entries` that pass the filter
+    visible: Vec<usize>,
+    kind_filter: EntryKindFilter,
+    text_filter: String,
+    input_mode: InputMode,
+    selected: usize,
+    offset: usize,
+    follow: bool,
+    paused: bool,
+    should_quit: bool,
+    progress: RobocopyProgress,
+    in_flight: Vec<RobocopyInFlightFile>,
+    rate_history: VecDeque<u64>,
+    resolver: RobocopyPathResolver,
+    finder: PathFinder,
+    finder_selected: u32,
+    pane: Pane,
+    tree_pane: PathTreePane,
+    status: RobocopyJobStatus,
+    thresholds: RobocopyJobThresholds,
+    last_output: SystemTime,
+}
+
+impl RobocopyLogsApp {
+    /// `follow` keeps the newest entry selected as entries arrive.
+    #[must_use]
+    pub fn new(follow: bool, progress: RobocopyProgress) -> Self {
+        Self {
+            header: None,
+            summary: None,
+            entries: Vec::new(),
+            errors: Vec::new(),
+            visible: Vec::new(),
+            kind_filter: EntryKindFilter::All,
+            text_filter: String::new(),
+            input_mode: InputMode::Normal,
+            selected: 0,
+            offset: 0,
+            follow,
+            paused: false,
+            should_quit: false,
+            progress,
+            in_flight: Vec::new(),
+            rate_history: VecDeque::with_capacity(RATE_HISTORY_LEN),
+            resolver: RobocopyPathResolver::new(),
+            finder: PathFinder::new(),
+            finder_selected: 0,
+            pane: Pane::Entries,
+            tree_pane: PathTreePane::default(),
+            status: RobocopyJobStatus::new(),
+            thresholds: RobocopyJobThresholds::default(),
+            last_output: SystemTime::now(),
+        }
+    }
+
+    /// Forget what was read, e.g. after the log was truncated or rotated.
+    /// Filters and the key mode are kept.
+    pub fn reset(&mut self) {
+        self.progress.reset();
+        *self = Self {
+            kind_filter: self.kind_filter,
+            text_filter: std::mem::take(&mut self.text_filter),
+            input_mode: self.input_mode,
+            paused: self.paused,
+            ..Self::new(self.follow, self.progress.clone())
+        };
+    }
+
+    /// Take in whatever the parser produced.
+    pub fn push_advance(&mut self, advance: RobocopyParseAdvance, now: Instant) {
+        self.progress.push_advance(&advance, now);
+        self.status.push_advance(&advance);
+        if advance != RobocopyParseAdvance::NeedMoreData {
+            self.last_output = SystemTime::now();
+        }
+        match advance {
+            RobocopyParseAdvance::NeedMoreData => {}
+            RobocopyParseAdvance::Header(header) => {
+                self.header = Some(header);
+                self.summary = None;
+                self.in_flight.clear();
+            }
+            RobocopyParseAdvance::LogEntry(entry) => self.push_entry(entry),
+            RobocopyParseAdvance::Summary(summary) => {
+                self.summary = Some(summary);
+                self.in_flight.clear();
+            }
+        }
+    }
+
+    /// Record the current throughput for the sparkline; call about once a second.
+    pub fn sample_rate(&mut self, now: Instant) {
+        self.progress.tick(now);
+        if self.rate_history.len() == RATE_HISTORY_LEN {
+            self.rate_history.pop_front();
+        }
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "rates are non-negative and approximate"
+        )]
+        let rate = self.progress.snapshot().bytes_per_second.unwrap_or(0.0) as u64;
+        self.rate_history.push_back(rate);
+    }
+
+    /// Forget the rate measured while catching up on an existing log.
+    pub fn restart_clock(&mut self, now: Instant) {
+        self.progress.restart_clock(now);
+    }
+
+    /// When the log last grew, e.g. its modification time after catching up,
+    /// since output read at startup may have been written long ago.
+    pub fn set_last_output(&mut self, last_output: SystemTime) {
+        self.last_output = last_output;
+    }
+
+    /// Where the job is in its lifecycle, judging quiet spells by the clock.
+    #[must_use]
+    pub fn job_state(&self) -> RobocopyJobState {
+        let idle = SystemTime::now()
+            .duration_since(self.last_output)
+            .unwrap_or_default();
+        self.status.state(idle, &self.thresholds)
+    }
+
+    fn push_entry(&mut self, entry: RobocopyLogEntry) {
+        self.track_in_flight(&entry);
+        self.tree_pane.push(&entry);
+        if let Some(last) = self.entries.last_mut()
+            && last.file_class().is_some()
+            && last.file_class() == entry.file_class()
+            && last.path() == entry.path()
+        {
+            // Another progress tick for the same file
+            *last = entry;
+            return;
+        }
+        let index = self.entries.len();
+        if let Some(path) = self.resolver.resolve(&entry) {
+            self.finder.push(PathFinderItem {
+                path: path.to_string_lossy().into_owned(),
+                entry: index,
+            });
+        }
+        if is_error(&entry) {
+            self.errors.push(index);
+        }
+        let visible = self.matches(&entry);
+        self.entries.push(entry);
+        if visible {
+            self.visible.push(index);
+            if self.follow {
+                self.selected = self.visible.len() - 1;
+            }
+        }
+    }
+
+    fn track_in_flight(&mut self, entry: &RobocopyLogEntry) {
+        match entry {
+            RobocopyLogEntry::NewFile { size, path, .. }
+            | RobocopyLogEntry::File { size, path, .. } => {
+                let copied = entry
+                    .file_class()
+                    .is_some_and(|class| class.summary_column() == RobocopySummaryColumn::Copied);
+                let percent = entry.percentages().and_then(|p| p.last().copied());
+                self.in_flight.retain(|file| file.path != *path);
+                if copied && let Some(percent) = percent.filter(|percent| *percent < 100) {
+                    if self.in_flight.len() == MAX_IN_FLIGHT {
+                        self.in_flight.remove(0);
+                    }
+                    self.in_flight.push(RobocopyInFlightFile {
+                        path: path.clone(),
+                        size: *size,
+                        percent: Some(percent),
+                    });
+                }
+            }
+            RobocopyLogEntry::Error { .. } | RobocopyLogEntry::RetryLimitExceeded => {
+                // The file that failed is the one that moved last
+                self.in_flight.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn matches(&self, entry: &RobocopyLogEntry) -> bool {
+        if !self.kind_filter.matches(entry) {
+            return false;
+        }
+        if self.text_filter.is_empty() {
+            return true;
+        }
+        let Some(path) = entry.path() else {
+            return false;
+        };
+        path.to_string_lossy()
+            .to_lowercase()
+            .contains(&self.text_filter.to_lowercase())
+    }
+
+    fn refilter(&mut self) {
+        let selected_entry = self.visible.get(self.selected).copied();
+        self.visible = (0..self.entries.len())
+            .filter(|index| self.matches(&self.entries[*index]))
+            .collect();
+        self.selected = if self.follow {
+            self.visible.len().saturating_sub(1)
+        } else {
+            // Stay near the entry that was selected before
+            selected_entry.map_or(0, |entry| {
+                self.visible
+                    .partition_point(|index| *index < entry)
+                    .min(self.visible.len().saturating_sub(1))
+            })
+        };
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match self.input_mode {
+            InputMode::Normal => self.handle_normal_key(key),
+            InputMode::EditingFilter => self.handle_filter_key(key),
+            InputMode::Finding => self.handle_finder_key(key),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        if self.pane == Pane::Tree && self.tree_pane.handle_key(key.code) {
+            return;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('p' | ' ') => self.paused = !self.paused,
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.select_last();
+                }
+            }
+            KeyCode::Char('/') => self.input_mode = InputMode::EditingFilter,
+            KeyCode::Char('s') => {
+                self.input_mode = InputMode::Finding;
+                self.finder_selected = 0;
+            }
+            KeyCode::Char('t') => {
+                self.pane = match self.pane {
+                    Pane::Entries => Pane::Tree,
+                    Pane::Tree => Pane::Entries,
+                };
+            }
+            KeyCode::Tab => {
+                self.kind_filter = self.kind_filter.next();
+                self.refilter();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-PAGE.cast_signed()),
+            KeyCode::PageDown => self.move_selection(PAGE.cast_signed()),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.follow = false;
+                self.selected = 0;
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.follow = true;
+                self.select_last();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.input_mode = InputMode::Normal,
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.text_filter.clear();
+                self.refilter();
+            }
+            KeyCode::Backspace => {
+                self.text_filter.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) => {
+                self.text_filter.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_finder_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Enter => {
+                if let Some(found) = self.finder.matches(self.finder_selected, 1).pop() {
+                    self.jump_to_entry(found.item.entry);
+                }
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Up => self.finder_selected = self.finder_selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.finder_selected =
+                    (self.finder_selected + 1).min(self.finder.match_count().saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                let mut query = self.finder.query().to_string();
+                query.pop();
+                self.finder.set_query(&query);
+                self.finder_selected = 0;
+            }
+            KeyCode::Char(c) => {
+                let query = format!("{}{c}", self.finder.query());
+                self.finder.set_query(&query);
+                self.finder_selected = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Select an entry in the list, clearing filters that would hide it.
+    pub fn jump_to_entry(&mut self, index: usize) {
+        self.follow = false;
+        if !self.visible.contains(&index) {
+            self.kind_filter = EntryKindFilter::All;
+            self.text_filter.clear();
+            self.refilter();
+        }
+        self.selected = self.visible.partition_point(|visible| *visible < index);
+    }
+
+    /// Let the path finder publish results found since the last frame.
+    pub fn tick_finder(&mut self) {
+        self.finder.tick();
+        self.finder_selected = self
+            .finder_selected
+            .min(self.finder.match_count().saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+        // Scrolling to the bottom picks following back up
+        self.follow = delta > 0 && self.selected == last;
+    }
+
+    fn select_last(&mut self) {
+        self.selected = self.visible.len().saturating_sub(1);
+    }
+
+    /// The range of visible entries to draw in a list `height` rows tall,
+    /// scrolled just enough to keep the selection on screen.
+    pub fn scroll_window(&mut self, height: usize) -> std::ops::Range<usize> {
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if height > 0 && self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        let end = (self.offset + height).min(self.visible.len());
+        self.offset.min(end)..end
+    }
+
+    /// Finder matches to draw in a list `height` rows tall, and the row of the
+    /// selected match within them.
+    pub fn finder_window(&mut self, height: u32) -> (Vec<PathFinderMatch>, usize) {
+        let start = (self.finder_selected + 1).saturating_sub(height);
+        let matches = self.finder.matches(start, height);
+        (matches, (self.finder_selected - start) as usize)
+    }
+
+    #[must_use]
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    #[must_use]
+    pub fn tree_pane(&self) -> &PathTreePane {
+        &self.tree_pane
+    }
+
+    pub fn tree_pane_mut(&mut self) -> &mut PathTreePane {
+        &mut self.tree_pane
+    }
+
+    #[must_use]
+    pub fn finder(&self) -> &PathFinder {
+        &self.finder
+    }
+
+    /// The entry at `index` in log order.
+    #[must_use]
+    pub fn entry(&self, index: usize) -> Option<&RobocopyLogEntry> {
+        self.entries.get(index)
+    }
+
+    #[must_use]
+    pub fn header(&self) -> Option<&RobocopyHeader> {
+        self.header.as_ref()
+    }
+
+    #[must_use]
+    pub fn summary(&self) -> Option<&RobocopySummary> {
+        self.summary.as_ref()
+    }
+
+    /// The entry at `position` in the filtered list.
+    #[must_use]
+    pub fn visible_entry(&self, position: usize) -> Option<&RobocopyLogEntry> {
+        self.visible
+            .get(position)
+            .map(|index| &self.entries[*index])
+    }
+
+    #[must_use]
+    pub fn visible_len(&self) -> usize {
+        self.visible.len()
+    }
+
+    #[must_use]
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Error entries, oldest first.
+    #[must_use]
+    pub fn errors(&self) -> impl DoubleEndedIterator<Item = &RobocopyLogEntry> + ExactSizeIterator {
+        self.errors.iter().map(|index| &self.entries[*index])
+    }
+
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> RobocopyProgressSnapshot {
+        self.progress.snapshot()
+    }
+
+    #[must_use]
+    pub fn in_flight(&self) -> &[RobocopyInFlightFile] {
+        &self.in_flight
+    }
+
+    #[must_use]
+    pub fn rate_history(&self) -> &VecDeque<u64> {
+        &self.rate_history
+    }
+
+    #[must_use]
+    pub fn kind_filter(&self) -> EntryKindFilter {
+        self.kind_filter
+    }
+
+    #[must_use]
+    pub fn text_filter(&self) -> &str {
+        &self.text_filter
+    }
+
+    #[must_use]
+    pub fn input_mode(&self) -> InputMode {
+        self.input_mode
+    }
+
+    #[must_use]
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// While paused the caller stops reading the log, so the screen holds still.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    #[must_use]
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Take back a quit request, for when quitting a log returns to the dashboard.
+    pub fn cancel_quit(&mut self) {
+        self.should_quit = false;
+    }
+}
+
+fn is_error(entry: &RobocopyLogEntry) -> bool {
+    matches!(
+        entry,
+        RobocopyLogEntry::Error { .. }
+            | RobocopyLogEntry::AccessDeniedError { .. }
+            | RobocopyLogEntry::RetryLimitExceeded
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+
+    fn app_for(config: RobocopyLogGeneratorConfig, follow: bool) -> eyre::Result<RobocopyLogsApp> {
+        let text = RobocopyLogGenerator::new(config).generate_log().to_string();
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(&text);
+        let mut app = RobocopyLogsApp::new(follow, RobocopyProgress::new());
+        loop {
+            let advance = parser.advance()?;
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                return Ok(app);
+            }
+            app.push_advance(advance, Instant::now());
+        }
+    }
+
+    fn press(app: &mut RobocopyLogsApp, code: KeyCode) {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn progress_ticks_update_entries_in_place() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 30,
+            error_percent: 20,
+            ..Default::default()
+        };
+        let log = RobocopyLogGenerator::new(config.clone()).generate_log();
+        let app = app_for(config, true)?;
+        assert!(app.header().is_some());
+        assert!(app.summary().is_some());
+        assert_eq!(app.entry_count(), log.parts.len());
+        assert_eq!(
+            app.errors().count(),
+            log.parts.iter().filter(|entry| is_error(entry)).count()
+        );
+        assert_eq!(app.selected(), app.visible_len() - 1);
+        assert!(app.in_flight().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn filters_and_keys() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 40,
+            error_percent: 20,
+            ..Default::default()
+        };
+        let mut app = app_for(config, false)?;
+        let all = app.visible_len();
+        assert_eq!(app.selected(), 0);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.kind_filter(), EntryKindFilter::Files);
+        assert!((0..app.visible_len()).all(|i| {
+            app.visible_entry(i)
+                .is_some_and(|e| e.file_class().is_some())
+        }));
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.visible_len(), app.errors().count());
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.visible_len(), all);
+
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.input_mode(), InputMode::EditingFilter);
+        for c in "zzz-no-such-path".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.visible_len(), 0);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.visible_len(), all);
+        assert_eq!(app.input_mode(), InputMode::Normal);
+
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.selected(), PAGE);
+        assert!(!app.is_following());
+        press(&mut app, KeyCode::End);
+        assert!(app.is_following());
+        assert_eq!(app.selected(), all - 1);
+        assert_eq!(app.scroll_window(10), all - 10..all);
+
+        press(&mut app, KeyCode::Char('p'));
+        assert!(app.is_paused());
+        press(&mut app, KeyCode::Char('p'));
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.should_quit());
+        Ok(())
+    }
+
+    #[test]
+    fn finder_jumps_to_the_entry_for_a_path() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 200,
+            error_percent: 10,
+            ..Default::default()
+        };
+        let mut app = app_for(config, true)?;
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.input_mode(), InputMode::Finding);
+        while app.finder.tick() {}
+        assert!(app.finder().item_count() as usize >= app.entry_count() - app.errors().count());
+
+        // Look for a file by its full path, though the log only has its name
+        let mut resolver = RobocopyPathResolver::new();
+        let target = (0..app.entry_count())
+            .filter_map(|index| {
+                let entry = app.entry(index)?;
+                let path = resolver.resolve(entry)?;
+                entry.file_class().map(|_| (index, path))
+            })
+            .last()
+            .expect("generated log has files");
+        for c in target.1.to_string_lossy().chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        while app.finder.tick() {}
+        app.tick_finder();
+        let (matches, selected) = app.finder_window(5);
+        assert_eq!(selected, 0);
+        assert_eq!(matches[0].item.entry, target.0);
+
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.input_mode(), InputMode::Normal);
+        assert_eq!(app.kind_filter(), EntryKindFilter::Files);
+        assert!(!app.is_following());
+        let selected = app
+            .visible_entry(app.selected())
+            .expect("entry is selected");
+        assert_eq!(Some(selected), app.entry(target.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+use crate::robocopy::robocopy_job_status::RobocopyJobState;
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::tail::log_directory_tailer::LogDirectoryEvent;
+use crate::tui::robocopy_logs_app::RobocopyLogsApp;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::crossterm::event::KeyEvent;
+use ratatui::crossterm::event::KeyModifiers;
+use std::path::PathBuf;
+use std::time::Instant;
+use tracing::warn;
+
+/// One log on the dashboard, with the full single-log screen behind it.
+#[derive(Debug)]
+pub struct DashboardLog {
+    pub path: PathBuf,
+    parser: RobocopyLogParser,
+    app: RobocopyLogsApp,
+    // Why the log stopped being read, until it is truncated or replaced
+    error: Option<String>,
+}
+
+impl DashboardLog {
+    #[must_use]
+    pub fn app(&self) -> &RobocopyLogsApp {
+        &self.app
+    }
+
+    /// Where the job the log is on is in its lifecycle.
+    #[must_use]
+    pub fn state(&self) -> RobocopyJobState {
+        self.app.job_state()
+    }
+
+    /// Why the parser gave up on the log; nothing more is read from it until
+    /// the file is truncated or replaced.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// State behind the `robocopy-logs-tui` screen when it watches a directory
+/// or glob: one row per log, and the single-log screen for the one opened.
+#[derive(Debug)]
+pub struct RobocopyLogsDashboard {
+    logs: Vec<DashboardLog>,
+    follow: bool,
+    progress: RobocopyProgress,
+    selected: usize,
+    open: Option<usize>,
+    should_quit: bool,
+}
+
+impl RobocopyLogsDashboard {
+    /// `follow` and `progress` are handed to the screen of every log.
+    #[must_use]
+    pub fn new(follow: bool, progress: RobocopyProgress) -> Self {
+        Self {
+            logs: Vec::new(),
+            follow,
+            progress,
+            selected: 0,
+            open: None,
+            should_quit: false,
+        }
+    }
+
+    /// Take in whatever the directory tailer observed.
+    ///
+    /// A log the parser rejects shows the error in its row and is left alone
+    /// while the others carry on.
+    pub fn push_event(&mut self, event: LogDirectoryEvent, now: Instant) {
+        match event {
+            LogDirectoryEvent::Added { path, .. } => self.logs.push(DashboardLog {
+                path,
+                parser: RobocopyLogParser::new(),
+                app: RobocopyLogsApp::new(self.follow, self.progress.clone()),
+                error: None,
+            }),
+            LogDirectoryEvent::CaughtUp { log } => {
+                if let Some(log) = self.logs.get_mut(log) {
+                    log.app.restart_clock(now);
+                    if let Ok(modified) =
+                        std::fs::metadata(&log.path).and_then(|metadata| metadata.modified())
+                    {
+                        log.app.set_last_output(modified);
+                    }
+                }
+            }
+            LogDirectoryEvent::Tail { log, event } => {
+                let Some(log) = self.logs.get_mut(log) else {
+                    return;
+                };
+                if event.is_reset() {
+                    log.app.reset();
+                    log.error = None;
+                } else if log.error.is_some() {
+                    return;
+                }
+                event.feed(&mut log.parser);
+                loop {
+                    match log.parser.advance() {
+                        Ok(RobocopyParseAdvance::NeedMoreData) => break,
+                        Ok(advance) => log.app.push_advance(advance, now),
+                        Err(error) => {
+                            warn!("Stopped reading {}: {error:?}", log.path.display());
+                            log.error = Some(error.to_string());
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the current throughput of every log; call about once a second.
+    pub fn sample_rate(&mut self, now: Instant) {
+        for log in &mut self.logs {
+            log.app.sample_rate(now);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.should_quit = true;
+            return;
+        }
+        if let Some(app) = self.open_log_mut() {
+            app.handle_key(key);
+            // Quitting a log goes back to the dashboard
+            if app.should_quit() {
+                app.cancel_quit();
+                self.open = None;
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.logs.len().saturating_sub(1));
+            }
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                self.selected = self.logs.len().saturating_sub(1);
+            }
+            KeyCode::Enter if self.selected < self.logs.len() => self.open = Some(self.selected),
+            _ => {}
+        }
+    }
+
+    #[must_use]
+    pub fn logs(&self) -> &[DashboardLog] {
+        &self.logs
+    }
+
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// The screen of the log opened from the dashboard, if any.
+    pub fn open_log_mut(&mut self) -> Option<&mut RobocopyLogsApp> {
+        let open = self.open?;
+        self.logs.get_mut(open).map(|log| &mut log.app)
+    }
+
+    /// Whether the open log is paused, in which case the caller stops reading
+    /// every log so the screen holds still.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.open
+            .and_then(|open| self.logs.get(open))
+            .is_some_and(|log| log.app.is_paused())
+    }
+
+    #[must_use]
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::tail::tail_event::TailEvent;
+
+    fn press(dashboard: &mut RobocopyLogsDashboard, code: KeyCode) {
+        dashboard.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn tracks_each_log_and_opens_one() {
+        let mut dashboard = RobocopyLogsDashboard::new(true, RobocopyProgress::new());
+        let log = RobocopyLogGenerator::new(RobocopyLogGeneratorConfig {
+            files: 20,
+            ..Default::default()
+        })
+        .generate_log()
+        .to_string();
+        let now = Instant::now();
+        for (index, name) in ["a.log", "b.log"].into_iter().enumerate() {
+            dashboard.push_event(
+                LogDirectoryEvent::Added {
+                    log: index,
+                    path: name.into(),
+                },
+                now,
+            );
+        }
+        assert!(
+            dashboard
+                .logs()
+                .iter()
+                .all(|log| log.app().header().is_none())
+        );
+        // Only the first job has finished
+        let (running, _) = log.split_at(log.find("Total    Copied").expect("has a summary"));
+        for (index, text) in [log.as_str(), running].into_iter().enumerate() {
+            dashboard.push_event(
+                LogDirectoryEvent::Tail {
+                    log: index,
+                    event: TailEvent::Data(text.as_bytes().to_vec()),
+                },
+                now,
+            );
+        }
+        let states: Vec<_> = dashboard.logs().iter().map(DashboardLog::state).collect();
+        assert_eq!(
+            states,
+            [RobocopyJobState::Finished, RobocopyJobState::Running]
+        );
+
+        press(&mut dashboard, KeyCode::Down);
+        press(&mut dashboard, KeyCode::Enter);
+        let app = dashboard.open_log_mut().expect("a log is open");
+        assert!(app.summary().is_none());
+        press(&mut dashboard, KeyCode::Char('q'));
+        assert!(dashboard.open_log_mut().is_none());
+        assert!(!dashboard.should_quit());
+        press(&mut dashboard, KeyCode::Char('q'));
+        assert!(dashboard.should_quit());
+    }
+
+    #[test]
+    fn starts_a_log_over_when_it_is_replaced() {
+        let mut dashboard = RobocopyLogsDashboard::new(true, RobocopyProgress::new());
+        let log = RobocopyLogGenerator::new(RobocopyLogGeneratorConfig {
+            files: 20,
+            ..Default::default()
+        })
+        .generate_log()
+        .to_string();
+        let now = Instant::now();
+        dashboard.push_event(
+            LogDirectoryEvent::Added {
+                log: 0,
+                path: "a.log".into(),
+            },
+            now,
+        );
+        dashboard.push_event(
+            LogDirectoryEvent::Tail {
+                log: 0,
+                event: TailEvent::Data(log.as_bytes().to_vec()),
+            },
+            now,
+        );
+        let entries = dashboard.logs()[0].app().entry_count();
+        assert!(entries > 0);
+        for reset in [TailEvent::Truncated, TailEvent::Rotated] {
+            dashboard.push_event(
+                LogDirectoryEvent::Tail {
+                    log: 0,
+                    event: reset,
+                },
+                now,
+            );
+            let app = dashboard.logs()[0].app();
+            assert_eq!(app.entry_count(), 0);
+            assert!(app.header().is_none());
+            dashboard.push_event(
+                LogDirectoryEvent::Tail {
+                    log: 0,
+                    event: TailEvent::Data(log.as_bytes().to_vec()),
+                },
+                now,
+            );
+            assert_eq!(dashboard.logs()[0].app().entry_count(), entries);
+        }
+    }
+
+    #[test]
+    fn stops_reading_a_log_it_cannot_parse_until_it_is_replaced() {
+        let mut dashboard = RobocopyLogsDashboard::new(true, RobocopyProgress::new());
+        let sample = include_bytes!("../robocopy/sample.txt").to_vec();
+        let now = Instant::now();
+        dashboard.push_event(
+            LogDirectoryEvent::Added {
+                log: 0,
+                path: "a.log".into(),
+            },
+            now,
+        );
+        for event in [
+            TailEvent::Data(sample.clone()),
+            TailEvent::Data(b"\t    New File  \t\t  lots\tJ:\\x\r\n".to_vec()),
+            TailEvent::Data(sample.clone()),
+        ] {
+            dashboard.push_event(LogDirectoryEvent::Tail { log: 0, event }, now);
+        }
+        let log = &dashboard.logs()[0];
+        assert!(log.error().is_some());
+        let entries = log.app().entry_count();
+
+        dashboard.push_event(
+            LogDirectoryEvent::Tail {
+                log: 0,
+                event: TailEvent::Rotated,
+            },
+            now,
+        );
+        dashboard.push_event(
+            LogDirectoryEvent::Tail {
+                log: 0,
+                event: TailEvent::Data(sample),
+            },
+            now,
+        );
+        let log = &dashboard.logs()[0];
+        assert!(log.error().is_none());
+        assert_eq!(log.app().entry_count(), entries);
+    }
+}
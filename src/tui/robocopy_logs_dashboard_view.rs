@@ -0,0 +1,263 @@
+use crate::robocopy::robocopy_job_status::RobocopyJobState;
+use crate::tui::robocopy_logs_dashboard::DashboardLog;
+use crate::tui::robocopy_logs_dashboard::RobocopyLogsDashboard;
+use crate::tui::robocopy_logs_view;
+use humansize::BINARY;
+use humansize::format_size;
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::Cell;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Row;
+use ratatui::widgets::Table;
+use ratatui::widgets::TableState;
+use thousands::Separable;
+use uom::si::information::byte;
+
+/// Draw the dashboard, or the screen of the log opened from it.
+pub fn render(frame: &mut Frame, dashboard: &mut RobocopyLogsDashboard) {
+    if let Some(app) = dashboard.open_log_mut() {
+        robocopy_logs_view::render(frame, app);
+        return;
+    }
+    let [table_area, footer_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+    let mut bytes = 0;
+    let mut rate = 0.0;
+    let mut errors = 0;
+    let rows: Vec<Row> = dashboard
+        .logs()
+        .iter()
+        .map(|log| {
+            let snapshot = log.app().progress();
+            bytes += snapshot.bytes_done.get::<byte>();
+            rate += snapshot.bytes_per_second.unwrap_or(0.0);
+            errors += log.app().errors().len();
+            log_row(log)
+        })
+        .collect();
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "rates are non-negative and approximate"
+    )]
+    let title = format!(
+        " robocopy logs {}  {}  {}/s  {} errors ",
+        dashboard.logs().len(),
+        format_size(bytes, BINARY),
+        format_size(rate as u64, BINARY),
+        errors.separate_with_commas()
+    );
+    let header = Row::new([
+        "Log",
+        "Status",
+        "Source",
+        "Dest",
+        "Files",
+        "Copied",
+        "Rate",
+        "Errors",
+        "Current file",
+    ])
+    .style(Style::new().bold());
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(16),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(9),
+            Constraint::Length(11),
+            Constraint::Length(12),
+            Constraint::Length(7),
+            Constraint::Fill(2),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(title))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(Some(dashboard.selected()));
+    frame.render_stateful_widget(table, table_area, &mut state);
+
+    let footer = if dashboard.logs().is_empty() {
+        Line::from("Waiting for logs to appear...  q quit".dim())
+    } else {
+        Line::from("q quit  ↑↓ select  Enter open log (q returns here)".dim())
+    };
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn log_row(log: &DashboardLog) -> Row<'static> {
+    let app = log.app();
+    let snapshot = app.progress();
+    let state = log.state();
+    let state_style = match state {
+        RobocopyJobState::Running => Style::new().fg(Color::Green),
+        RobocopyJobState::WaitingOnRetry => Style::new().fg(Color::Yellow),
+        RobocopyJobState::Stalled => Style::new().fg(Color::LightRed),
+        RobocopyJobState::Finished => Style::new().fg(Color::Cyan),
+        RobocopyJobState::Abandoned => Style::new().fg(Color::Red).bold(),
+    };
+    let (source, dest) = app.header().map_or_else(Default::default, |header| {
+        (
+            header.source.display().to_string(),
+            header.dest.display().to_string(),
+        )
+    });
+    let error_count = app.errors().len();
+    let errors = Cell::from(error_count.separate_with_commas()).style(if error_count > 0 {
+        Style::new().fg(Color::Red)
+    } else {
+        Style::new()
+    });
+    // A log the parser gave up on shows why in place of the file in flight
+    let (status, current) = if let Some(error) = log.error() {
+        (
+            Cell::from("parse error").style(Style::new().fg(Color::Red).bold()),
+            Cell::from(error.to_string()).style(Style::new().fg(Color::Red)),
+        )
+    } else {
+        let current = snapshot
+            .current_file
+            .as_ref()
+            .map_or_else(String::new, |file| {
+                format!("{:>3}% {}", file.percent.unwrap_or(0), file.path.display())
+            });
+        (
+            Cell::from(state.name()).style(state_style),
+            Cell::from(current),
+        )
+    };
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "rates are non-negative and approximate"
+    )]
+    let rate = snapshot.bytes_per_second.map_or_else(String::new, |rate| {
+        format!("{}/s", format_size(rate as u64, BINARY))
+    });
+    Row::new([
+        Cell::from(log.path.file_name().map_or_else(
+            || log.path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        )),
+        status,
+        Cell::from(source),
+        Cell::from(dest),
+        Cell::from(snapshot.files_done.separate_with_commas()),
+        Cell::from(format_size(snapshot.bytes_done.get::<byte>(), BINARY)),
+        Cell::from(rate),
+        errors,
+        current,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_progress::RobocopyProgress;
+    use crate::tail::log_directory_tailer::LogDirectoryEvent;
+    use crate::tail::tail_event::TailEvent;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use std::time::Instant;
+
+    #[test]
+    fn renders_a_row_per_log() -> eyre::Result<()> {
+        let mut dashboard = RobocopyLogsDashboard::new(true, RobocopyProgress::new());
+        let text = RobocopyLogGenerator::new(RobocopyLogGeneratorConfig {
+            files: 20,
+            ..Default::default()
+        })
+        .generate_log()
+        .to_string();
+        for (log, name) in ["share-a.log", "share-b.log"].into_iter().enumerate() {
+            dashboard.push_event(
+                LogDirectoryEvent::Added {
+                    log,
+                    path: name.into(),
+                },
+                Instant::now(),
+            );
+        }
+        dashboard.push_event(
+            LogDirectoryEvent::Tail {
+                log: 0,
+                event: TailEvent::Data(text.into_bytes()),
+            },
+            Instant::now(),
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 10))?;
+        terminal.draw(|frame| render(frame, &mut dashboard))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(screen.contains("robocopy logs 2"), "{screen}");
+        assert!(screen.contains("share-a.log"));
+        assert!(screen.contains("finished"));
+        assert!(screen.contains("share-b.log"));
+        assert!(screen.contains("running"));
+        assert!(screen.contains(r"C:\source\"));
+        Ok(())
+    }
+
+    #[test]
+    fn shows_why_a_log_stopped_being_read() -> eyre::Result<()> {
+        let mut dashboard = RobocopyLogsDashboard::new(true, RobocopyProgress::new());
+        for (log, name) in ["broken.log", "fine.log"].into_iter().enumerate() {
+            dashboard.push_event(
+                LogDirectoryEvent::Added {
+                    log,
+                    path: name.into(),
+                },
+                Instant::now(),
+            );
+        }
+        let mut broken = include_bytes!("../robocopy/sample.txt").to_vec();
+        broken.extend_from_slice(b"\t    New File  \t\t  lots\tJ:\\x\r\n");
+        for (log, text) in [broken, include_bytes!("../robocopy/sample.txt").to_vec()]
+            .into_iter()
+            .enumerate()
+        {
+            dashboard.push_event(
+                LogDirectoryEvent::Tail {
+                    log,
+                    event: TailEvent::Data(text),
+                },
+                Instant::now(),
+            );
+        }
+        assert!(dashboard.logs()[0].error().is_some());
+        assert!(dashboard.logs()[1].error().is_none());
+
+        let mut terminal = Terminal::new(TestBackend::new(200, 10))?;
+        terminal.draw(|frame| render(frame, &mut dashboard))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(screen.contains("parse error"), "{screen}");
+        assert!(screen.contains("Failed to parse New File line"), "{screen}");
+        assert!(screen.contains("running"), "{screen}");
+        Ok(())
+    }
+}
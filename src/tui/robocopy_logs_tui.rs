@@ -0,0 +1,166 @@
+use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+use crate::robocopy::robocopy_progress::RobocopyProgress;
+use crate::tail::TailEvent;
+use crate::tail::log_directory_tailer::LogDirectoryEvent;
+use crate::tail::log_directory_tailer::LogDirectoryTailer;
+use crate::tail::log_directory_tailer::LogWatchTarget;
+use crate::tail::log_tailer::default_log_tailer;
+use crate::tui::robocopy_logs_app::RobocopyLogsApp;
+use crate::tui::robocopy_logs_dashboard::RobocopyLogsDashboard;
+use crate::tui::robocopy_logs_dashboard_view;
+use crate::tui::robocopy_logs_view::render;
+use crossbeam_channel::Receiver;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event;
+use ratatui::crossterm::event::Event;
+use ratatui::crossterm::event::KeyEventKind;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::info;
+
+/// How long to wait for a key press before checking the log again.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the throughput sparkline gets a new sample.
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Follow a robocopy log in an interactive terminal UI until the user quits.
+///
+/// The existing contents are read before the first frame so the rate only
+/// reflects copying that happens while watching. With `follow` the entry
+/// list starts at, and sticks to, the newest entry.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be tailed or parsed, or the terminal fails.
+pub fn run_robocopy_logs_tui(
+    path: &Path,
+    follow: bool,
+    progress: RobocopyProgress,
+) -> eyre::Result<()> {
+    // The parser must see the file from the beginning to track its state
+    let rx = default_log_tailer().tail(path)?;
+    let mut parser = RobocopyLogParser::new();
+    let mut app = RobocopyLogsApp::new(follow, progress);
+
+    info!("Catching up on {}", path.display());
+    while let Ok(event) = rx.recv_timeout(POLL_INTERVAL) {
+        apply(&mut parser, &mut app, event)?;
+        if rx.is_empty() {
+            break;
+        }
+    }
+    app.restart_clock(Instant::now());
+    if let Ok(modified) = std::fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        app.set_last_output(modified);
+    }
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &rx, &mut parser, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    rx: &Receiver<TailEvent>,
+    parser: &mut RobocopyLogParser,
+    app: &mut RobocopyLogsApp,
+) -> eyre::Result<()> {
+    let mut last_sample = Instant::now();
+    while !app.should_quit() {
+        app.tick_finder();
+        terminal.draw(|frame| render(frame, app))?;
+        if event::poll(POLL_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key);
+        }
+        if app.is_paused() {
+            continue;
+        }
+        // Leaving events in the channel while paused keeps the screen still
+        while let Ok(event) = rx.try_recv() {
+            apply(parser, app, event)?;
+        }
+        if last_sample.elapsed() >= RATE_SAMPLE_INTERVAL {
+            app.sample_rate(Instant::now());
+            last_sample = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+/// Follow every log matching `target` on a dashboard until the user quits,
+/// picking up logs created while it runs.
+///
+/// Each log is caught up on separately, so its rate only reflects copying
+/// that happens while watching. A log that cannot be parsed shows the error
+/// in its row rather than closing the dashboard.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be listed or the terminal fails.
+pub fn run_robocopy_logs_dashboard(
+    target: LogWatchTarget,
+    follow: bool,
+    progress: RobocopyProgress,
+) -> eyre::Result<()> {
+    info!("Watching {}", target.directory().display());
+    let rx = LogDirectoryTailer::default().watch(target)?;
+    let mut dashboard = RobocopyLogsDashboard::new(follow, progress);
+    let mut terminal = ratatui::init();
+    let result = dashboard_event_loop(&mut terminal, &rx, &mut dashboard);
+    ratatui::restore();
+    result
+}
+
+fn dashboard_event_loop(
+    terminal: &mut DefaultTerminal,
+    rx: &Receiver<LogDirectoryEvent>,
+    dashboard: &mut RobocopyLogsDashboard,
+) -> eyre::Result<()> {
+    let mut last_sample = Instant::now();
+    while !dashboard.should_quit() {
+        if let Some(app) = dashboard.open_log_mut() {
+            app.tick_finder();
+        }
+        terminal.draw(|frame| robocopy_logs_dashboard_view::render(frame, dashboard))?;
+        if event::poll(POLL_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            dashboard.handle_key(key);
+        }
+        if dashboard.is_paused() {
+            continue;
+        }
+        while let Ok(event) = rx.try_recv() {
+            dashboard.push_event(event, Instant::now());
+        }
+        if last_sample.elapsed() >= RATE_SAMPLE_INTERVAL {
+            dashboard.sample_rate(Instant::now());
+            last_sample = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+fn apply(
+    parser: &mut RobocopyLogParser,
+    app: &mut RobocopyLogsApp,
+    event: TailEvent,
+) -> eyre::Result<()> {
+    if event.is_reset() {
+        app.reset();
+    }
+    event.feed(parser);
+    loop {
+        match parser.advance()? {
+            RobocopyParseAdvance::NeedMoreData => return Ok(()),
+            advance => app.push_advance(advance, Instant::now()),
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use crate::robocopy::robocopy_job_status::RobocopyJobState;
+use crate::robocopy::robocopy_log_entry::RobocopyDirectoryClass;
+use crate::robocopy::robocopy_log_entry::RobocopyFileClass;
+use crate::robocopy::robocopy_log_entry::RobocopyLogEntry;
+use crate::robocopy::robocopy_path_tree::RobocopyPathNodeKind;
+use crate::robocopy::robocopy_size::format_robocopy_size;
+use crate::robocopy::robocopy_summary::RobocopySummaryColumn;
+use crate::robocopy::robocopy_win32_error::win32_error_info;
+use crate::tui::robocopy_logs_app::EntryKindFilter;
+use crate::tui::robocopy_logs_app::InputMode;
+use crate::tui::robocopy_logs_app::Pane;
+use crate::tui::robocopy_logs_app::RobocopyLogsApp;
+use humansize::BINARY;
+use humansize::format_size;
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Gauge;
+use ratatui::widgets::LineGauge;
+use ratatui::widgets::List;
+use ratatui::widgets::ListItem;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Sparkline;
+use uom::si::information::byte;
+
+/// Draw the whole screen.
+pub fn render(frame: &mut Frame, app: &mut RobocopyLogsApp) {
+    let in_flight_height = match app.in_flight().len() {
+        0 => 0,
+        n => u16::try_from(n).unwrap_or(u16::MAX).saturating_add(2),
+    };
+    let [
+        header_area,
+        progress_area,
+        in_flight_area,
+        body_area,
+        rate_area,
+        footer_area,
+    ] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Length(3),
+        Constraint::Length(in_flight_height),
+        Constraint::Min(5),
+        Constraint::Length(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [entries_area, errors_area] =
+        Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .areas(body_area);
+
+    render_header(frame, app, header_area);
+    render_progress(frame, app, progress_area);
+    render_in_flight(frame, app, in_flight_area);
+    match app.pane() {
+        Pane::Entries => render_entries(frame, app, entries_area),
+        Pane::Tree => render_tree(frame, app, entries_area),
+    }
+    render_errors(frame, app, errors_area);
+    render_rate(frame, app, rate_area);
+    render_footer(frame, app, footer_area);
+    if app.input_mode() == InputMode::Finding {
+        render_finder(frame, app);
+    }
+}
+
+fn render_header(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    let mut status = Vec::new();
+    if app.is_paused() {
+        status.push(" PAUSED ".black().on_yellow());
+    }
+    if app.is_following() {
+        status.push(" FOLLOW ".black().on_green());
+    }
+    let state = app.job_state();
+    let label = format!(" {} ", state.name().replace('-', " ").to_uppercase());
+    status.push(match state {
+        RobocopyJobState::Running => label.black().on_blue(),
+        RobocopyJobState::WaitingOnRetry => label.black().on_yellow(),
+        RobocopyJobState::Stalled => label.black().on_light_red(),
+        RobocopyJobState::Finished => label.black().on_cyan(),
+        RobocopyJobState::Abandoned => label.white().on_red(),
+    });
+    let block = Block::bordered()
+        .title(" robocopy ")
+        .title(Line::from(status).right_aligned());
+    let label = |name: &str| Span::styled(format!("{name:>8} : "), Style::new().dim());
+    let lines = match app.header() {
+        Some(header) => vec![
+            Line::from(vec![label("Started"), header.started.to_string().into()]),
+            Line::from(vec![
+                label("Source"),
+                header.source.display().to_string().into(),
+            ]),
+            Line::from(vec![
+                label("Dest"),
+                header.dest.display().to_string().into(),
+            ]),
+            Line::from(vec![
+                label("Options"),
+                format!("{} {}", header.files, header.options).into(),
+            ]),
+        ],
+        None => vec![Line::from("Waiting for a header...".dim())],
+    };
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn render_progress(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    let snapshot = app.progress();
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(" Progress "))
+        .gauge_style(Style::new().fg(Color::Green))
+        .ratio(snapshot.fraction().unwrap_or(0.0))
+        .label(snapshot.to_string());
+    frame.render_widget(gauge, area);
+}
+
+fn render_in_flight(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    if area.height == 0 {
+        return;
+    }
+    let block = Block::bordered().title(" In flight ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let rows = Layout::vertical(vec![Constraint::Length(1); app.in_flight().len()]).split(inner);
+    for (file, row) in app.in_flight().iter().zip(rows.iter()) {
+        let percent = file.percent.unwrap_or(0);
+        let gauge = LineGauge::default()
+            .filled_style(Style::new().fg(Color::Cyan))
+            .ratio(f64::from(percent.min(100)) / 100.0)
+            .label(format!(
+                "{percent:>3}% {:>10} {}",
+                format_robocopy_size(file.size),
+                file.path.display()
+            ));
+        frame.render_widget(gauge, *row);
+    }
+}
+
+fn render_entries(frame: &mut Frame, app: &mut RobocopyLogsApp, area: Rect) {
+    let mut title = vec![format!(
+        " Entries {}/{} ",
+        app.visible_len(),
+        app.entry_count()
+    )];
+    if app.kind_filter() != EntryKindFilter::All {
+        title.push(format!("[{}] ", app.kind_filter().label()));
+    }
+    if !app.text_filter().is_empty() {
+        title.push(format!("/{} ", app.text_filter()));
+    }
+    let block = Block::bordered().title(title.concat());
+    let height = usize::from(block.inner(area).height);
+    let window = app.scroll_window(height);
+    let selected = app.selected();
+    let items: Vec<ListItem> = window
+        .filter_map(|position| {
+            let entry = app.visible_entry(position)?;
+            let mut item = ListItem::new(entry_line(entry));
+            if position == selected {
+                item = item.style(Style::new().add_modifier(Modifier::REVERSED));
+            }
+            Some(item)
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn render_tree(frame: &mut Frame, app: &mut RobocopyLogsApp, area: Rect) {
+    let block = Block::bordered().title(format!(
+        " Tree {} paths ",
+        app.tree_pane().tree().len().saturating_sub(1)
+    ));
+    let height = usize::from(block.inner(area).height);
+    let (rows, selected) = app.tree_pane_mut().window(height);
+    let tree = app.tree_pane().tree();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let node = tree.node(row.node);
+            let marker = match (node.kind, row.expanded) {
+                (RobocopyPathNodeKind::File, _) => "  ",
+                (_, true) => "▾ ",
+                (_, false) if node.children.is_empty() => "  ",
+                (_, false) => "▸ ",
+            };
+            let errors = if node.stats.errors > 0 {
+                Span::styled(format!("{:>6}", node.stats.errors), Color::Red)
+            } else {
+                Span::raw(format!("{:>6}", ""))
+            };
+            let name_style = match node.kind {
+                RobocopyPathNodeKind::Directory => Style::new().fg(Color::Blue),
+                RobocopyPathNodeKind::File => Style::new(),
+            };
+            let line = Line::from(vec![
+                format!(
+                    "{:>10} {:>8}",
+                    format_size(node.stats.bytes_copied.get::<byte>(), BINARY),
+                    node.stats.file_count()
+                )
+                .into(),
+                errors,
+                format!("  {}{marker}", "  ".repeat(row.depth)).into(),
+                Span::styled(node.name.clone(), name_style),
+            ]);
+            let mut item = ListItem::new(line);
+            if index == selected {
+                item = item.style(Style::new().add_modifier(Modifier::REVERSED));
+            }
+            item
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn render_errors(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    let block = Block::bordered()
+        .title(format!(" Errors {} ", app.errors().len()))
+        .border_style(if app.errors().len() > 0 {
+            Style::new().fg(Color::Red)
+        } else {
+            Style::new()
+        });
+    let height = usize::from(block.inner(area).height);
+    let mut items: Vec<ListItem> = app
+        .errors()
+        .rev()
+        .take(height)
+        .map(|entry| ListItem::new(error_line(entry)))
+        .collect();
+    items.reverse();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn render_rate(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    let latest = app.rate_history().back().copied().unwrap_or(0);
+    let block = Block::bordered().title(format!(" Throughput {}/s ", format_size(latest, BINARY)));
+    // Show the most recent samples that fit
+    let width = usize::from(block.inner(area).width);
+    let data: Vec<u64> = app
+        .rate_history()
+        .iter()
+        .skip(app.rate_history().len().saturating_sub(width))
+        .copied()
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(block)
+        .style(Style::new().fg(Color::Cyan))
+        .data(&data);
+    frame.render_widget(sparkline, area);
+}
+
+fn render_footer(frame: &mut Frame, app: &RobocopyLogsApp, area: Rect) {
+    let line = match app.input_mode() {
+        InputMode::EditingFilter => Line::from(vec![
+            "Filter: ".bold(),
+            app.text_filter().into(),
+            "█".into(),
+            "  (Enter keep, Esc clear)".dim(),
+        ]),
+        InputMode::Finding => {
+            Line::from("type to search  ↑↓ select  Enter jump to entry  Esc close".dim())
+        }
+        InputMode::Normal => Line::from(
+            "q quit  p pause  f follow  / filter  Tab kind  s find  t tree  ←→ fold  ↑↓ PgUp PgDn scroll"
+                .dim(),
+        ),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// The fuzzy path finder, drawn over the middle of the screen.
+fn render_finder(frame: &mut Frame, app: &mut RobocopyLogsApp) {
+    let [_, area, _] = Layout::vertical([
+        Constraint::Percentage(15),
+        Constraint::Percentage(70),
+        Constraint::Percentage(15),
+    ])
+    .areas(frame.area());
+    let [_, area, _] = Layout::horizontal([
+        Constraint::Percentage(10),
+        Constraint::Percentage(80),
+        Constraint::Percentage(10),
+    ])
+    .areas(area);
+    frame.render_widget(Clear, area);
+    let block = Block::bordered().title(format!(
+        " Find path {}/{} ",
+        app.finder().match_count(),
+        app.finder().item_count()
+    ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [input_area, results_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+    let input = Line::from(vec![
+        "> ".bold(),
+        app.finder().query().to_string().into(),
+        "█".into(),
+    ]);
+    frame.render_widget(Paragraph::new(input), input_area);
+
+    let (matches, selected) = app.finder_window(u32::from(results_area.height));
+    let highlight = Style::new().fg(Color::Yellow).bold();
+    let items: Vec<ListItem> = matches
+        .iter()
+        .enumerate()
+        .map(|(row, found)| {
+            let mut spans = vec![
+                app.entry(found.item.entry)
+                    .map_or_else(Span::default, outcome_span),
+                " ".into(),
+            ];
+            let mut highlights = found.highlights.iter().peekable();
+            for (index, c) in found.item.path.chars().enumerate() {
+                if highlights.next_if(|h| **h as usize == index).is_some() {
+                    spans.push(Span::styled(c.to_string(), highlight));
+                } else {
+                    spans.push(Span::raw(c.to_string()));
+                }
+            }
+            let mut item = ListItem::new(Line::from(spans));
+            if row == selected {
+                item = item.style(Style::new().add_modifier(Modifier::REVERSED));
+            }
+            item
+        })
+        .collect();
+    frame.render_widget(List::new(items), results_area);
+}
+
+/// What happened to a path, in a fixed-width column.
+fn outcome_span(entry: &RobocopyLogEntry) -> Span<'static> {
+    match entry {
+        RobocopyLogEntry::NewFile { .. } | RobocopyLogEntry::File { .. } => {
+            let class = entry.file_class().unwrap_or(RobocopyFileClass::NewFile);
+            Span::styled(format!("{:<12}", class.label()), class_style(class))
+        }
+        RobocopyLogEntry::Directory { class, .. } => {
+            let label = match class {
+                RobocopyDirectoryClass::Existing => "Dir",
+                class => class.label(),
+            };
+            Span::styled(format!("{label:<12}"), Style::new().fg(Color::Blue))
+        }
+        _ => match entry.error_code() {
+            Some(code) => Span::styled(format!("{:<12}", format!("ERROR {code}")), Color::Red),
+            None => Span::raw(format!("{:<12}", "")),
+        },
+    }
+}
+
+fn class_style(class: RobocopyFileClass) -> Style {
+    match class.summary_column() {
+        RobocopySummaryColumn::Copied => Style::new().fg(Color::Green),
+        RobocopySummaryColumn::Skipped => Style::new().dim(),
+        RobocopySummaryColumn::Mismatch | RobocopySummaryColumn::Failed => {
+            Style::new().fg(Color::Red)
+        }
+        RobocopySummaryColumn::Extras => Style::new().fg(Color::Yellow),
+    }
+}
+
+/// One row of the entry list.
+fn entry_line(entry: &RobocopyLogEntry) -> Line<'static> {
+    match entry {
+        RobocopyLogEntry::NewFile { size, path, .. }
+        | RobocopyLogEntry::File { size, path, .. } => Line::from(vec![
+            outcome_span(entry),
+            format!("{:>10}  ", format_robocopy_size(*size)).into(),
+            path.display().to_string().into(),
+        ]),
+        RobocopyLogEntry::Directory {
+            class,
+            file_count,
+            path,
+        } => {
+            let style = match class {
+                RobocopyDirectoryClass::Existing => Style::new().fg(Color::Blue),
+                RobocopyDirectoryClass::New => Style::new().fg(Color::Blue).bold(),
+                RobocopyDirectoryClass::Extra => Style::new().fg(Color::Yellow),
+            };
+            let label = match class {
+                RobocopyDirectoryClass::Existing => "Dir",
+                class => class.label(),
+            };
+            Line::from(vec![
+                Span::styled(format!("{label:<12}"), style),
+                format!("{file_count:>10}  ").into(),
+                Span::styled(path.display().to_string(), style),
+            ])
+        }
+        RobocopyLogEntry::Retry { wait } => {
+            Line::from(format!("Waiting {} seconds... Retrying...", wait.as_secs()).yellow())
+        }
+        RobocopyLogEntry::Error { .. }
+        | RobocopyLogEntry::AccessDeniedError { .. }
+        | RobocopyLogEntry::RetryLimitExceeded => error_line(entry),
+    }
+}
+
+/// One row of the error panel.
+fn error_line(entry: &RobocopyLogEntry) -> Line<'static> {
+    let red = Style::new().fg(Color::Red);
+    match (entry.error_code(), entry.path()) {
+        (Some(code), Some(path)) => {
+            let name = win32_error_info(code).map_or("", |info| info.name);
+            Line::from(vec![
+                Span::styled(format!("ERROR {code} {name} "), red),
+                path.display().to_string().into(),
+            ])
+        }
+        _ => Line::from(Span::styled("ERROR: RETRY LIMIT EXCEEDED.", red)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGenerator;
+    use crate::robocopy::robocopy_log_generator::RobocopyLogGeneratorConfig;
+    use crate::robocopy::robocopy_log_parser::RobocopyLogParser;
+    use crate::robocopy::robocopy_log_parser::RobocopyParseAdvance;
+    use crate::robocopy::robocopy_progress::RobocopyProgress;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyCode;
+    use ratatui::crossterm::event::KeyEvent;
+    use ratatui::crossterm::event::KeyModifiers;
+    use std::time::Instant;
+
+    #[test]
+    fn renders_header_entries_and_errors() -> eyre::Result<()> {
+        let config = RobocopyLogGeneratorConfig {
+            files: 20,
+            error_percent: 30,
+            ..Default::default()
+        };
+        let text = RobocopyLogGenerator::new(config).generate_log().to_string();
+        let mut parser = RobocopyLogParser::new();
+        parser.accept(&text);
+        let mut app = RobocopyLogsApp::new(true, RobocopyProgress::new());
+        loop {
+            let advance = parser.advance()?;
+            if advance == RobocopyParseAdvance::NeedMoreData {
+                break;
+            }
+            app.push_advance(advance, Instant::now());
+        }
+        app.sample_rate(Instant::now());
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40))?;
+        terminal.draw(|frame| render(frame, &mut app))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(screen.contains(r"C:\source\"), "{screen}");
+        assert!(screen.contains(r"D:\dest\"));
+        assert!(screen.contains("FINISHED"));
+        assert!(screen.contains(&format!("Entries {0}/{0}", app.entry_count())));
+        assert!(screen.contains(&format!("Errors {}", app.errors().len())));
+
+        // The tree pane replaces the entry list and starts with the drive open
+        app.handle_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        terminal.draw(|frame| render(frame, &mut app))?;
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+        assert!(screen.contains(" Tree "), "{screen}");
+        assert!(screen.contains(r"▾ C:"));
+        assert!(screen.contains("▸ source"));
+        Ok(())
+    }
+}